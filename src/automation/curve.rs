@@ -0,0 +1,135 @@
+//! A small library of automation curve shapes, plus sample-accurate
+//! rendering of a segment between two automation points.
+
+/// The shape of an automation segment between two points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveShape {
+    /// Holds the start value until the very last sample, which jumps to the
+    /// end value.
+    Step,
+    /// Constant-rate interpolation between the two values.
+    Linear,
+    /// Exponential easing. `curvature` is in `(-1.0, 1.0)`: negative values
+    /// ease out, positive values ease in, `0.0` is equivalent to `Linear`.
+    Exponential { curvature: f32 },
+    /// A cubic Bezier easing curve with control points `(x1, y1)` and
+    /// `(x2, y2)`, in the same style as CSS `cubic-bezier()`.
+    Bezier { x1: f32, y1: f32, x2: f32, y2: f32 },
+}
+
+impl CurveShape {
+    /// Evaluates the normalized (0..=1) ease value at normalized time `t`
+    /// (0..=1). The result is not clamped to `[0, 1]` for overshooting
+    /// Bezier curves.
+    pub fn ease(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            CurveShape::Step => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            CurveShape::Linear => t,
+            CurveShape::Exponential { curvature } => {
+                let k = *curvature as f64;
+                if k.abs() < 1e-6 {
+                    t
+                } else {
+                    // Maps t through (e^(k*t) - 1) / (e^k - 1), which passes
+                    // through (0, 0) and (1, 1) for any nonzero k.
+                    (k * t).exp_m1() / k.exp_m1()
+                }
+            }
+            CurveShape::Bezier { x1, y1, x2, y2 } => {
+                cubic_bezier_y_at_x(t, *x1 as f64, *y1 as f64, *x2 as f64, *y2 as f64)
+            }
+        }
+    }
+}
+
+/// Solves for `y` at a given `x` on a cubic Bezier curve from `(0,0)` to
+/// `(1,1)` with control points `(x1, y1)` and `(x2, y2)`, via bisection on
+/// the parametric `x(u)`.
+fn cubic_bezier_y_at_x(x: f64, x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    let bezier = |u: f64, p1: f64, p2: f64| {
+        let mu = 1.0 - u;
+        3.0 * mu * mu * u * p1 + 3.0 * mu * u * u * p2 + u * u * u
+    };
+
+    let mut lo = 0.0;
+    let mut hi = 1.0;
+    let mut u = x;
+    for _ in 0..32 {
+        u = (lo + hi) * 0.5;
+        let cur_x = bezier(u, x1, x2);
+        if cur_x < x {
+            lo = u;
+        } else {
+            hi = u;
+        }
+    }
+    bezier(u, y1, y2)
+}
+
+/// Renders `num_samples` sample-accurate values of a curve segment that
+/// interpolates from `start_value` at sample 0 to `end_value` at
+/// `num_samples - 1`.
+pub fn render_segment(
+    shape: CurveShape,
+    start_value: f32,
+    end_value: f32,
+    num_samples: usize,
+) -> Vec<f32> {
+    if num_samples == 0 {
+        return Vec::new();
+    }
+    if num_samples == 1 {
+        return vec![start_value];
+    }
+    let last = (num_samples - 1) as f64;
+    (0..num_samples)
+        .map(|i| {
+            let t = i as f64 / last;
+            let eased = shape.ease(t);
+            (start_value as f64 + (end_value as f64 - start_value as f64) * eased) as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_is_evenly_spaced() {
+        let samples = render_segment(CurveShape::Linear, 0.0, 10.0, 5);
+        assert_eq!(samples, vec![0.0, 2.5, 5.0, 7.5, 10.0]);
+    }
+
+    #[test]
+    fn step_holds_until_the_last_sample() {
+        let samples = render_segment(CurveShape::Step, 0.0, 10.0, 4);
+        assert_eq!(samples, vec![0.0, 0.0, 0.0, 10.0]);
+    }
+
+    #[test]
+    fn every_shape_starts_and_ends_at_the_segment_endpoints() {
+        let shapes = [
+            CurveShape::Linear,
+            CurveShape::Exponential { curvature: 0.8 },
+            CurveShape::Bezier { x1: 0.25, y1: 0.1, x2: 0.25, y2: 1.0 },
+        ];
+        for shape in shapes {
+            let samples = render_segment(shape, -2.0, 3.0, 10);
+            assert!((samples[0] - -2.0).abs() < 1e-4);
+            assert!((samples[9] - 3.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn single_sample_segment_holds_the_start_value() {
+        assert_eq!(render_segment(CurveShape::Linear, 1.0, 9.0, 1), vec![1.0]);
+    }
+}
@@ -0,0 +1,281 @@
+//! Sample-accurate automation lanes.
+//!
+//! A lane is a sorted list of breakpoints per `(plugin, param)`, each with
+//! the [`CurveShape`] governing the segment leading into it. Where the
+//! plugin host processor used to reduce a block's worth of host-side
+//! automation down to a single value, [`AutomationLanes::events_for_block`]
+//! instead returns the exact, sample-offset-tagged
+//! [`EventParamValue`](crate::plugin::EventParamValue) events it should
+//! emit: one at the start of the block for the lane's current value, plus
+//! one for every breakpoint the block crosses.
+
+use std::collections::HashMap;
+
+use crate::automation::curve::CurveShape;
+use crate::id::{ParamID, PluginInstanceID};
+use crate::plugin::{EventParamValue, ParamCookie};
+
+/// One breakpoint in an automation lane, at an absolute sample position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutomationPoint {
+    pub sample: u64,
+    pub value: f64,
+    /// The curve shape of the segment leading into this point, from the
+    /// previous point (or held flat before the first point).
+    pub shape: CurveShape,
+}
+
+/// A host-to-plugin parameter value event, tagged with its offset in
+/// samples from the start of the process block it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedParamValue {
+    pub sample_offset: u32,
+    pub event: EventParamValue,
+}
+
+/// A sorted list of automation breakpoints for a single parameter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AutomationLane {
+    points: Vec<AutomationPoint>,
+}
+
+impl AutomationLane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a breakpoint, keeping the lane sorted by sample position.
+    /// Replaces any existing point at the same sample.
+    pub fn push_point(&mut self, point: AutomationPoint) {
+        match self.points.binary_search_by_key(&point.sample, |p| p.sample) {
+            Ok(index) => self.points[index] = point,
+            Err(index) => self.points.insert(index, point),
+        }
+    }
+
+    pub fn points(&self) -> &[AutomationPoint] {
+        &self.points
+    }
+
+    /// The lane's value at `sample`, held flat before the first point and
+    /// after the last, and eased between the two points surrounding it
+    /// otherwise.
+    pub fn value_at(&self, sample: u64) -> f64 {
+        let Some(first) = self.points.first() else {
+            return 0.0;
+        };
+        if sample <= first.sample {
+            return first.value;
+        }
+        let last = self.points.last().unwrap();
+        if sample >= last.sample {
+            return last.value;
+        }
+
+        let next_index = self.points.partition_point(|p| p.sample <= sample);
+        let prev = &self.points[next_index - 1];
+        let next = &self.points[next_index];
+        let t = (sample - prev.sample) as f64 / (next.sample - prev.sample) as f64;
+        prev.value + (next.value - prev.value) * next.shape.ease(t)
+    }
+}
+
+/// Per-`(plugin, param)` automation lanes, read by the plugin host processor
+/// to emit sample-accurate parameter events instead of one value per block.
+#[derive(Debug, Default)]
+pub struct AutomationLanes {
+    lanes: HashMap<(PluginInstanceID, ParamID), AutomationLane>,
+}
+
+impl AutomationLanes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mutable access to a parameter's lane, creating an empty one if it
+    /// doesn't exist yet.
+    pub fn lane_mut(&mut self, plugin: PluginInstanceID, param: ParamID) -> &mut AutomationLane {
+        self.lanes.entry((plugin, param)).or_default()
+    }
+
+    pub fn lane(&self, plugin: PluginInstanceID, param: ParamID) -> Option<&AutomationLane> {
+        self.lanes.get(&(plugin, param))
+    }
+
+    /// Removes all lanes for a plugin, e.g. when it is removed from the
+    /// graph.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.lanes.retain(|key, _| key.0 != plugin);
+    }
+
+    /// Returns the sample-accurate events a plugin host processor should
+    /// emit for `[block_start, block_start + block_frames)`: one at offset
+    /// `0` for the lane's value at the start of the block, plus one for
+    /// every breakpoint strictly inside it, in ascending offset order.
+    ///
+    /// Returns nothing for a parameter with no lane, leaving the processor
+    /// free to fall back to whatever value it already has.
+    pub fn events_for_block(
+        &self,
+        plugin: PluginInstanceID,
+        param: ParamID,
+        cookie: ParamCookie,
+        block_start: u64,
+        block_frames: u32,
+    ) -> Vec<TimedParamValue> {
+        self.events_for_block_compensated(plugin, param, cookie, block_start, block_frames, 0)
+    }
+
+    /// Like [`events_for_block`](Self::events_for_block), but reads the
+    /// lane `latency_samples` earlier than the block it's scheduling for.
+    ///
+    /// PDC delays a plugin's output by its compensated path latency, so a
+    /// move drawn to land at sample `T` on the heard timeline must reach
+    /// the plugin's input `latency_samples` earlier to come out the other
+    /// side at `T`. `sample_offset`s in the returned events stay relative
+    /// to `block_start` (the block actually being processed now), only the
+    /// sample position the lane is read at is shifted.
+    pub fn events_for_block_compensated(
+        &self,
+        plugin: PluginInstanceID,
+        param: ParamID,
+        cookie: ParamCookie,
+        block_start: u64,
+        block_frames: u32,
+        latency_samples: u32,
+    ) -> Vec<TimedParamValue> {
+        let Some(lane) = self.lane(plugin, param) else {
+            return Vec::new();
+        };
+        let read_start = block_start.saturating_sub(latency_samples as u64);
+        let read_end = read_start + block_frames as u64;
+
+        let mut events = vec![TimedParamValue {
+            sample_offset: 0,
+            event: EventParamValue { param_id: param, value: lane.value_at(read_start), cookie },
+        }];
+        for point in lane.points() {
+            if point.sample > read_start && point.sample < read_end {
+                events.push(TimedParamValue {
+                    sample_offset: (point.sample - read_start) as u32,
+                    event: EventParamValue { param_id: param, value: point.value, cookie },
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(sample: u64, value: f64, shape: CurveShape) -> AutomationPoint {
+        AutomationPoint { sample, value, shape }
+    }
+
+    #[test]
+    fn value_before_the_first_point_holds_flat() {
+        let mut lane = AutomationLane::new();
+        lane.push_point(point(100, 0.5, CurveShape::Linear));
+        assert_eq!(lane.value_at(0), 0.5);
+    }
+
+    #[test]
+    fn value_after_the_last_point_holds_flat() {
+        let mut lane = AutomationLane::new();
+        lane.push_point(point(0, 0.2, CurveShape::Linear));
+        lane.push_point(point(100, 0.8, CurveShape::Linear));
+        assert_eq!(lane.value_at(1000), 0.8);
+    }
+
+    #[test]
+    fn value_between_points_interpolates_linearly() {
+        let mut lane = AutomationLane::new();
+        lane.push_point(point(0, 0.0, CurveShape::Linear));
+        lane.push_point(point(100, 1.0, CurveShape::Linear));
+        assert_eq!(lane.value_at(50), 0.5);
+    }
+
+    #[test]
+    fn pushing_a_point_at_an_existing_sample_replaces_it() {
+        let mut lane = AutomationLane::new();
+        lane.push_point(point(0, 0.0, CurveShape::Linear));
+        lane.push_point(point(0, 0.9, CurveShape::Linear));
+        assert_eq!(lane.points().len(), 1);
+        assert_eq!(lane.value_at(0), 0.9);
+    }
+
+    #[test]
+    fn events_for_block_includes_the_start_value_and_interior_breakpoints() {
+        let mut lanes = AutomationLanes::new();
+        let plugin = PluginInstanceID::new();
+        let param = ParamID(7);
+        let lane = lanes.lane_mut(plugin, param);
+        lane.push_point(point(0, 0.0, CurveShape::Linear));
+        lane.push_point(point(50, 1.0, CurveShape::Linear));
+        lane.push_point(point(200, 0.0, CurveShape::Linear));
+
+        let events = lanes.events_for_block(plugin, param, ParamCookie::NONE, 0, 100);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], TimedParamValue {
+            sample_offset: 0,
+            event: EventParamValue { param_id: param, value: 0.0, cookie: ParamCookie::NONE },
+        });
+        assert_eq!(events[1], TimedParamValue {
+            sample_offset: 50,
+            event: EventParamValue { param_id: param, value: 1.0, cookie: ParamCookie::NONE },
+        });
+    }
+
+    #[test]
+    fn compensated_events_read_the_lane_earlier_by_the_latency() {
+        let mut lanes = AutomationLanes::new();
+        let plugin = PluginInstanceID::new();
+        let param = ParamID(7);
+        let lane = lanes.lane_mut(plugin, param);
+        lane.push_point(point(0, 0.0, CurveShape::Linear));
+        lane.push_point(point(50, 1.0, CurveShape::Linear));
+
+        // Processing block [100, 200) with 50 samples of latency should
+        // read the lane as if it were block [50, 150): the breakpoint at
+        // 50 now falls at the very start of the read window.
+        let events =
+            lanes.events_for_block_compensated(plugin, param, ParamCookie::NONE, 100, 100, 50);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].sample_offset, 0);
+        assert_eq!(events[0].event.value, 1.0);
+    }
+
+    #[test]
+    fn zero_latency_compensation_matches_events_for_block() {
+        let mut lanes = AutomationLanes::new();
+        let plugin = PluginInstanceID::new();
+        let param = ParamID(7);
+        let lane = lanes.lane_mut(plugin, param);
+        lane.push_point(point(0, 0.0, CurveShape::Linear));
+        lane.push_point(point(50, 1.0, CurveShape::Linear));
+
+        assert_eq!(
+            lanes.events_for_block(plugin, param, ParamCookie::NONE, 0, 100),
+            lanes.events_for_block_compensated(plugin, param, ParamCookie::NONE, 0, 100, 0),
+        );
+    }
+
+    #[test]
+    fn events_for_block_is_empty_for_a_parameter_with_no_lane() {
+        let lanes = AutomationLanes::new();
+        let events =
+            lanes.events_for_block(PluginInstanceID::new(), ParamID(0), ParamCookie::NONE, 0, 128);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn removing_a_plugin_clears_its_lanes() {
+        let mut lanes = AutomationLanes::new();
+        let plugin = PluginInstanceID::new();
+        lanes.lane_mut(plugin, ParamID(0)).push_point(point(0, 1.0, CurveShape::Linear));
+        lanes.remove_plugin(plugin);
+        assert!(lanes.lane(plugin, ParamID(0)).is_none());
+    }
+}
@@ -0,0 +1,116 @@
+//! Param automation recording state.
+//!
+//! This module tracks which parameters are currently "armed" for automation
+//! capture. Only armed parameters generate automation-capture events on the
+//! main thread, which keeps the main-thread event traffic down in sessions
+//! where many plugin GUIs are open and constantly nudging values.
+
+use std::collections::HashMap;
+
+use crate::id::{ParamID, PluginInstanceID};
+
+pub mod curve;
+pub mod lane;
+
+pub use curve::{render_segment, CurveShape};
+pub use lane::{AutomationLane, AutomationLanes, AutomationPoint, TimedParamValue};
+
+/// Per-plugin record-arm state for automation capture.
+#[derive(Debug, Default)]
+struct PluginArmState {
+    /// The plugin-wide default used for parameters that don't have an
+    /// explicit per-parameter override.
+    default_armed: bool,
+    /// Explicit per-parameter overrides of `default_armed`.
+    overrides: HashMap<ParamID, bool>,
+}
+
+/// Tracks automation record-arm flags for every plugin in the session.
+///
+/// A parameter is considered armed if it has an explicit per-parameter
+/// override, falling back to the plugin-wide default otherwise. Plugins
+/// with no state at all are treated as un-armed by default.
+#[derive(Debug, Default)]
+pub struct AutomationArmState {
+    plugins: HashMap<PluginInstanceID, PluginArmState>,
+}
+
+impl AutomationArmState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether automation-capture events should be generated for
+    /// this parameter.
+    pub fn is_armed(&self, plugin_id: PluginInstanceID, param_id: ParamID) -> bool {
+        let Some(state) = self.plugins.get(&plugin_id) else {
+            return false;
+        };
+        state.overrides.get(&param_id).copied().unwrap_or(state.default_armed)
+    }
+
+    /// Sets the plugin-wide default arm state. This does not affect
+    /// parameters that already have an explicit per-parameter override.
+    pub fn set_plugin_default_armed(&mut self, plugin_id: PluginInstanceID, armed: bool) {
+        self.plugins.entry(plugin_id).or_default().default_armed = armed;
+    }
+
+    /// Sets an explicit per-parameter arm override.
+    pub fn set_param_armed(&mut self, plugin_id: PluginInstanceID, param_id: ParamID, armed: bool) {
+        self.plugins.entry(plugin_id).or_default().overrides.insert(param_id, armed);
+    }
+
+    /// Clears a per-parameter override, falling back to the plugin-wide
+    /// default for this parameter again.
+    pub fn clear_param_override(&mut self, plugin_id: PluginInstanceID, param_id: ParamID) {
+        if let Some(state) = self.plugins.get_mut(&plugin_id) {
+            state.overrides.remove(&param_id);
+        }
+    }
+
+    /// Removes all arm state for a plugin, e.g. when it is removed from the
+    /// graph.
+    pub fn remove_plugin(&mut self, plugin_id: PluginInstanceID) {
+        self.plugins.remove(&plugin_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_unarmed() {
+        let state = AutomationArmState::new();
+        let plugin = PluginInstanceID::new();
+        assert!(!state.is_armed(plugin, ParamID(0)));
+    }
+
+    #[test]
+    fn plugin_wide_default_applies_to_unset_params() {
+        let mut state = AutomationArmState::new();
+        let plugin = PluginInstanceID::new();
+        state.set_plugin_default_armed(plugin, true);
+        assert!(state.is_armed(plugin, ParamID(0)));
+        assert!(state.is_armed(plugin, ParamID(1)));
+    }
+
+    #[test]
+    fn per_param_override_wins_over_default() {
+        let mut state = AutomationArmState::new();
+        let plugin = PluginInstanceID::new();
+        state.set_plugin_default_armed(plugin, true);
+        state.set_param_armed(plugin, ParamID(0), false);
+        assert!(!state.is_armed(plugin, ParamID(0)));
+        assert!(state.is_armed(plugin, ParamID(1)));
+    }
+
+    #[test]
+    fn clearing_override_restores_default() {
+        let mut state = AutomationArmState::new();
+        let plugin = PluginInstanceID::new();
+        state.set_param_armed(plugin, ParamID(0), true);
+        state.clear_param_override(plugin, ParamID(0));
+        assert!(!state.is_armed(plugin, ParamID(0)));
+    }
+}
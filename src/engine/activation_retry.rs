@@ -0,0 +1,238 @@
+//! Retry/backoff policy for plugin activation failures.
+//!
+//! When a plugin's `activate()` fails, it used to just stay deactivated.
+//! Transient failures (e.g. temporary resource exhaustion) are often worth
+//! retrying a few times with backoff before giving up; permanent failures
+//! never are. This tracks that retry state per plugin and reports it
+//! through [`on_idle`](ActivationRetryTracker::on_idle), with a manual
+//! [`retry_activation`](ActivationRetryTracker::retry_activation) escape
+//! hatch for a user-initiated retry.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::id::PluginInstanceID;
+
+/// Whether an activation failure is worth retrying automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationFailure {
+    /// Likely to succeed on a later attempt (e.g. the host was briefly out
+    /// of a shared resource).
+    Transient,
+    /// Will not succeed by retrying (e.g. a missing dependency, an
+    /// incompatible plugin version).
+    Permanent,
+}
+
+/// How many times, and how far apart, a transient activation failure
+/// should be retried before being treated as permanent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub backoff_multiplier: f32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_backoff: Duration::from_millis(500), backoff_multiplier: 2.0 }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        self.initial_backoff.mul_f32(self.backoff_multiplier.powi(attempt.saturating_sub(1) as i32))
+    }
+}
+
+/// One plugin's in-flight retry state.
+#[derive(Debug, Clone, Copy)]
+struct PendingRetry {
+    attempt: u32,
+    retry_at: Instant,
+}
+
+/// Reported when a plugin's activation failure is recorded, so the host can
+/// surface it to the user (and, for `TransientRetryScheduled`, know not to
+/// report it as a hard error yet).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationEvent {
+    /// A transient failure was recorded and will be retried automatically.
+    TransientRetryScheduled { plugin: PluginInstanceID, attempt: u32 },
+    /// A transient failure exhausted its retry budget; it's being treated
+    /// as permanent from here on.
+    TransientRetriesExhausted { plugin: PluginInstanceID },
+    /// A permanent failure was recorded; no retry will be scheduled.
+    PermanentFailure { plugin: PluginInstanceID },
+}
+
+/// Tracks per-plugin activation retry state and decides, each idle tick,
+/// which plugins are due for an automatic retry.
+#[derive(Debug, Default)]
+pub struct ActivationRetryTracker {
+    policy: RetryPolicy,
+    pending: HashMap<PluginInstanceID, PendingRetry>,
+}
+
+impl ActivationRetryTracker {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy, pending: HashMap::new() }
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: RetryPolicy) {
+        self.policy = policy;
+    }
+
+    /// Records an activation failure for `plugin`, scheduling a backoff
+    /// retry if it's transient and retries remain.
+    pub fn record_failure(&mut self, plugin: PluginInstanceID, failure: ActivationFailure, now: Instant) -> ActivationEvent {
+        match failure {
+            ActivationFailure::Permanent => {
+                self.pending.remove(&plugin);
+                ActivationEvent::PermanentFailure { plugin }
+            }
+            ActivationFailure::Transient => {
+                let attempt = self.pending.get(&plugin).map_or(1, |p| p.attempt + 1);
+                if attempt > self.policy.max_retries {
+                    self.pending.remove(&plugin);
+                    ActivationEvent::TransientRetriesExhausted { plugin }
+                } else {
+                    let retry_at = now + self.policy.backoff_for_attempt(attempt);
+                    self.pending.insert(plugin, PendingRetry { attempt, retry_at });
+                    ActivationEvent::TransientRetryScheduled { plugin, attempt }
+                }
+            }
+        }
+    }
+
+    /// Clears a plugin's retry state after it activates successfully.
+    pub fn record_success(&mut self, plugin: PluginInstanceID) {
+        self.pending.remove(&plugin);
+    }
+
+    /// Called from the host's idle tick: returns every plugin whose backoff
+    /// has elapsed and should have its activation retried now. Does not
+    /// clear their pending state; callers report the outcome via
+    /// [`record_failure`](Self::record_failure) or
+    /// [`record_success`](Self::record_success) as usual.
+    pub fn on_idle(&self, now: Instant) -> Vec<PluginInstanceID> {
+        self.pending.iter().filter(|(_, retry)| retry.retry_at <= now).map(|(&plugin, _)| plugin).collect()
+    }
+
+    /// Forces an immediate retry of `plugin`, bypassing any remaining
+    /// backoff. Returns `false` if `plugin` has no pending failure to
+    /// retry (it never failed, or already gave up permanently).
+    pub fn retry_activation(&mut self, plugin: PluginInstanceID, now: Instant) -> bool {
+        match self.pending.get_mut(&plugin) {
+            Some(retry) => {
+                retry.retry_at = now;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_pending(&self, plugin: PluginInstanceID) -> bool {
+        self.pending.contains_key(&plugin)
+    }
+
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.pending.remove(&plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy { max_retries: 2, initial_backoff: Duration::from_millis(100), backoff_multiplier: 2.0 }
+    }
+
+    #[test]
+    fn a_permanent_failure_is_never_scheduled_for_retry() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        let now = Instant::now();
+
+        let event = tracker.record_failure(plugin, ActivationFailure::Permanent, now);
+        assert_eq!(event, ActivationEvent::PermanentFailure { plugin });
+        assert!(!tracker.is_pending(plugin));
+        assert!(tracker.on_idle(now + Duration::from_secs(10)).is_empty());
+    }
+
+    #[test]
+    fn a_transient_failure_schedules_backoff_and_becomes_due_once_it_elapses() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        let now = Instant::now();
+
+        let event = tracker.record_failure(plugin, ActivationFailure::Transient, now);
+        assert_eq!(event, ActivationEvent::TransientRetryScheduled { plugin, attempt: 1 });
+        assert!(tracker.on_idle(now).is_empty(), "not due yet");
+        assert_eq!(tracker.on_idle(now + Duration::from_millis(101)), vec![plugin]);
+    }
+
+    #[test]
+    fn exhausting_retries_reports_a_distinct_event_from_a_permanent_failure() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        let now = Instant::now();
+
+        tracker.record_failure(plugin, ActivationFailure::Transient, now);
+        tracker.record_failure(plugin, ActivationFailure::Transient, now);
+        let event = tracker.record_failure(plugin, ActivationFailure::Transient, now);
+
+        assert_eq!(event, ActivationEvent::TransientRetriesExhausted { plugin });
+        assert!(!tracker.is_pending(plugin));
+    }
+
+    #[test]
+    fn backoff_grows_with_each_attempt() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        let now = Instant::now();
+
+        tracker.record_failure(plugin, ActivationFailure::Transient, now);
+        assert!(tracker.on_idle(now + Duration::from_millis(150)) == vec![plugin]);
+
+        tracker.record_failure(plugin, ActivationFailure::Transient, now + Duration::from_millis(150));
+        // Second attempt backs off 200ms, so 150ms later it should not be due yet.
+        assert!(tracker.on_idle(now + Duration::from_millis(300)).is_empty());
+        assert_eq!(tracker.on_idle(now + Duration::from_millis(351)), vec![plugin]);
+    }
+
+    #[test]
+    fn a_success_clears_pending_retry_state() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        let now = Instant::now();
+
+        tracker.record_failure(plugin, ActivationFailure::Transient, now);
+        tracker.record_success(plugin);
+        assert!(!tracker.is_pending(plugin));
+    }
+
+    #[test]
+    fn manual_retry_bypasses_remaining_backoff() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        let now = Instant::now();
+
+        tracker.record_failure(plugin, ActivationFailure::Transient, now);
+        assert!(tracker.on_idle(now).is_empty());
+        assert!(tracker.retry_activation(plugin, now));
+        assert_eq!(tracker.on_idle(now), vec![plugin]);
+    }
+
+    #[test]
+    fn manual_retry_on_a_plugin_with_no_failure_on_record_is_a_no_op() {
+        let mut tracker = ActivationRetryTracker::new(policy());
+        let plugin = PluginInstanceID::new();
+        assert!(!tracker.retry_activation(plugin, Instant::now()));
+    }
+}
@@ -0,0 +1,197 @@
+//! Dirty-tracked, chunked autosave scheduling for the engine timer.
+//!
+//! Collecting a full project save state on every [`on_timer`] tick would be
+//! wasteful once nothing has changed since the last save, and doing the
+//! whole collection synchronously in one tick can stall the main thread
+//! once a session accumulates enough plugins. [`AutosaveScheduler`] only
+//! starts a pass once the configured interval has elapsed since the last
+//! save *and* the project has been marked dirty since, then spreads
+//! collecting each plugin's state across as many ticks as it takes,
+//! [`chunk_size`](AutosaveScheduler::new) plugins at a time.
+//!
+//! [`on_timer`]: crate::engine::DSEngineMainThread::on_timer
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::id::PluginInstanceID;
+use crate::plugin::state::DSPluginSaveState;
+
+/// Default time between autosave passes, chosen to be infrequent enough to
+/// not add I/O pressure but frequent enough to bound data loss.
+pub const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Default number of plugins' state collected per tick while a pass is in
+/// progress.
+pub const DEFAULT_AUTOSAVE_CHUNK_SIZE: usize = 8;
+
+/// Reported once an autosave pass finishes, so the host can surface it
+/// (e.g. a transient "Saved" indicator, or a warning toast on failure).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutosaveEvent {
+    Saved,
+    Failed(String),
+}
+
+/// One autosave pass's progress: the plugins still waiting to have their
+/// state collected, and what's been collected from the rest so far.
+struct InProgress {
+    remaining: Vec<PluginInstanceID>,
+    collected: HashMap<PluginInstanceID, DSPluginSaveState>,
+}
+
+/// Tracks when the project is dirty and due for an autosave, and the
+/// in-progress chunked collection of one pass.
+pub struct AutosaveScheduler {
+    interval: Duration,
+    chunk_size: usize,
+    dirty: bool,
+    last_saved: Option<Instant>,
+    in_progress: Option<InProgress>,
+}
+
+impl Default for AutosaveScheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUTOSAVE_INTERVAL, DEFAULT_AUTOSAVE_CHUNK_SIZE)
+    }
+}
+
+impl AutosaveScheduler {
+    pub fn new(interval: Duration, chunk_size: usize) -> Self {
+        Self { interval, chunk_size: chunk_size.max(1), dirty: false, last_saved: None, in_progress: None }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Marks the project changed since the last autosave, making it
+    /// eligible to start a new pass once the interval elapses.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Whether a pass is currently being collected across ticks.
+    pub fn in_progress(&self) -> bool {
+        self.in_progress.is_some()
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.dirty && self.last_saved.is_none_or(|last_saved| now.duration_since(last_saved) >= self.interval)
+    }
+
+    /// Starts a new pass if one isn't already running and it's due, then
+    /// collects up to [`chunk_size`](Self::new) more plugins' state via
+    /// `plugin_state` from `nodes`. Returns the full collection once the
+    /// pass completes; until then, and when no pass is due, returns
+    /// `None`.
+    pub fn advance(
+        &mut self,
+        now: Instant,
+        nodes: &[PluginInstanceID],
+        mut plugin_state: impl FnMut(PluginInstanceID) -> DSPluginSaveState,
+    ) -> Option<HashMap<PluginInstanceID, DSPluginSaveState>> {
+        if self.in_progress.is_none() {
+            if !self.is_due(now) {
+                return None;
+            }
+            self.in_progress = Some(InProgress { remaining: nodes.to_vec(), collected: HashMap::new() });
+        }
+        let pass = self.in_progress.as_mut().expect("populated above if absent");
+        for _ in 0..self.chunk_size {
+            let Some(plugin) = pass.remaining.pop() else { break };
+            let state = plugin_state(plugin);
+            pass.collected.insert(plugin, state);
+        }
+        if pass.remaining.is_empty() {
+            Some(self.in_progress.take().expect("checked above").collected)
+        } else {
+            None
+        }
+    }
+
+    /// Records that a completed pass was persisted (successfully or not),
+    /// resetting dirty state so the next pass only starts once something
+    /// changes again.
+    pub fn finish(&mut self, now: Instant) {
+        self.dirty = false;
+        self.last_saved = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_clean_project_never_becomes_due() {
+        let scheduler = AutosaveScheduler::new(Duration::from_secs(10), 8);
+        assert!(!scheduler.is_due(Instant::now()));
+    }
+
+    #[test]
+    fn a_dirty_project_is_due_immediately_if_never_saved() {
+        let mut scheduler = AutosaveScheduler::new(Duration::from_secs(10), 8);
+        scheduler.mark_dirty();
+        assert!(scheduler.is_due(Instant::now()));
+    }
+
+    #[test]
+    fn a_dirty_project_waits_out_the_interval_after_the_last_save() {
+        let mut scheduler = AutosaveScheduler::new(Duration::from_secs(10), 8);
+        let now = Instant::now();
+        scheduler.mark_dirty();
+        scheduler.finish(now);
+        scheduler.mark_dirty();
+
+        assert!(!scheduler.is_due(now + Duration::from_secs(5)));
+        assert!(scheduler.is_due(now + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn advance_collects_one_chunk_per_call_until_the_pass_completes() {
+        let mut scheduler = AutosaveScheduler::new(Duration::from_secs(10), 2);
+        let now = Instant::now();
+        scheduler.mark_dirty();
+        let nodes: Vec<PluginInstanceID> = (0..5).map(|_| PluginInstanceID::new()).collect();
+
+        let mut calls = 0;
+        assert!(scheduler.advance(now, &nodes, |_| { calls += 1; DSPluginSaveState::new(Vec::new()) }).is_none());
+        assert_eq!(calls, 2);
+        assert!(scheduler.in_progress());
+
+        assert!(scheduler.advance(now, &nodes, |_| { calls += 1; DSPluginSaveState::new(Vec::new()) }).is_none());
+        assert_eq!(calls, 4);
+
+        let collected = scheduler.advance(now, &nodes, |_| { calls += 1; DSPluginSaveState::new(Vec::new()) }).unwrap();
+        assert_eq!(calls, 5);
+        assert_eq!(collected.len(), 5);
+        assert!(!scheduler.in_progress());
+    }
+
+    #[test]
+    fn advance_does_nothing_when_not_due_and_no_pass_is_in_progress() {
+        let mut scheduler = AutosaveScheduler::new(Duration::from_secs(10), 2);
+        let nodes = vec![PluginInstanceID::new()];
+        assert!(scheduler.advance(Instant::now(), &nodes, |_| DSPluginSaveState::new(Vec::new())).is_none());
+    }
+
+    #[test]
+    fn finishing_a_pass_clears_dirty_and_resets_the_interval_clock() {
+        let mut scheduler = AutosaveScheduler::new(Duration::from_secs(10), 8);
+        let now = Instant::now();
+        scheduler.mark_dirty();
+        scheduler.finish(now);
+
+        assert!(!scheduler.is_dirty());
+        assert!(!scheduler.is_due(now + Duration::from_secs(100)));
+    }
+}
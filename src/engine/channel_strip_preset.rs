@@ -0,0 +1,208 @@
+//! Portable, versioned preset format for a whole chain of plugins rooted at
+//! one plugin in the graph (e.g. a channel strip: gate, EQ, compressor),
+//! exported as a self-contained unit a host can drop into a different
+//! project.
+//!
+//! Unlike [`ProjectSaveState`](super::project_state::ProjectSaveState),
+//! which captures the whole session, a [`ChannelStripPreset`] only walks
+//! downstream from a chosen root plugin and also records each plugin's
+//! [`PluginKey`] (which [`ProjectSaveState`](super::project_state::ProjectSaveState)
+//! doesn't need to, since its plugins are already instantiated when it's
+//! collected) so the chain can be re-instantiated from scratch somewhere
+//! else.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::{apply_graph_edit, AbstractGraph, GraphEditRequest};
+use crate::id::PluginInstanceID;
+use crate::plugin::scanner::PluginKey;
+use crate::plugin::state::DSPluginSaveState;
+
+/// The current version written by this build of dropseed for
+/// [`ChannelStripPreset`].
+pub const CURRENT_CHANNEL_STRIP_PRESET_VERSION: u32 = 1;
+
+/// One plugin's saved identity and state within a [`ChannelStripPreset`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelStripPlugin {
+    pub key: PluginKey,
+    pub state: DSPluginSaveState,
+}
+
+/// A portable chain of plugins: an ordered list of plugin identities and
+/// their saved state, the edges between them, and a host-settable display
+/// name, all self-contained enough to reconstruct the chain elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelStripPreset {
+    pub version: u32,
+    pub name: String,
+    pub plugins: Vec<ChannelStripPlugin>,
+    /// Dependency edges between plugins, referencing positions in
+    /// `plugins` rather than the live `PluginInstanceID`s an import
+    /// allocates fresh.
+    pub edges: Vec<(u32, u32)>,
+}
+
+/// Every node reachable from `root` by following edges forward (`root`
+/// included), in breadth-first discovery order.
+fn downstream_chain(graph: &AbstractGraph, root: PluginInstanceID) -> Vec<PluginInstanceID> {
+    let mut visited = HashSet::new();
+    let mut order = Vec::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(root);
+    visited.insert(root);
+
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for &(from, to) in graph.edges() {
+            if from == node && visited.insert(to) {
+                queue.push_back(to);
+            }
+        }
+    }
+    order
+}
+
+impl ChannelStripPreset {
+    /// Captures the chain of plugins reachable downstream of `root`
+    /// (`root` included) into a self-contained preset named `name`.
+    /// `plugin_key` and `plugin_state` are called once per plugin in the
+    /// chain, in the same order as `plugins` in the result.
+    pub fn export_chain(
+        graph: &AbstractGraph,
+        root: PluginInstanceID,
+        name: impl Into<String>,
+        mut plugin_key: impl FnMut(PluginInstanceID) -> PluginKey,
+        mut plugin_state: impl FnMut(PluginInstanceID) -> DSPluginSaveState,
+    ) -> Self {
+        let nodes = downstream_chain(graph, root);
+        let index_of: HashMap<PluginInstanceID, u32> =
+            nodes.iter().enumerate().map(|(index, &id)| (id, index as u32)).collect();
+
+        let plugins = nodes
+            .iter()
+            .map(|&id| ChannelStripPlugin { key: plugin_key(id), state: plugin_state(id) })
+            .collect();
+        let edges = graph
+            .edges()
+            .iter()
+            .filter_map(|&(from, to)| match (index_of.get(&from), index_of.get(&to)) {
+                (Some(&f), Some(&t)) => Some((f, t)),
+                _ => None,
+            })
+            .collect();
+
+        Self { version: CURRENT_CHANNEL_STRIP_PRESET_VERSION, name: name.into(), plugins, edges }
+    }
+
+    /// Adds every plugin in this preset to `graph` as a freshly allocated
+    /// node and reconnects `edges` between them, returning the new IDs in
+    /// the same order as `plugins` so the caller can instantiate each one
+    /// (using its [`ChannelStripPlugin::key`]) against its saved state. An
+    /// edge whose saved index is out of range is silently dropped; every
+    /// other edge is between nodes this call just added, so it can't fail.
+    pub fn import_chain(&self, graph: &mut AbstractGraph) -> Vec<PluginInstanceID> {
+        let ids: Vec<PluginInstanceID> = self.plugins.iter().map(|_| PluginInstanceID::new()).collect();
+        for &id in &ids {
+            graph.add_node(id);
+        }
+
+        let mut request = GraphEditRequest::new();
+        for &(from, to) in &self.edges {
+            if let (Some(&from_id), Some(&to_id)) = (ids.get(from as usize), ids.get(to as usize)) {
+                request = request.connect(from_id, to_id);
+            }
+        }
+        apply_graph_edit(graph, request);
+
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::scanner::PluginFormat;
+
+    fn key(id: &str) -> PluginKey {
+        PluginKey { format: PluginFormat::Clap, id: id.to_string() }
+    }
+
+    #[test]
+    fn exporting_a_chain_only_includes_plugins_downstream_of_the_root() {
+        let mut graph = AbstractGraph::new();
+        let upstream = PluginInstanceID::new();
+        let gate = PluginInstanceID::new();
+        let eq = PluginInstanceID::new();
+        let comp = PluginInstanceID::new();
+        for id in [upstream, gate, eq, comp] {
+            graph.add_node(id);
+        }
+        graph.connect(upstream, gate);
+        graph.connect(gate, eq);
+        graph.connect(eq, comp);
+
+        let preset = ChannelStripPreset::export_chain(
+            &graph,
+            gate,
+            "Vocal strip",
+            |id| key(if id == gate { "gate" } else if id == eq { "eq" } else { "comp" }),
+            |_| DSPluginSaveState::new(Vec::new()),
+        );
+
+        assert_eq!(preset.name, "Vocal strip");
+        assert_eq!(preset.plugins.len(), 3);
+        assert_eq!(preset.plugins[0].key, key("gate"));
+        assert_eq!(preset.edges, vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn importing_a_preset_recreates_its_plugins_and_edges() {
+        let mut graph = AbstractGraph::new();
+        let preset = ChannelStripPreset {
+            version: CURRENT_CHANNEL_STRIP_PRESET_VERSION,
+            name: "Drum bus".to_string(),
+            plugins: vec![
+                ChannelStripPlugin { key: key("comp"), state: DSPluginSaveState::new(vec![1]) },
+                ChannelStripPlugin { key: key("limiter"), state: DSPluginSaveState::new(vec![2]) },
+            ],
+            edges: vec![(0, 1)],
+        };
+
+        let ids = preset.import_chain(&mut graph);
+        assert_eq!(ids.len(), 2);
+        assert_eq!(graph.nodes(), &ids[..]);
+        assert_eq!(graph.edges(), &[(ids[0], ids[1])]);
+    }
+
+    #[test]
+    fn importing_drops_an_out_of_range_edge_instead_of_panicking() {
+        let mut graph = AbstractGraph::new();
+        let preset = ChannelStripPreset {
+            version: CURRENT_CHANNEL_STRIP_PRESET_VERSION,
+            name: "Broken".to_string(),
+            plugins: vec![ChannelStripPlugin { key: key("comp"), state: DSPluginSaveState::new(Vec::new()) }],
+            edges: vec![(0, 5)],
+        };
+
+        let ids = preset.import_chain(&mut graph);
+        assert_eq!(ids.len(), 1);
+        assert!(graph.edges().is_empty());
+    }
+
+    #[test]
+    fn a_chain_exported_then_imported_round_trips_its_topology() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.connect(a, b);
+
+        let preset = ChannelStripPreset::export_chain(&graph, a, "Chain", |_| key("p"), |_| DSPluginSaveState::new(Vec::new()));
+
+        let mut other_graph = AbstractGraph::new();
+        let ids = preset.import_chain(&mut other_graph);
+        assert_eq!(other_graph.edges(), &[(ids[0], ids[1])]);
+    }
+}
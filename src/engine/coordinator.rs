@@ -0,0 +1,79 @@
+//! Coordinates multiple engine instances (e.g. separate open projects) that
+//! share a single plugin scanner, so scanning the user's plugin folders
+//! only ever happens once per process.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::engine::DSEngineMainThread;
+use crate::plugin::scanner::PluginScanner;
+
+/// Owns a set of named engine instances (one per open project) plus a
+/// shared, thread-safe plugin scanner they all read from.
+#[derive(Default)]
+pub struct EngineCoordinator {
+    scanner: Arc<Mutex<PluginScanner>>,
+    engines: HashMap<String, DSEngineMainThread>,
+}
+
+impl EngineCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cheap-to-clone handle to the scanner shared by every engine this
+    /// coordinator manages.
+    pub fn shared_scanner(&self) -> Arc<Mutex<PluginScanner>> {
+        self.scanner.clone()
+    }
+
+    /// Creates a new engine under `name`, returning `false` without
+    /// replacing anything if that name is already in use.
+    pub fn add_engine(&mut self, name: &str) -> bool {
+        if self.engines.contains_key(name) {
+            return false;
+        }
+        self.engines.insert(name.to_string(), DSEngineMainThread::new());
+        true
+    }
+
+    pub fn remove_engine(&mut self, name: &str) -> Option<DSEngineMainThread> {
+        self.engines.remove(name)
+    }
+
+    pub fn engine(&self, name: &str) -> Option<&DSEngineMainThread> {
+        self.engines.get(name)
+    }
+
+    pub fn engine_mut(&mut self, name: &str) -> Option<&mut DSEngineMainThread> {
+        self.engines.get_mut(name)
+    }
+
+    pub fn engine_names(&self) -> impl Iterator<Item = &str> {
+        self.engines.keys().map(|s| s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engines_share_the_same_scanner_instance() {
+        let mut coordinator = EngineCoordinator::new();
+        coordinator.add_engine("project_a");
+        coordinator.add_engine("project_b");
+
+        let scanner_a = coordinator.shared_scanner();
+        let scanner_b = coordinator.shared_scanner();
+        assert!(Arc::ptr_eq(&scanner_a, &scanner_b));
+    }
+
+    #[test]
+    fn adding_a_duplicate_name_does_not_replace_the_existing_engine() {
+        let mut coordinator = EngineCoordinator::new();
+        assert!(coordinator.add_engine("project"));
+        assert!(!coordinator.add_engine("project"));
+        assert_eq!(coordinator.engine_names().count(), 1);
+    }
+}
@@ -0,0 +1,109 @@
+//! Best-effort plugin state snapshots for emergency crash recovery.
+//!
+//! If a plugin takes down the audio thread, there may be no time left for
+//! the ordinary save flow (asking every plugin to serialize its state on
+//! the main thread). A [`CrashSnapshotTable`] lets the audio thread publish
+//! each plugin's latest known state as it goes, the same producer/consumer
+//! handoff [`MessageQueue`](super::message_queue::MessageQueue) uses, so
+//! whatever was captured before things went wrong survives for the main
+//! thread to bundle into an emergency backup [`ProjectSaveState`](super::project_state::ProjectSaveState).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::id::PluginInstanceID;
+use crate::plugin::state::DSPluginSaveState;
+
+#[derive(Debug, Default)]
+struct SnapshotSlots {
+    entries: HashMap<PluginInstanceID, DSPluginSaveState>,
+}
+
+/// A shared table of best-effort plugin state snapshots. Cloning shares the
+/// same underlying table, so a handle can be cloned to give the audio
+/// thread publish access while the main thread keeps one to read from
+/// after a crash.
+#[derive(Debug, Clone, Default)]
+pub struct CrashSnapshotTable {
+    slots: Arc<Mutex<SnapshotSlots>>,
+}
+
+impl CrashSnapshotTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publishes (or replaces) a plugin's latest known state. Intended to
+    /// be called periodically (e.g. once per `on_timer` tick) rather than
+    /// every process block, since it takes a lock.
+    pub fn publish(&self, plugin: PluginInstanceID, state: DSPluginSaveState) {
+        self.slots.lock().unwrap().entries.insert(plugin, state);
+    }
+
+    /// Drops a plugin's snapshot, e.g. when it is removed from the graph.
+    pub fn remove_plugin(&self, plugin: PluginInstanceID) {
+        self.slots.lock().unwrap().entries.remove(&plugin);
+    }
+
+    /// Collects every currently published snapshot, for bundling into an
+    /// emergency backup project. Plugins with no published snapshot yet
+    /// (just instantiated, or never opted in) are simply absent.
+    pub fn snapshot_all(&self) -> Vec<(PluginInstanceID, DSPluginSaveState)> {
+        self.slots.lock().unwrap().entries.iter().map(|(&id, state)| (id, state.clone())).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publishing_a_snapshot_makes_it_available_to_readers() {
+        let table = CrashSnapshotTable::new();
+        let plugin = PluginInstanceID::new();
+        table.publish(plugin, DSPluginSaveState::new(vec![1, 2, 3]));
+
+        let all = table.snapshot_all();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0], (plugin, DSPluginSaveState::new(vec![1, 2, 3])));
+    }
+
+    #[test]
+    fn publishing_again_replaces_the_previous_snapshot() {
+        let table = CrashSnapshotTable::new();
+        let plugin = PluginInstanceID::new();
+        table.publish(plugin, DSPluginSaveState::new(vec![1]));
+        table.publish(plugin, DSPluginSaveState::new(vec![2]));
+
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.snapshot_all()[0].1, DSPluginSaveState::new(vec![2]));
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_snapshot() {
+        let table = CrashSnapshotTable::new();
+        let plugin = PluginInstanceID::new();
+        table.publish(plugin, DSPluginSaveState::new(vec![1]));
+        table.remove_plugin(plugin);
+
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn cloned_handles_share_the_same_table() {
+        let table = CrashSnapshotTable::new();
+        let handle = table.clone();
+        let plugin = PluginInstanceID::new();
+
+        handle.publish(plugin, DSPluginSaveState::new(vec![9]));
+        assert_eq!(table.len(), 1);
+    }
+}
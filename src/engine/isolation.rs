@@ -0,0 +1,107 @@
+//! Process isolation groups.
+//!
+//! Plugins can be assigned to a named isolation group so that a crash or a
+//! deliberate restart of one plugin doesn't require stopping audio for the
+//! whole graph: only the plugins in the affected group need to be
+//! deactivated and reactivated.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::id::PluginInstanceID;
+
+/// Identifies an isolation group. Groups are created implicitly the first
+/// time a plugin is assigned to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IsolationGroupID(pub u32);
+
+/// Tracks which isolation group each plugin belongs to.
+///
+/// Plugins with no explicit assignment are implicitly isolated from each
+/// other (restarting one never affects another ungrouped plugin).
+#[derive(Debug, Default)]
+pub struct IsolationGroups {
+    group_of: HashMap<PluginInstanceID, IsolationGroupID>,
+    members_of: HashMap<IsolationGroupID, HashSet<PluginInstanceID>>,
+}
+
+impl IsolationGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns a plugin to an isolation group, removing any previous
+    /// assignment it had.
+    pub fn assign(&mut self, plugin_id: PluginInstanceID, group: IsolationGroupID) {
+        self.remove_plugin(plugin_id);
+        self.group_of.insert(plugin_id, group);
+        self.members_of.entry(group).or_default().insert(plugin_id);
+    }
+
+    /// Removes a plugin from whatever isolation group it was in.
+    pub fn remove_plugin(&mut self, plugin_id: PluginInstanceID) {
+        if let Some(group) = self.group_of.remove(&plugin_id) {
+            if let Some(members) = self.members_of.get_mut(&group) {
+                members.remove(&plugin_id);
+                if members.is_empty() {
+                    self.members_of.remove(&group);
+                }
+            }
+        }
+    }
+
+    pub fn group_of(&self, plugin_id: PluginInstanceID) -> Option<IsolationGroupID> {
+        self.group_of.get(&plugin_id).copied()
+    }
+
+    /// Returns every plugin that must be restarted together with
+    /// `plugin_id`: the rest of its isolation group, or just itself if it
+    /// isn't in one.
+    pub fn restart_set(&self, plugin_id: PluginInstanceID) -> Vec<PluginInstanceID> {
+        match self.group_of(plugin_id) {
+            Some(group) => self.members_of.get(&group).into_iter().flatten().copied().collect(),
+            None => vec![plugin_id],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ungrouped_plugin_only_restarts_itself() {
+        let groups = IsolationGroups::new();
+        let plugin = PluginInstanceID::new();
+        assert_eq!(groups.restart_set(plugin), vec![plugin]);
+    }
+
+    #[test]
+    fn grouped_plugins_restart_together() {
+        let mut groups = IsolationGroups::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        groups.assign(a, IsolationGroupID(0));
+        groups.assign(b, IsolationGroupID(0));
+        groups.assign(c, IsolationGroupID(1));
+
+        let mut restart_a = groups.restart_set(a);
+        restart_a.sort();
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(restart_a, expected);
+        assert_eq!(groups.restart_set(c), vec![c]);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_it_from_its_group() {
+        let mut groups = IsolationGroups::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        groups.assign(a, IsolationGroupID(0));
+        groups.assign(b, IsolationGroupID(0));
+        groups.remove_plugin(a);
+        assert_eq!(groups.restart_set(b), vec![b]);
+        assert_eq!(groups.group_of(a), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! A bounded, generation-tagged queue for main-thread → audio-thread
+//! messages (schedule swaps, parameter batches, and the like).
+//!
+//! A host that rapidly edits the graph can enqueue messages faster than the
+//! audio thread drains them. Tagging each message with the generation that
+//! was current when it was produced lets the main thread cancel everything
+//! older than its latest edit instead of letting the audio thread apply a
+//! stale schedule swap or param batch on top of a newer one. The queue is
+//! bounded so a runaway producer reports backpressure instead of growing
+//! without limit or panicking on an unwrap.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::graph::Schedule;
+use crate::plugin::EventParamValue;
+
+/// Default capacity for a [`MessageQueue`] created via [`Default`].
+pub const DEFAULT_QUEUE_CAPACITY: usize = 256;
+
+/// A main-to-audio message carried by [`DSEngineMainThread`]'s default
+/// message queue: either a freshly compiled schedule to swap in, or a
+/// batch of parameter updates to apply before the next block.
+///
+/// [`DSEngineMainThread`]: crate::engine::DSEngineMainThread
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineMessage {
+    ScheduleSwap(Schedule),
+    ParamBatch(Vec<EventParamValue>),
+}
+
+/// Returned by [`MessageQueue::push`] when the queue is already at
+/// capacity, instead of panicking or dropping the message silently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull;
+
+/// Tags a message with the producer generation it was created under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Generation(pub u64);
+
+#[derive(Debug)]
+struct Entry<T> {
+    generation: Generation,
+    message: T,
+}
+
+#[derive(Debug)]
+struct QueueState<T> {
+    capacity: usize,
+    entries: VecDeque<Entry<T>>,
+}
+
+/// A bounded, generation-tagged message queue. Cloning shares the same
+/// underlying queue and generation counter, so a handle can be cloned to
+/// give the audio thread read/pop access while the main thread keeps one
+/// to push and cancel with.
+#[derive(Debug, Clone)]
+pub struct MessageQueue<T> {
+    state: Arc<Mutex<QueueState<T>>>,
+    next_generation: Arc<AtomicU64>,
+}
+
+impl<T> Default for MessageQueue<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUEUE_CAPACITY)
+    }
+}
+
+impl<T> MessageQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(QueueState { capacity, entries: VecDeque::new() })),
+            next_generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Allocates a fresh generation tag, e.g. at the start of a batch of
+    /// messages describing one graph edit.
+    pub fn next_generation(&self) -> Generation {
+        Generation(self.next_generation.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Enqueues `message` tagged with `generation`. Fails with
+    /// [`QueueFull`] instead of blocking or panicking if the queue is
+    /// already at capacity.
+    pub fn push(&self, generation: Generation, message: T) -> Result<(), QueueFull> {
+        let mut state = self.state.lock().unwrap();
+        if state.entries.len() >= state.capacity {
+            return Err(QueueFull);
+        }
+        state.entries.push_back(Entry { generation, message });
+        Ok(())
+    }
+
+    /// Pops the oldest queued message along with the generation it was
+    /// tagged with, or `None` if the queue is empty.
+    pub fn pop(&self) -> Option<(Generation, T)> {
+        let mut state = self.state.lock().unwrap();
+        state.entries.pop_front().map(|entry| (entry.generation, entry.message))
+    }
+
+    /// The number of messages currently queued.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.state.lock().unwrap().capacity
+    }
+
+    /// Lists the generation tag of every message currently queued, oldest
+    /// first, for introspection without consuming them.
+    pub fn pending_generations(&self) -> Vec<Generation> {
+        self.state.lock().unwrap().entries.iter().map(|entry| entry.generation).collect()
+    }
+
+    /// Drops every queued message tagged with a generation older than
+    /// `current`, since a superseding edit means they'd otherwise be
+    /// applied out of date. Returns how many messages were dropped.
+    pub fn cancel_stale(&self, current: Generation) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let before = state.entries.len();
+        state.entries.retain(|entry| entry.generation >= current);
+        before - state.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_pop_in_fifo_order_with_their_generation() {
+        let queue = MessageQueue::new(4);
+        let gen_a = queue.next_generation();
+        let gen_b = queue.next_generation();
+        queue.push(gen_a, "first").unwrap();
+        queue.push(gen_b, "second").unwrap();
+
+        assert_eq!(queue.pop(), Some((gen_a, "first")));
+        assert_eq!(queue.pop(), Some((gen_b, "second")));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn pushing_past_capacity_reports_backpressure_instead_of_panicking() {
+        let queue = MessageQueue::new(2);
+        let generation = queue.next_generation();
+        queue.push(generation, 1).unwrap();
+        queue.push(generation, 2).unwrap();
+        assert_eq!(queue.push(generation, 3), Err(QueueFull));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn pending_generations_enumerates_without_consuming() {
+        let queue = MessageQueue::new(4);
+        let gen_a = queue.next_generation();
+        let gen_b = queue.next_generation();
+        queue.push(gen_a, "a").unwrap();
+        queue.push(gen_b, "b").unwrap();
+
+        assert_eq!(queue.pending_generations(), vec![gen_a, gen_b]);
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn cancel_stale_drops_older_generations_and_keeps_newer_ones() {
+        let queue = MessageQueue::new(4);
+        let gen_a = queue.next_generation();
+        let gen_b = queue.next_generation();
+        let gen_c = queue.next_generation();
+        queue.push(gen_a, "stale schedule").unwrap();
+        queue.push(gen_b, "stale params").unwrap();
+        queue.push(gen_c, "current schedule").unwrap();
+
+        let dropped = queue.cancel_stale(gen_c);
+        assert_eq!(dropped, 2);
+        assert_eq!(queue.pop(), Some((gen_c, "current schedule")));
+    }
+
+    #[test]
+    fn cloned_handles_share_the_same_queue_and_generation_counter() {
+        let queue = MessageQueue::new(4);
+        let handle = queue.clone();
+
+        let generation = queue.next_generation();
+        handle.push(generation, "from handle").unwrap();
+
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop(), Some((generation, "from handle")));
+    }
+}
@@ -0,0 +1,584 @@
+//! The main-thread side of the Dropseed engine.
+
+use std::time::{Duration, Instant};
+
+use crate::automation::{AutomationArmState, AutomationLanes};
+use crate::graph::{
+    apply_graph_edit, AbstractGraph, EngineThreadSettings, GraphEditRequest, PluginLatencies, TerminalPortNames,
+    TraceCapture,
+};
+use crate::id::PluginInstanceID;
+use crate::metering::{MeterTaps, MonitorLatencyCompensation};
+use crate::plugin::state::DSPluginSaveState;
+use crate::plugin::{
+    DualMonoWrapper, EventQuantizer, HostEventCallbacks, NoteDialectTable, ParamCookieCache, ParamReadoutTable,
+    PluginBlockLimits, PluginDryWetStages, PluginGainStages, PluginProcessStats,
+};
+use crate::transport::TempoMap;
+use crate::util::declick::LoopDeclicker;
+
+pub mod activation_retry;
+pub mod autosave;
+pub mod channel_strip_preset;
+pub mod coordinator;
+pub mod crash_snapshot;
+pub mod isolation;
+pub mod message_queue;
+pub mod offline_render;
+pub mod project_state;
+pub mod reentrancy;
+pub mod safe_mode;
+pub mod scheduler;
+pub mod track;
+pub mod undo_history;
+
+use isolation::IsolationGroups;
+use reentrancy::ReentrancyGuard;
+use scheduler::WorkScheduler;
+
+pub use activation_retry::{ActivationEvent, ActivationFailure, ActivationRetryTracker, RetryPolicy};
+pub use autosave::{AutosaveEvent, AutosaveScheduler};
+pub use channel_strip_preset::{ChannelStripPlugin, ChannelStripPreset};
+pub use coordinator::EngineCoordinator;
+pub use crash_snapshot::CrashSnapshotTable;
+pub use message_queue::{EngineMessage, Generation, MessageQueue, QueueFull};
+pub use offline_render::{OfflineRenderEvent, OfflineRenderer, RenderConfig, RenderedBlock};
+pub use project_state::ProjectSaveState;
+pub use safe_mode::SafeModeState;
+pub use scheduler::TaskPriority;
+pub use track::{Track, DEFAULT_CHANNEL_STRIP_RAMP_SAMPLES};
+pub use undo_history::UndoHistory;
+
+/// Default per-tick time budget for [`DSEngineMainThread::on_timer`], chosen
+/// to stay well under a frame at typical UI refresh rates.
+pub const DEFAULT_TIMER_BUDGET: Duration = Duration::from_millis(2);
+
+/// The main-thread handle to a running Dropseed engine instance.
+///
+/// This owns all of the non-realtime state of the session (the graph
+/// topology, plugin hosts, and anything else that is only ever touched from
+/// the main thread) and communicates with the realtime audio thread through
+/// lock-free channels.
+#[derive(Default)]
+pub struct DSEngineMainThread {
+    automation_arm_state: AutomationArmState,
+    monitor_latency_compensation: MonitorLatencyCompensation,
+    isolation_groups: IsolationGroups,
+    reentrancy_guard: ReentrancyGuard<DSEngineMainThread>,
+    work_scheduler: WorkScheduler,
+    safe_mode: SafeModeState,
+    param_readouts: ParamReadoutTable,
+    terminal_port_names: TerminalPortNames,
+    event_quantizer: EventQuantizer,
+    output_declick: LoopDeclicker,
+    param_cookies: ParamCookieCache,
+    schedule_trace: TraceCapture,
+    plugin_gain_stages: PluginGainStages,
+    plugin_dry_wet_stages: PluginDryWetStages,
+    activation_retry: ActivationRetryTracker,
+    audio_messages: MessageQueue<EngineMessage>,
+    dual_mono: DualMonoWrapper,
+    host_event_callbacks: HostEventCallbacks,
+    offline_render: OfflineRenderer,
+    plugin_block_limits: PluginBlockLimits,
+    note_dialects: NoteDialectTable,
+    plugin_latencies: PluginLatencies,
+    thread_settings: EngineThreadSettings,
+    automation_lanes: AutomationLanes,
+    meter_taps: MeterTaps,
+    crash_snapshots: CrashSnapshotTable,
+    autosave: AutosaveScheduler,
+    plugin_process_stats: PluginProcessStats,
+    pending_declick: bool,
+}
+
+impl DSEngineMainThread {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read/write access to the per-parameter automation record-arm state.
+    pub fn automation_arm_state(&self) -> &AutomationArmState {
+        &self.automation_arm_state
+    }
+
+    /// Mutable access to the per-parameter automation record-arm state.
+    pub fn automation_arm_state_mut(&mut self) -> &mut AutomationArmState {
+        &mut self.automation_arm_state
+    }
+
+    /// Read/write access to per-plugin monitor-latency compensation for GUI
+    /// metering feedback.
+    pub fn monitor_latency_compensation(&self) -> &MonitorLatencyCompensation {
+        &self.monitor_latency_compensation
+    }
+
+    /// Mutable access to per-plugin monitor-latency compensation for GUI
+    /// metering feedback.
+    pub fn monitor_latency_compensation_mut(&mut self) -> &mut MonitorLatencyCompensation {
+        &mut self.monitor_latency_compensation
+    }
+
+    /// Read/write access to plugin process isolation group assignments.
+    pub fn isolation_groups(&self) -> &IsolationGroups {
+        &self.isolation_groups
+    }
+
+    /// Mutable access to plugin process isolation group assignments.
+    pub fn isolation_groups_mut(&mut self) -> &mut IsolationGroups {
+        &mut self.isolation_groups
+    }
+
+    /// Runs `op` against this engine, deferring it if it was invoked while
+    /// an outer call to `run_guarded` is already in progress on this
+    /// thread (e.g. from a plugin host callback that re-enters the engine).
+    pub fn run_guarded(&mut self, op: impl FnOnce(&mut DSEngineMainThread) + 'static) {
+        // `run_or_defer` needs `&mut self.reentrancy_guard` and `&mut self`
+        // simultaneously; take the guard out for the duration of the call.
+        let mut guard = std::mem::take(&mut self.reentrancy_guard);
+        guard.run_or_defer(self, op);
+        self.reentrancy_guard = guard;
+    }
+
+    /// Queues `task` to run on a future [`on_timer`](Self::on_timer) tick
+    /// under `priority`, instead of doing it immediately.
+    pub fn schedule_work(&mut self, priority: TaskPriority, task: impl FnOnce() + 'static) {
+        self.work_scheduler.schedule(priority, task);
+    }
+
+    /// Called periodically (e.g. from a GUI idle timer) to run queued
+    /// upkeep work such as parameter syncing, garbage collection, and cache
+    /// maintenance. Only runs as much queued work as fits in `budget`,
+    /// highest priority first, so a large session doesn't stall the calling
+    /// thread on any single tick.
+    ///
+    /// Returns the number of tasks run this tick.
+    pub fn on_timer(&mut self, budget: Duration) -> usize {
+        self.work_scheduler.run_tick(budget)
+    }
+
+    /// Read/write access to safe-mode activation state, used to recover a
+    /// project whose external plugins are crashing on load.
+    pub fn safe_mode(&self) -> &SafeModeState {
+        &self.safe_mode
+    }
+
+    /// Mutable access to safe-mode activation state.
+    pub fn safe_mode_mut(&mut self) -> &mut SafeModeState {
+        &mut self.safe_mode
+    }
+
+    /// Read/write access to the table of realtime-safe parameter readouts
+    /// used by host-side audio-thread DSP to read a plugin's current
+    /// effective parameter value without round-tripping through this
+    /// (main) thread.
+    pub fn param_readouts(&self) -> &ParamReadoutTable {
+        &self.param_readouts
+    }
+
+    /// Mutable access to the realtime-safe parameter readout table.
+    pub fn param_readouts_mut(&mut self) -> &mut ParamReadoutTable {
+        &mut self.param_readouts
+    }
+
+    /// Read/write access to host-settable display names for the graph's
+    /// input/output terminal ports.
+    pub fn terminal_port_names(&self) -> &TerminalPortNames {
+        &self.terminal_port_names
+    }
+
+    /// Mutable access to terminal port display names.
+    pub fn terminal_port_names_mut(&mut self) -> &mut TerminalPortNames {
+        &mut self.terminal_port_names
+    }
+
+    /// Read/write access to per-plugin live note-event input quantization.
+    pub fn event_quantizer(&self) -> &EventQuantizer {
+        &self.event_quantizer
+    }
+
+    /// Mutable access to per-plugin live note-event input quantization.
+    pub fn event_quantizer_mut(&mut self) -> &mut EventQuantizer {
+        &mut self.event_quantizer
+    }
+
+    /// The declicker used to crossfade the engine's graph output across a
+    /// transport loop boundary.
+    pub fn output_declick(&self) -> &LoopDeclicker {
+        &self.output_declick
+    }
+
+    /// Sets the loop-boundary output crossfade length in samples. `0`
+    /// disables declicking.
+    pub fn set_output_declick_len(&mut self, crossfade_len: usize) {
+        self.output_declick.set_crossfade_len(crossfade_len);
+    }
+
+    /// Arms the output declicker to start a fresh crossfade at the top of
+    /// the next audio block, for any transport discontinuity the host
+    /// wants smoothed over — a seek as well as a loop point.
+    pub fn request_declick(&mut self) {
+        self.pending_declick = true;
+    }
+
+    /// Consumes the pending declick request, if any. The host's audio
+    /// callback calls this once per block to learn whether it should start
+    /// [`output_declick`](Self::output_declick) over from sample `0`
+    /// (via [`LoopDeclicker::apply_to_block`]) instead of continuing
+    /// wherever the last crossfade left off.
+    pub fn take_pending_declick(&mut self) -> bool {
+        std::mem::take(&mut self.pending_declick)
+    }
+
+    /// Read/write access to the cache of CLAP parameter cookies used to
+    /// build host-emitted param-value events on the fast path.
+    pub fn param_cookies(&self) -> &ParamCookieCache {
+        &self.param_cookies
+    }
+
+    /// Mutable access to the parameter cookie cache.
+    pub fn param_cookies_mut(&mut self) -> &mut ParamCookieCache {
+        &mut self.param_cookies
+    }
+
+    /// The handle used to request and retrieve a one-block debug trace of
+    /// the processor schedule's task order, durations, and buffer-constant
+    /// flags. Clone it to hand the same capture slot to the audio thread.
+    pub fn schedule_trace(&self) -> &TraceCapture {
+        &self.schedule_trace
+    }
+
+    /// Read/write access to the host-managed per-plugin input/output gain
+    /// stages wrapped around every plugin host processor.
+    pub fn plugin_gain_stages(&self) -> &PluginGainStages {
+        &self.plugin_gain_stages
+    }
+
+    /// Mutable access to the host-managed per-plugin gain stages.
+    pub fn plugin_gain_stages_mut(&mut self) -> &mut PluginGainStages {
+        &mut self.plugin_gain_stages
+    }
+
+    /// Read/write access to the host-managed per-plugin dry/wet mix and
+    /// bypass stages wrapped around every plugin host processor.
+    pub fn plugin_dry_wet_stages(&self) -> &PluginDryWetStages {
+        &self.plugin_dry_wet_stages
+    }
+
+    /// Mutable access to the host-managed per-plugin dry/wet and bypass
+    /// stages.
+    pub fn plugin_dry_wet_stages_mut(&mut self) -> &mut PluginDryWetStages {
+        &mut self.plugin_dry_wet_stages
+    }
+
+    /// Read/write access to the plugin activation retry/backoff tracker.
+    pub fn activation_retry(&self) -> &ActivationRetryTracker {
+        &self.activation_retry
+    }
+
+    /// Mutable access to the plugin activation retry/backoff tracker.
+    pub fn activation_retry_mut(&mut self) -> &mut ActivationRetryTracker {
+        &mut self.activation_retry
+    }
+
+    /// The generation-tagged, bounded queue of messages (schedule swaps,
+    /// parameter batches) waiting to be applied on the audio thread. Clone
+    /// it to hand the audio thread the same queue handle.
+    pub fn audio_messages(&self) -> &MessageQueue<EngineMessage> {
+        &self.audio_messages
+    }
+
+    /// Read/write access to the dual-mono wrapper tracking which logical
+    /// plugin IDs are actually ganged left/right instance pairs, for
+    /// inserting mono-only plugins into a stereo path automatically.
+    pub fn dual_mono(&self) -> &DualMonoWrapper {
+        &self.dual_mono
+    }
+
+    /// Mutable access to the dual-mono wrapper.
+    pub fn dual_mono_mut(&mut self) -> &mut DualMonoWrapper {
+        &mut self.dual_mono
+    }
+
+    /// Read/write access to per-plugin host event callbacks, invoked each
+    /// block just before that plugin processes.
+    pub fn host_event_callbacks(&self) -> &HostEventCallbacks {
+        &self.host_event_callbacks
+    }
+
+    /// Mutable access to per-plugin host event callbacks.
+    pub fn host_event_callbacks_mut(&mut self) -> &mut HostEventCallbacks {
+        &mut self.host_event_callbacks
+    }
+
+    /// Read/write access to the faster-than-realtime export renderer.
+    pub fn offline_render(&self) -> &OfflineRenderer {
+        &self.offline_render
+    }
+
+    /// Mutable access to the export renderer.
+    pub fn offline_render_mut(&mut self) -> &mut OfflineRenderer {
+        &mut self.offline_render
+    }
+
+    /// Read/write access to per-plugin maximum block-size overrides, used
+    /// by the plugin host processor to split its block into smaller
+    /// sub-blocks for plugins that need them.
+    pub fn plugin_block_limits(&self) -> &PluginBlockLimits {
+        &self.plugin_block_limits
+    }
+
+    /// Mutable access to per-plugin maximum block-size overrides.
+    pub fn plugin_block_limits_mut(&mut self) -> &mut PluginBlockLimits {
+        &mut self.plugin_block_limits
+    }
+
+    /// Read/write access to per-note-port dialect negotiation results and
+    /// forced overrides.
+    pub fn note_dialects(&self) -> &NoteDialectTable {
+        &self.note_dialects
+    }
+
+    /// Mutable access to the note dialect table.
+    pub fn note_dialects_mut(&mut self) -> &mut NoteDialectTable {
+        &mut self.note_dialects
+    }
+
+    /// Read/write access to per-plugin reported processing latency, used to
+    /// compute automatic delay compensation across the graph's parallel
+    /// paths. A host should call [`set_latency`](PluginLatencies::set_latency)
+    /// whenever a plugin reports a latency change (e.g. the CLAP `latency`
+    /// extension's `changed` callback) and recompile the schedule's delay
+    /// compensation afterward via [`compute_delay_compensation`](crate::graph::compute_delay_compensation).
+    pub fn plugin_latencies(&self) -> &PluginLatencies {
+        &self.plugin_latencies
+    }
+
+    /// Mutable access to per-plugin reported processing latency.
+    pub fn plugin_latencies_mut(&mut self) -> &mut PluginLatencies {
+        &mut self.plugin_latencies
+    }
+
+    /// How many worker threads the audio thread should spread a compiled
+    /// schedule's independent waves across. The audio thread builds one
+    /// [`ParallelWorkerPool`](crate::graph::ParallelWorkerPool) from this
+    /// at activation time and reuses it block-to-block via
+    /// [`execute_parallel`](crate::graph::execute_parallel), rather than
+    /// spawning threads per block.
+    pub fn thread_settings(&self) -> &EngineThreadSettings {
+        &self.thread_settings
+    }
+
+    /// Sets the number of worker threads to use for parallel schedule
+    /// execution.
+    pub fn set_thread_settings(&mut self, settings: EngineThreadSettings) {
+        self.thread_settings = settings;
+    }
+
+    /// Read/write access to host-fed, per-parameter automation lanes, used
+    /// by the plugin host processor to emit sample-accurate parameter
+    /// events instead of reducing a block to a single value.
+    pub fn automation_lanes(&self) -> &AutomationLanes {
+        &self.automation_lanes
+    }
+
+    /// Mutable access to the automation lanes.
+    pub fn automation_lanes_mut(&mut self) -> &mut AutomationLanes {
+        &mut self.automation_lanes
+    }
+
+    /// Read/write access to host-requested metering taps on plugin outputs
+    /// and graph output channels.
+    pub fn meter_taps(&self) -> &MeterTaps {
+        &self.meter_taps
+    }
+
+    /// Mutable access to the metering taps.
+    pub fn meter_taps_mut(&mut self) -> &mut MeterTaps {
+        &mut self.meter_taps
+    }
+
+    /// Read/write access to per-plugin process-call duration histograms,
+    /// for spotting rare worst-case spikes that cause dropouts even when a
+    /// plugin's average CPU load looks fine.
+    pub fn plugin_process_stats(&self) -> &PluginProcessStats {
+        &self.plugin_process_stats
+    }
+
+    /// Mutable access to the process-duration histograms.
+    pub fn plugin_process_stats_mut(&mut self) -> &mut PluginProcessStats {
+        &mut self.plugin_process_stats
+    }
+
+    /// The table of best-effort plugin state snapshots used for emergency
+    /// crash recovery. Clone it to hand the audio thread the same table
+    /// handle to publish into.
+    pub fn crash_snapshots(&self) -> &CrashSnapshotTable {
+        &self.crash_snapshots
+    }
+
+    /// Captures `graph` and `tempo_map`, together with every engine-level
+    /// table this engine tracks, into a [`ProjectSaveState`] a host can
+    /// persist and later pass to
+    /// [`restore_graph_from_save_state`](Self::restore_graph_from_save_state).
+    ///
+    /// `plugin_state` is called once per node in `graph`, in order, to
+    /// collect that plugin's own [`DSPluginSaveState`] blob (the engine
+    /// itself has no opinion on a plugin's internal state).
+    pub fn collect_graph_save_state(
+        &self,
+        graph: &AbstractGraph,
+        tempo_map: &TempoMap,
+        mut plugin_state: impl FnMut(PluginInstanceID) -> DSPluginSaveState,
+    ) -> ProjectSaveState {
+        let nodes = graph.nodes();
+        let index_of: std::collections::HashMap<PluginInstanceID, u32> =
+            nodes.iter().enumerate().map(|(index, &id)| (id, index as u32)).collect();
+
+        let plugins = nodes.iter().map(|&id| plugin_state(id)).collect();
+        let edges = graph
+            .edges()
+            .iter()
+            .map(|&(from, to)| (index_of[&from], index_of[&to]))
+            .collect();
+        let plugin_gains_db = self
+            .plugin_gain_stages
+            .entries()
+            .into_iter()
+            .map(|(id, input_db, output_db)| (index_of[&id], input_db, output_db))
+            .collect();
+        let plugin_dry_wet = self
+            .plugin_dry_wet_stages
+            .entries()
+            .into_iter()
+            .map(|(id, mix, bypassed)| (index_of[&id], mix, bypassed))
+            .collect();
+
+        ProjectSaveState::new(
+            plugins,
+            edges,
+            self.terminal_port_names.entries(),
+            plugin_gains_db,
+            plugin_dry_wet,
+            tempo_map,
+        )
+    }
+
+    /// Builds a best-effort emergency backup [`ProjectSaveState`] from
+    /// whatever [`crash_snapshots`](Self::crash_snapshots) has captured so
+    /// far, instead of asking each plugin for its state the normal way.
+    /// Intended for use after a watchdog detects the audio thread is gone,
+    /// when the regular [`collect_graph_save_state`](Self::collect_graph_save_state)
+    /// flow (which calls back into potentially-dead plugins) can no longer
+    /// be trusted to complete. Plugins with no published snapshot are
+    /// backed up with empty state rather than omitted, so the graph
+    /// topology is still fully recovered.
+    pub fn emergency_save_state(&self, graph: &AbstractGraph, tempo_map: &TempoMap) -> ProjectSaveState {
+        let snapshots: std::collections::HashMap<PluginInstanceID, DSPluginSaveState> =
+            self.crash_snapshots.snapshot_all().into_iter().collect();
+        self.collect_graph_save_state(graph, tempo_map, |id| {
+            snapshots.get(&id).cloned().unwrap_or_else(|| DSPluginSaveState::new(Vec::new()))
+        })
+    }
+
+    /// Read/write access to the autosave due/in-progress tracker.
+    pub fn autosave(&self) -> &AutosaveScheduler {
+        &self.autosave
+    }
+
+    /// Mutable access to the autosave tracker, e.g. to change its interval.
+    pub fn autosave_mut(&mut self) -> &mut AutosaveScheduler {
+        &mut self.autosave
+    }
+
+    /// Marks the project changed since the last autosave. Call this from
+    /// wherever the host already notices the project became dirty (an
+    /// edit, a parameter change, a graph edit) so the next due
+    /// [`tick_autosave`](Self::tick_autosave) call actually has something
+    /// worth saving.
+    pub fn mark_project_dirty(&mut self) {
+        self.autosave.mark_dirty();
+    }
+
+    /// Drives one tick of the autosave scheduler. If the project is dirty
+    /// and due for an autosave, collects another chunk of plugin state via
+    /// `plugin_state`; once a full pass completes, hands the assembled
+    /// [`ProjectSaveState`] to `persist` and reports the outcome. Returns
+    /// `None` on every tick that neither starts nor advances a pass, which
+    /// is the common case between autosaves.
+    pub fn tick_autosave(
+        &mut self,
+        now: Instant,
+        graph: &AbstractGraph,
+        tempo_map: &TempoMap,
+        plugin_state: impl FnMut(PluginInstanceID) -> DSPluginSaveState,
+        persist: impl FnOnce(ProjectSaveState) -> Result<(), String>,
+    ) -> Option<AutosaveEvent> {
+        let collected = self.autosave.advance(now, graph.nodes(), plugin_state)?;
+        let state = self.collect_graph_save_state(graph, tempo_map, |id| {
+            collected.get(&id).cloned().unwrap_or_else(|| DSPluginSaveState::new(Vec::new()))
+        });
+        self.autosave.finish(now);
+        Some(match persist(state) {
+            Ok(()) => AutosaveEvent::Saved,
+            Err(err) => AutosaveEvent::Failed(err),
+        })
+    }
+
+    /// Rebuilds an [`AbstractGraph`] and [`TempoMap`] from `state`, and
+    /// restores this engine's terminal port names, plugin gain stages, and
+    /// plugin dry/wet stages, ramping any restored gain changes over
+    /// `gain_ramp_samples` and any restored dry/wet or bypass changes over
+    /// `dry_wet_ramp_samples`.
+    ///
+    /// Every plugin in `state.plugins` gets a freshly allocated
+    /// [`PluginInstanceID`], returned in the same order so the caller can
+    /// re-instantiate each plugin against its saved state; `state.edges`
+    /// are remapped from saved indices to these new IDs. An edge whose
+    /// saved index is out of range is dropped and reported as a permanent
+    /// activation failure on [`activation_retry`](Self::activation_retry)
+    /// instead of failing the whole restore.
+    pub fn restore_graph_from_save_state(
+        &mut self,
+        state: &ProjectSaveState,
+        gain_ramp_samples: u32,
+        dry_wet_ramp_samples: u32,
+    ) -> (AbstractGraph, TempoMap, Vec<PluginInstanceID>, Vec<ActivationEvent>) {
+        let ids: Vec<PluginInstanceID> = state.plugins.iter().map(|_| PluginInstanceID::new()).collect();
+
+        let mut graph = AbstractGraph::new();
+        for &id in &ids {
+            graph.add_node(id);
+        }
+
+        let mut request = GraphEditRequest::new();
+        let mut blamed_for_invalid_edge = Vec::new();
+        for &(from, to) in &state.edges {
+            match (ids.get(from as usize), ids.get(to as usize)) {
+                (Some(&from_id), Some(&to_id)) => request = request.connect(from_id, to_id),
+                // Blame whichever endpoint is a real, just-restored plugin
+                // for the corrupt edge; if neither is, there's nothing to
+                // attribute it to.
+                (Some(&from_id), None) => blamed_for_invalid_edge.push(from_id),
+                (None, Some(&to_id)) => blamed_for_invalid_edge.push(to_id),
+                (None, None) => {}
+            }
+        }
+        // Every edge here connects nodes already added to `graph`, so none
+        // of them can fail; only an out-of-range saved index (handled
+        // above) is possible corruption.
+        apply_graph_edit(&mut graph, request);
+
+        let now = Instant::now();
+        let events = blamed_for_invalid_edge
+            .into_iter()
+            .map(|id| self.activation_retry.record_failure(id, ActivationFailure::Permanent, now))
+            .collect();
+
+        self.terminal_port_names = TerminalPortNames::from_entries(state.terminal_port_names.iter().cloned());
+        self.plugin_gain_stages = state.plugin_gain_stages(gain_ramp_samples, &ids);
+        self.plugin_dry_wet_stages = state.plugin_dry_wet_stages(dry_wet_ramp_samples, &ids);
+
+        (graph, state.tempo_map(), ids, events)
+    }
+}
@@ -0,0 +1,193 @@
+//! Faster-than-realtime export rendering: drives a compiled [`Schedule`]
+//! block-by-block from a worker thread with no audio device attached,
+//! delivering rendered blocks back to the main thread as they finish.
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use crate::graph::Schedule;
+
+/// The render target: sample rate, channel count, and block size to render
+/// at, plus the total frame count to cover.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderConfig {
+    pub sample_rate: f64,
+    pub channels: u16,
+    pub block_frames: u32,
+    pub total_frames: u64,
+}
+
+/// One rendered block of interleaved audio, delivered in frame order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedBlock {
+    pub start_frame: u64,
+    pub frames: u32,
+    pub samples: Vec<f32>,
+}
+
+/// An update from an in-flight offline render.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OfflineRenderEvent {
+    BlockReady(RenderedBlock),
+    /// The render reached `total_frames`; no further blocks will arrive.
+    Finished,
+}
+
+/// Drives a [`Schedule`] through a non-realtime render to a worker thread,
+/// so exporting a long project doesn't block the main thread (or require
+/// running at realtime speed through an audio device).
+///
+/// Plugins that support the CLAP `render` extension should be switched into
+/// non-realtime mode by the caller before starting a render and back into
+/// realtime mode once it finishes or is cancelled; that activation-state
+/// bookkeeping belongs with the rest of plugin activation, not here.
+#[derive(Default)]
+pub struct OfflineRenderer {
+    receiver: Option<Receiver<OfflineRenderEvent>>,
+}
+
+impl OfflineRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_rendering(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Starts rendering `schedule` under `config` on a worker thread,
+    /// calling `process_block(schedule, start_frame, frames)` once per
+    /// block to produce that block's interleaved samples. Cancels (and
+    /// replaces) any render already in progress.
+    pub fn begin_render(
+        &mut self,
+        schedule: Arc<Schedule>,
+        config: RenderConfig,
+        mut process_block: impl FnMut(&Schedule, u64, u32) -> Vec<f32> + Send + 'static,
+    ) {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut frame = 0u64;
+            while frame < config.total_frames {
+                let frames = (config.total_frames - frame).min(config.block_frames as u64) as u32;
+                let samples = process_block(&schedule, frame, frames);
+                if tx.send(OfflineRenderEvent::BlockReady(RenderedBlock { start_frame: frame, frames, samples })).is_err()
+                {
+                    // Receiver dropped (render was cancelled); stop early.
+                    return;
+                }
+                frame += frames as u64;
+            }
+            let _ = tx.send(OfflineRenderEvent::Finished);
+        });
+        self.receiver = Some(rx);
+    }
+
+    /// Non-blocking check for the next finished block or completion event.
+    /// Returns `None` if no render is in flight or none has arrived yet.
+    pub fn poll(&mut self) -> Option<OfflineRenderEvent> {
+        let rx = self.receiver.as_ref()?;
+        match rx.try_recv() {
+            Ok(OfflineRenderEvent::Finished) => {
+                self.receiver = None;
+                Some(OfflineRenderEvent::Finished)
+            }
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.receiver = None;
+                None
+            }
+        }
+    }
+
+    /// Stops delivering further blocks from the current render, if any.
+    /// The worker thread notices on its next send and exits.
+    pub fn cancel(&mut self) {
+        self.receiver = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait_for<T>(mut f: impl FnMut() -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(v) = f() {
+                return v;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for offline render");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn config(total_frames: u64, block_frames: u32) -> RenderConfig {
+        RenderConfig { sample_rate: 48_000.0, channels: 1, block_frames, total_frames }
+    }
+
+    #[test]
+    fn renders_blocks_in_frame_order_until_finished() {
+        let mut renderer = OfflineRenderer::new();
+        renderer.begin_render(Arc::new(Schedule::default()), config(10, 4), |_schedule, start_frame, frames| {
+            vec![start_frame as f32; frames as usize]
+        });
+
+        let mut blocks = Vec::new();
+        while let OfflineRenderEvent::BlockReady(block) = wait_for(|| renderer.poll()) {
+            blocks.push(block);
+        }
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0], RenderedBlock { start_frame: 0, frames: 4, samples: vec![0.0; 4] });
+        assert_eq!(blocks[1], RenderedBlock { start_frame: 4, frames: 4, samples: vec![4.0; 4] });
+        assert_eq!(blocks[2], RenderedBlock { start_frame: 8, frames: 2, samples: vec![8.0; 2] });
+        assert!(!renderer.is_rendering());
+    }
+
+    #[test]
+    fn is_rendering_is_true_until_finished_is_polled() {
+        let mut renderer = OfflineRenderer::new();
+        assert!(!renderer.is_rendering());
+        renderer.begin_render(Arc::new(Schedule::default()), config(4, 4), |_, _, frames| vec![0.0; frames as usize]);
+        assert!(renderer.is_rendering());
+
+        while wait_for(|| renderer.poll()) != OfflineRenderEvent::Finished {}
+        assert!(!renderer.is_rendering());
+    }
+
+    #[test]
+    fn a_zero_frame_render_finishes_immediately_with_no_blocks() {
+        let mut renderer = OfflineRenderer::new();
+        renderer.begin_render(Arc::new(Schedule::default()), config(0, 4), |_, _, frames| vec![0.0; frames as usize]);
+        assert_eq!(wait_for(|| renderer.poll()), OfflineRenderEvent::Finished);
+    }
+
+    #[test]
+    fn cancelling_stops_further_blocks_from_being_observed() {
+        let mut renderer = OfflineRenderer::new();
+        renderer.begin_render(Arc::new(Schedule::default()), config(1_000_000, 1), |_, _, frames| {
+            thread::sleep(Duration::from_millis(1));
+            vec![0.0; frames as usize]
+        });
+        renderer.cancel();
+        assert!(!renderer.is_rendering());
+        assert_eq!(renderer.poll(), None);
+    }
+
+    #[test]
+    fn starting_a_new_render_replaces_one_in_progress() {
+        let mut renderer = OfflineRenderer::new();
+        renderer.begin_render(Arc::new(Schedule::default()), config(1_000_000, 1), |_, _, frames| {
+            thread::sleep(Duration::from_millis(1));
+            vec![0.0; frames as usize]
+        });
+        renderer.begin_render(Arc::new(Schedule::default()), config(2, 2), |_, _, frames| vec![1.0; frames as usize]);
+
+        let event = wait_for(|| renderer.poll());
+        assert_eq!(event, OfflineRenderEvent::BlockReady(RenderedBlock { start_frame: 0, frames: 2, samples: vec![1.0; 2] }));
+    }
+}
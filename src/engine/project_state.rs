@@ -0,0 +1,219 @@
+//! Versioned engine-level project save state.
+//!
+//! Bundles every plugin's [`DSPluginSaveState`] together with the project's
+//! own schema version, so the graph topology and other engine-level state
+//! added in a later release can be migrated the same way plugin state is.
+
+use crate::graph::{TerminalPortID, TerminalPortNames};
+use crate::id::PluginInstanceID;
+use crate::plugin::state::DSPluginSaveState;
+use crate::plugin::{PluginDryWetStages, PluginGainStages};
+use crate::transport::TempoMap;
+use crate::util::versioned_migrations::{MigrationGap, MigrationRegistry};
+
+/// The current version written by this build of dropseed for
+/// [`ProjectSaveState`].
+pub const CURRENT_PROJECT_STATE_VERSION: u32 = 1;
+
+/// The full saved state of a project: every hosted plugin's state, the
+/// edges between them, the project's tempo map, the graph's user-settable
+/// terminal port names, per-plugin input/output trim, plus whatever other
+/// project-wide fields future versions add.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectSaveState {
+    pub version: u32,
+    pub plugins: Vec<DSPluginSaveState>,
+    /// Dependency edges between plugins, referencing positions in
+    /// `plugins` rather than the live `PluginInstanceID`s a restore
+    /// allocates fresh.
+    pub edges: Vec<(u32, u32)>,
+    pub terminal_port_names: Vec<(TerminalPortID, String)>,
+    /// Per-plugin input/output gain in dB, indexed the same way as
+    /// `edges` rather than by the live `PluginInstanceID`s a restore
+    /// allocates fresh.
+    pub plugin_gains_db: Vec<(u32, f32, f32)>,
+    /// Per-plugin dry/wet mix and bypass state, indexed the same way as
+    /// `edges`.
+    pub plugin_dry_wet: Vec<(u32, f32, bool)>,
+    pub sample_rate: f64,
+    /// The project tempo map's segments, as `(start_sample, bpm,
+    /// tempo_inc)`; see [`TempoMap::segments`].
+    pub tempo_segments: Vec<(u64, f64, f64)>,
+}
+
+impl ProjectSaveState {
+    /// Wraps `plugins`, `edges`, `terminal_port_names`, `plugin_gains_db`,
+    /// `plugin_dry_wet`, and `tempo_map` at the current schema version.
+    pub fn new(
+        plugins: Vec<DSPluginSaveState>,
+        edges: Vec<(u32, u32)>,
+        terminal_port_names: Vec<(TerminalPortID, String)>,
+        plugin_gains_db: Vec<(u32, f32, f32)>,
+        plugin_dry_wet: Vec<(u32, f32, bool)>,
+        tempo_map: &TempoMap,
+    ) -> Self {
+        Self {
+            version: CURRENT_PROJECT_STATE_VERSION,
+            plugins,
+            edges,
+            terminal_port_names,
+            plugin_gains_db,
+            plugin_dry_wet,
+            sample_rate: tempo_map.sample_rate(),
+            tempo_segments: tempo_map.segments(),
+        }
+    }
+
+    /// Rebuilds the [`TerminalPortNames`] table this project was saved
+    /// with.
+    pub fn terminal_port_names(&self) -> TerminalPortNames {
+        TerminalPortNames::from_entries(self.terminal_port_names.iter().cloned())
+    }
+
+    /// Rebuilds the [`PluginGainStages`] table this project was saved with,
+    /// ramping gain changes over `ramp_samples` from here on. `ids` maps
+    /// each saved plugin index back to the `PluginInstanceID` it was (or
+    /// will be) restored to, e.g. the IDs returned by
+    /// [`restore_graph_from_save_state`](crate::DSEngineMainThread::restore_graph_from_save_state).
+    pub fn plugin_gain_stages(&self, ramp_samples: u32, ids: &[PluginInstanceID]) -> PluginGainStages {
+        let entries = self
+            .plugin_gains_db
+            .iter()
+            .filter_map(|&(index, input_db, output_db)| ids.get(index as usize).map(|&id| (id, input_db, output_db)));
+        PluginGainStages::from_entries(ramp_samples, entries)
+    }
+
+    /// Rebuilds the [`PluginDryWetStages`] table this project was saved
+    /// with, ramping mix/bypass changes over `ramp_samples` from here on.
+    /// `ids` maps each saved plugin index back to the `PluginInstanceID` it
+    /// was (or will be) restored to, the same way
+    /// [`plugin_gain_stages`](Self::plugin_gain_stages) does.
+    pub fn plugin_dry_wet_stages(&self, ramp_samples: u32, ids: &[PluginInstanceID]) -> PluginDryWetStages {
+        let entries = self
+            .plugin_dry_wet
+            .iter()
+            .filter_map(|&(index, mix, bypassed)| ids.get(index as usize).map(|&id| (id, mix, bypassed)));
+        PluginDryWetStages::from_entries(ramp_samples, entries)
+    }
+
+    /// Rebuilds the [`TempoMap`] this project was saved with.
+    pub fn tempo_map(&self) -> TempoMap {
+        let mut segments = self.tempo_segments.iter();
+        let Some(&(_, bpm, _)) = segments.next() else {
+            return TempoMap::new(self.sample_rate, 120.0);
+        };
+        let mut tempo_map = TempoMap::new(self.sample_rate, bpm);
+        for &(start_sample, bpm, tempo_inc) in segments {
+            tempo_map.push_tempo_change(start_sample, bpm, tempo_inc);
+        }
+        tempo_map
+    }
+
+    /// Migrates this project's wrapper up to
+    /// `CURRENT_PROJECT_STATE_VERSION` using `registry`. Does not touch the
+    /// per-plugin states nested inside; callers should migrate those
+    /// individually with [`DSPluginSaveState::migrate`].
+    pub fn migrate(self, registry: &MigrationRegistry<Vec<DSPluginSaveState>>) -> Result<Self, MigrationGap> {
+        let version = self.version;
+        let plugins = registry.migrate(self.plugins, version, CURRENT_PROJECT_STATE_VERSION)?;
+        Ok(Self {
+            version: CURRENT_PROJECT_STATE_VERSION,
+            plugins,
+            edges: self.edges,
+            terminal_port_names: self.terminal_port_names,
+            plugin_gains_db: self.plugin_gains_db,
+            plugin_dry_wet: self.plugin_dry_wet,
+            sample_rate: self.sample_rate,
+            tempo_segments: self.tempo_segments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::TerminalDirection;
+
+    fn tempo_map() -> TempoMap {
+        TempoMap::new(48_000.0, 120.0)
+    }
+
+    #[test]
+    fn new_project_state_is_tagged_with_the_current_version() {
+        let state = ProjectSaveState::new(vec![], vec![], vec![], vec![], vec![], &tempo_map());
+        assert_eq!(state.version, CURRENT_PROJECT_STATE_VERSION);
+    }
+
+    #[test]
+    fn migrating_an_older_project_adds_a_default_plugin_entry() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(0, |mut plugins: Vec<DSPluginSaveState>| {
+            plugins.push(DSPluginSaveState::new(Vec::new()));
+            plugins
+        });
+
+        let old = ProjectSaveState {
+            version: 0,
+            plugins: vec![],
+            edges: vec![],
+            terminal_port_names: vec![],
+            plugin_gains_db: vec![],
+            plugin_dry_wet: vec![],
+            sample_rate: 48_000.0,
+            tempo_segments: vec![],
+        };
+        let migrated = old.migrate(&registry).unwrap();
+        assert_eq!(migrated.version, CURRENT_PROJECT_STATE_VERSION);
+        assert_eq!(migrated.plugins.len(), 1);
+    }
+
+    #[test]
+    fn terminal_port_names_round_trip_through_save_state() {
+        let mic = TerminalPortID::for_channel(TerminalDirection::GraphIn, 0);
+        let state =
+            ProjectSaveState::new(vec![], vec![], vec![(mic, "Mic 1".to_string())], vec![], vec![], &tempo_map());
+        assert_eq!(state.terminal_port_names().get(mic), Some("Mic 1"));
+    }
+
+    #[test]
+    fn plugin_gains_round_trip_through_save_state_by_index() {
+        let plugin = PluginInstanceID::new();
+        let state = ProjectSaveState::new(vec![], vec![], vec![], vec![(0, -6.0, 1.5)], vec![], &tempo_map());
+        let stages = state.plugin_gain_stages(8, &[plugin]);
+        assert!((stages.input_gain_db(plugin) - -6.0).abs() < 1e-4);
+        assert!((stages.output_gain_db(plugin) - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn plugin_dry_wet_round_trips_through_save_state_by_index() {
+        let plugin = PluginInstanceID::new();
+        let state = ProjectSaveState::new(vec![], vec![], vec![], vec![], vec![(0, 0.4, true)], &tempo_map());
+        let stages = state.plugin_dry_wet_stages(8, &[plugin]);
+        assert!((stages.dry_wet(plugin) - 0.4).abs() < 1e-4);
+        assert!(stages.is_bypassed(plugin));
+    }
+
+    #[test]
+    fn edges_round_trip_by_index() {
+        let state = ProjectSaveState::new(
+            vec![DSPluginSaveState::new(vec![]), DSPluginSaveState::new(vec![])],
+            vec![(0, 1)],
+            vec![],
+            vec![],
+            vec![],
+            &tempo_map(),
+        );
+        assert_eq!(state.edges, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn tempo_map_round_trips_through_save_state() {
+        let mut map = TempoMap::new(48_000.0, 120.0);
+        map.push_tempo_change(48_000, 90.0, 0.0);
+
+        let state = ProjectSaveState::new(vec![], vec![], vec![], vec![], vec![], &map);
+        let restored = state.tempo_map();
+        assert_eq!(restored.bpm_at_sample(0), 120.0);
+        assert_eq!(restored.bpm_at_sample(48_000), 90.0);
+    }
+}
@@ -0,0 +1,107 @@
+//! Safe-mode project activation: when a project won't load (or crashes)
+//! because of one of its external plugins, it can instead be opened with
+//! every external plugin swapped for a passthrough placeholder, keeping
+//! each one's saved state so it can be re-enabled individually afterward
+//! to find the culprit.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::plugin::state::DSPluginSaveState;
+
+/// Tracks which plugins are currently held out of the graph by safe mode,
+/// and the state each one should be restored with once re-enabled.
+#[derive(Debug, Default)]
+pub struct SafeModeState {
+    enabled: bool,
+    disabled: HashMap<PluginInstanceID, DSPluginSaveState>,
+}
+
+impl SafeModeState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Activates safe mode, placing every plugin in `external_plugins` into
+    /// the disabled set with its current state remembered. The caller is
+    /// responsible for actually swapping each one for a passthrough node in
+    /// the graph.
+    pub fn activate(&mut self, external_plugins: impl IntoIterator<Item = (PluginInstanceID, DSPluginSaveState)>) {
+        self.enabled = true;
+        self.disabled.extend(external_plugins);
+    }
+
+    /// Deactivates safe mode, returning every remaining disabled plugin's
+    /// saved state so the caller can restore it into the graph.
+    pub fn deactivate(&mut self) -> Vec<(PluginInstanceID, DSPluginSaveState)> {
+        self.enabled = false;
+        self.disabled.drain().collect()
+    }
+
+    pub fn is_disabled(&self, plugin: PluginInstanceID) -> bool {
+        self.disabled.contains_key(&plugin)
+    }
+
+    /// Re-enables a single plugin without leaving safe mode, returning its
+    /// remembered state so the caller can restore it in place of the
+    /// placeholder. Returns `None` if that plugin wasn't disabled.
+    pub fn reenable(&mut self, plugin: PluginInstanceID) -> Option<DSPluginSaveState> {
+        self.disabled.remove(&plugin)
+    }
+
+    pub fn disabled_plugins(&self) -> impl Iterator<Item = PluginInstanceID> + '_ {
+        self.disabled.keys().copied()
+    }
+
+    pub fn num_disabled(&self) -> usize {
+        self.disabled.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activating_marks_every_given_plugin_as_disabled() {
+        let mut safe_mode = SafeModeState::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+
+        safe_mode.activate([(a, DSPluginSaveState::new(vec![1])), (b, DSPluginSaveState::new(vec![2]))]);
+        assert!(safe_mode.is_enabled());
+        assert!(safe_mode.is_disabled(a));
+        assert!(safe_mode.is_disabled(b));
+    }
+
+    #[test]
+    fn reenabling_one_plugin_returns_its_state_and_leaves_safe_mode_on() {
+        let mut safe_mode = SafeModeState::new();
+        let a = PluginInstanceID::new();
+        safe_mode.activate([(a, DSPluginSaveState::new(vec![9]))]);
+
+        let state = safe_mode.reenable(a).unwrap();
+        assert_eq!(state.data, vec![9]);
+        assert!(!safe_mode.is_disabled(a));
+        assert!(safe_mode.is_enabled());
+    }
+
+    #[test]
+    fn deactivating_returns_every_remaining_disabled_plugin() {
+        let mut safe_mode = SafeModeState::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        safe_mode.activate([(a, DSPluginSaveState::new(vec![1])), (b, DSPluginSaveState::new(vec![2]))]);
+        safe_mode.reenable(a);
+
+        let restored = safe_mode.deactivate();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].0, b);
+        assert!(!safe_mode.is_enabled());
+        assert_eq!(safe_mode.num_disabled(), 0);
+    }
+}
@@ -0,0 +1,121 @@
+//! A throttled, prioritized scheduler for main-thread upkeep work.
+//!
+//! [`DSEngineMainThread::on_timer`] used to run every pending task on every
+//! tick, which stalls the UI thread once a session accumulates enough
+//! plugins. Tasks are queued here under a priority instead, and each tick
+//! only pulls off as much work as fits in a caller-supplied time budget,
+//! highest priority first.
+//!
+//! [`DSEngineMainThread::on_timer`]: crate::engine::DSEngineMainThread::on_timer
+
+use std::time::{Duration, Instant};
+
+/// Relative importance of a queued task. Higher-priority queues are always
+/// drained before lower-priority ones within a single tick's budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskPriority {
+    Low,
+    Medium,
+    High,
+}
+
+const PRIORITIES: [TaskPriority; 3] = [TaskPriority::High, TaskPriority::Medium, TaskPriority::Low];
+
+type Task = Box<dyn FnOnce() + 'static>;
+
+/// A queue of pending main-thread tasks, run a time-budgeted slice at a
+/// time via [`WorkScheduler::run_tick`].
+#[derive(Default)]
+pub struct WorkScheduler {
+    high: Vec<Task>,
+    medium: Vec<Task>,
+    low: Vec<Task>,
+}
+
+impl WorkScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn queue_mut(&mut self, priority: TaskPriority) -> &mut Vec<Task> {
+        match priority {
+            TaskPriority::High => &mut self.high,
+            TaskPriority::Medium => &mut self.medium,
+            TaskPriority::Low => &mut self.low,
+        }
+    }
+
+    /// Queues `task` to run on a future tick under `priority`.
+    pub fn schedule(&mut self, priority: TaskPriority, task: impl FnOnce() + 'static) {
+        self.queue_mut(priority).push(Box::new(task));
+    }
+
+    pub fn num_pending(&self) -> usize {
+        self.high.len() + self.medium.len() + self.low.len()
+    }
+
+    /// Runs queued tasks, highest priority first, until either every queue
+    /// is empty or `budget` has elapsed. The budget is checked between
+    /// tasks, not inside them, so a single slow task can still overrun it;
+    /// callers should keep individual tasks short.
+    ///
+    /// Returns the number of tasks run this tick.
+    pub fn run_tick(&mut self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut ran = 0;
+        for priority in PRIORITIES {
+            while !self.queue_mut(priority).is_empty() {
+                if start.elapsed() >= budget {
+                    return ran;
+                }
+                let task = self.queue_mut(priority).remove(0);
+                task();
+                ran += 1;
+            }
+        }
+        ran
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn runs_higher_priority_tasks_before_lower_ones() {
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let mut scheduler = WorkScheduler::new();
+
+        let o = order.clone();
+        scheduler.schedule(TaskPriority::Low, move || o.borrow_mut().push("low"));
+        let o = order.clone();
+        scheduler.schedule(TaskPriority::High, move || o.borrow_mut().push("high"));
+        let o = order.clone();
+        scheduler.schedule(TaskPriority::Medium, move || o.borrow_mut().push("medium"));
+
+        scheduler.run_tick(Duration::from_secs(1));
+        assert_eq!(*order.borrow(), vec!["high", "medium", "low"]);
+    }
+
+    #[test]
+    fn a_zero_budget_still_leaves_work_pending() {
+        let mut scheduler = WorkScheduler::new();
+        scheduler.schedule(TaskPriority::High, || {});
+        let ran = scheduler.run_tick(Duration::from_secs(0));
+        assert_eq!(ran, 0);
+        assert_eq!(scheduler.num_pending(), 1);
+    }
+
+    #[test]
+    fn draining_all_tasks_empties_the_queues() {
+        let mut scheduler = WorkScheduler::new();
+        for _ in 0..5 {
+            scheduler.schedule(TaskPriority::Medium, || {});
+        }
+        let ran = scheduler.run_tick(Duration::from_secs(1));
+        assert_eq!(ran, 5);
+        assert_eq!(scheduler.num_pending(), 0);
+    }
+}
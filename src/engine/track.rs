@@ -0,0 +1,284 @@
+//! A higher-level track/bus abstraction over the raw audio graph: an
+//! instrument slot, an ordered effect chain, and a channel strip, so a
+//! host doesn't have to hand-roll graph edge bookkeeping for ordinary
+//! mixer operations.
+//!
+//! Unlike [`ChannelStripPreset`](super::channel_strip_preset::ChannelStripPreset),
+//! which is a portable save format for a chain of plugins, a [`Track`] is a
+//! live handle: it keeps its own chain ordering in sync with the graph as
+//! the host inserts, moves, and routes plugins.
+
+use crate::graph::{apply_graph_edit, AbstractGraph, GraphEditRequest};
+use crate::id::PluginInstanceID;
+use crate::nodes::gain_pan::GainPanNode;
+
+/// How many samples the track's channel strip ramps a gain/pan change over.
+pub const DEFAULT_CHANNEL_STRIP_RAMP_SAMPLES: u32 = 512;
+
+/// A track: an optional instrument generating audio, an ordered effect
+/// chain processing it, and a channel strip (gain/pan) at its tail,
+/// optionally routed into a bus.
+///
+/// The channel strip is a host-run [`GainPanNode`] rather than a graph
+/// node: like the plugin-output observers in
+/// [`MeterTaps`](crate::metering::MeterTaps), it processes the track's
+/// audio directly rather than sitting in the schedule as a plugin would.
+pub struct Track {
+    instrument: Option<PluginInstanceID>,
+    effects: Vec<PluginInstanceID>,
+    channel_strip: GainPanNode,
+    bus: Option<PluginInstanceID>,
+    wired_chain: Vec<PluginInstanceID>,
+    wired_bus: Option<PluginInstanceID>,
+}
+
+impl Track {
+    /// Creates a track around an optional instrument. The instrument, if
+    /// any, must already be a node in the graph this track will be wired
+    /// into.
+    pub fn new(instrument: Option<PluginInstanceID>) -> Self {
+        Self {
+            instrument,
+            effects: Vec::new(),
+            channel_strip: GainPanNode::new(DEFAULT_CHANNEL_STRIP_RAMP_SAMPLES),
+            bus: None,
+            wired_chain: Vec::new(),
+            wired_bus: None,
+        }
+    }
+
+    pub fn instrument(&self) -> Option<PluginInstanceID> {
+        self.instrument
+    }
+
+    /// This track's effect chain, instrument excluded, in processing
+    /// order.
+    pub fn effects(&self) -> &[PluginInstanceID] {
+        &self.effects
+    }
+
+    pub fn bus(&self) -> Option<PluginInstanceID> {
+        self.bus
+    }
+
+    /// Read/write access to the track's channel strip (gain and pan),
+    /// which the host runs directly on this track's output block.
+    pub fn channel_strip(&mut self) -> &mut GainPanNode {
+        &mut self.channel_strip
+    }
+
+    /// Every node in this track's signal path, instrument first, in
+    /// processing order.
+    fn chain(&self) -> Vec<PluginInstanceID> {
+        self.instrument.into_iter().chain(self.effects.iter().copied()).collect()
+    }
+
+    /// Inserts `effect` into the effect chain at `index` (clamped to the
+    /// chain's current length) and reconnects the graph edges around it.
+    /// `effect` must already be a node in `graph`.
+    pub fn add_effect_at(&mut self, index: usize, effect: PluginInstanceID, graph: &mut AbstractGraph) {
+        let index = index.min(self.effects.len());
+        self.effects.insert(index, effect);
+        self.rewire(graph);
+    }
+
+    /// Removes `effect` from the chain, if present, and reconnects the
+    /// graph edges around the gap.
+    pub fn remove_effect(&mut self, effect: PluginInstanceID, graph: &mut AbstractGraph) {
+        self.effects.retain(|&id| id != effect);
+        self.rewire(graph);
+    }
+
+    /// Moves the effect at `from` to `to` within the chain and reconnects
+    /// the graph edges around it. A no-op if either index is out of range.
+    pub fn move_effect(&mut self, from: usize, to: usize, graph: &mut AbstractGraph) {
+        if from >= self.effects.len() || to >= self.effects.len() {
+            return;
+        }
+        let effect = self.effects.remove(from);
+        self.effects.insert(to, effect);
+        self.rewire(graph);
+    }
+
+    /// Connects this track's tail (its last effect, or its instrument if
+    /// it has none) to `bus`, replacing any previous routing. `bus` must
+    /// already be a node in `graph`.
+    pub fn route_track_to_bus(&mut self, bus: PluginInstanceID, graph: &mut AbstractGraph) {
+        self.bus = Some(bus);
+        self.rewire(graph);
+    }
+
+    /// Disconnects this track's tail from its bus, if routed, without
+    /// removing any plugins.
+    pub fn unroute_from_bus(&mut self, graph: &mut AbstractGraph) {
+        self.bus = None;
+        self.rewire(graph);
+    }
+
+    /// Disconnects every edge this track last wired (between its own
+    /// chain nodes, and from its tail to its bus) and reconnects them
+    /// fresh from the current `instrument`/`effects`/`bus`. Rebuilding
+    /// from scratch on every structural change is simpler and less
+    /// error-prone than diffing the old and new chain order edge by edge.
+    fn rewire(&mut self, graph: &mut AbstractGraph) {
+        for window in self.wired_chain.windows(2) {
+            graph.disconnect(window[0], window[1]);
+        }
+        if let (Some(&tail), Some(bus)) = (self.wired_chain.last(), self.wired_bus) {
+            graph.disconnect(tail, bus);
+        }
+
+        let chain = self.chain();
+        let mut request = GraphEditRequest::new();
+        for window in chain.windows(2) {
+            request = request.connect(window[0], window[1]);
+        }
+        if let (Some(&tail), Some(bus)) = (chain.last(), self.bus) {
+            request = request.connect(tail, bus);
+        }
+        apply_graph_edit(graph, request);
+
+        self.wired_chain = chain;
+        self.wired_bus = self.bus;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_an_effect_chains_it_after_the_instrument() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let eq = PluginInstanceID::new();
+        graph.add_node(instrument);
+        graph.add_node(eq);
+
+        let mut track = Track::new(Some(instrument));
+        track.add_effect_at(0, eq, &mut graph);
+
+        assert_eq!(graph.edges(), &[(instrument, eq)]);
+        assert_eq!(track.effects(), &[eq]);
+    }
+
+    #[test]
+    fn inserting_an_effect_in_the_middle_rewires_its_neighbors() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let comp = PluginInstanceID::new();
+        let eq = PluginInstanceID::new();
+        for id in [instrument, comp, eq] {
+            graph.add_node(id);
+        }
+
+        let mut track = Track::new(Some(instrument));
+        track.add_effect_at(0, comp, &mut graph);
+        track.add_effect_at(0, eq, &mut graph);
+
+        assert_eq!(track.effects(), &[eq, comp]);
+        assert_eq!(graph.edges(), &[(instrument, eq), (eq, comp)]);
+    }
+
+    #[test]
+    fn moving_an_effect_reconnects_the_chain_in_the_new_order() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let comp = PluginInstanceID::new();
+        let eq = PluginInstanceID::new();
+        for id in [instrument, comp, eq] {
+            graph.add_node(id);
+        }
+
+        let mut track = Track::new(Some(instrument));
+        track.add_effect_at(0, comp, &mut graph);
+        track.add_effect_at(1, eq, &mut graph);
+        track.move_effect(1, 0, &mut graph);
+
+        assert_eq!(track.effects(), &[eq, comp]);
+        assert_eq!(graph.edges(), &[(instrument, eq), (eq, comp)]);
+    }
+
+    #[test]
+    fn removing_an_effect_closes_the_gap() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let comp = PluginInstanceID::new();
+        let eq = PluginInstanceID::new();
+        for id in [instrument, comp, eq] {
+            graph.add_node(id);
+        }
+
+        let mut track = Track::new(Some(instrument));
+        track.add_effect_at(0, comp, &mut graph);
+        track.add_effect_at(1, eq, &mut graph);
+        track.remove_effect(comp, &mut graph);
+
+        assert_eq!(track.effects(), &[eq]);
+        assert_eq!(graph.edges(), &[(instrument, eq)]);
+    }
+
+    #[test]
+    fn routing_to_a_bus_connects_the_chains_tail() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let bus = PluginInstanceID::new();
+        graph.add_node(instrument);
+        graph.add_node(bus);
+
+        let mut track = Track::new(Some(instrument));
+        track.route_track_to_bus(bus, &mut graph);
+
+        assert_eq!(graph.edges(), &[(instrument, bus)]);
+        assert_eq!(track.bus(), Some(bus));
+    }
+
+    #[test]
+    fn re_routing_to_a_different_bus_disconnects_the_old_one() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let bus_a = PluginInstanceID::new();
+        let bus_b = PluginInstanceID::new();
+        for id in [instrument, bus_a, bus_b] {
+            graph.add_node(id);
+        }
+
+        let mut track = Track::new(Some(instrument));
+        track.route_track_to_bus(bus_a, &mut graph);
+        track.route_track_to_bus(bus_b, &mut graph);
+
+        assert_eq!(graph.edges(), &[(instrument, bus_b)]);
+    }
+
+    #[test]
+    fn unrouting_from_a_bus_drops_the_edge_without_touching_plugins() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let bus = PluginInstanceID::new();
+        graph.add_node(instrument);
+        graph.add_node(bus);
+
+        let mut track = Track::new(Some(instrument));
+        track.route_track_to_bus(bus, &mut graph);
+        track.unroute_from_bus(&mut graph);
+
+        assert!(graph.edges().is_empty());
+        assert_eq!(track.instrument(), Some(instrument));
+        assert_eq!(track.bus(), None);
+    }
+
+    #[test]
+    fn moving_an_effect_with_an_out_of_range_index_is_a_no_op() {
+        let mut graph = AbstractGraph::new();
+        let instrument = PluginInstanceID::new();
+        let comp = PluginInstanceID::new();
+        graph.add_node(instrument);
+        graph.add_node(comp);
+
+        let mut track = Track::new(Some(instrument));
+        track.add_effect_at(0, comp, &mut graph);
+        track.move_effect(0, 5, &mut graph);
+
+        assert_eq!(track.effects(), &[comp]);
+    }
+}
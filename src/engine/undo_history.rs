@@ -0,0 +1,255 @@
+//! Bounded undo history with optional spill-to-disk for older entries.
+//!
+//! Keeping every undo entry for a long session resident in memory can grow
+//! unbounded. [`UndoHistory`] caps how many entries it keeps in memory and,
+//! once a host configures a spill directory, moves older ones out to disk
+//! instead of discarding them, restoring them transparently if the user
+//! keeps undoing past the in-memory window. Dropseed has no opinion on how
+//! a `T` (typically a [`ProjectSaveState`](super::project_state::ProjectSaveState))
+//! should be serialized, so the host supplies `encode`/`decode` functions
+//! once when constructing the history, the same way
+//! [`AutosaveScheduler`](super::autosave::AutosaveScheduler) defers
+//! collecting plugin state to a host-supplied closure.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+enum Location<T> {
+    InMemory(T),
+    OnDisk(PathBuf),
+}
+
+struct Entry<T> {
+    description: String,
+    location: Location<T>,
+}
+
+type Encode<T> = Box<dyn Fn(&T) -> Vec<u8> + Send>;
+type Decode<T> = Box<dyn Fn(&[u8]) -> T + Send>;
+
+pub struct UndoHistory<T> {
+    entries: VecDeque<Entry<T>>,
+    in_memory_limit: usize,
+    total_limit: Option<usize>,
+    spill_dir: Option<PathBuf>,
+    next_spill_id: u64,
+    encode: Encode<T>,
+    decode: Decode<T>,
+}
+
+impl<T> UndoHistory<T> {
+    /// Keeps at most `in_memory_limit` entries resident at once; `encode`
+    /// and `decode` round-trip an entry to bytes for spilling to disk.
+    pub fn new(
+        in_memory_limit: usize,
+        encode: impl Fn(&T) -> Vec<u8> + Send + 'static,
+        decode: impl Fn(&[u8]) -> T + Send + 'static,
+    ) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            in_memory_limit: in_memory_limit.max(1),
+            total_limit: None,
+            spill_dir: None,
+            next_spill_id: 0,
+            encode: Box::new(encode),
+            decode: Box::new(decode),
+        }
+    }
+
+    /// Enables spilling entries older than the in-memory window to disk
+    /// under `dir` instead of discarding them; `None` disables spilling
+    /// again (entries beyond the window are then simply kept in memory,
+    /// unless [`set_total_limit`](Self::set_total_limit) is also set).
+    pub fn set_spill_dir(&mut self, dir: Option<PathBuf>) {
+        self.spill_dir = dir;
+    }
+
+    /// Caps the total number of entries, in memory and spilled combined;
+    /// pushing past this discards the oldest entry entirely, deleting its
+    /// spill file if it had one. `None` means no cap.
+    pub fn set_total_limit(&mut self, limit: Option<usize>) {
+        self.total_limit = limit;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Records a new undo entry, spilling the oldest in-memory entry to
+    /// disk once the in-memory window is exceeded (if a spill directory is
+    /// configured), and dropping the oldest entry entirely once the total
+    /// cap is exceeded.
+    pub fn push(&mut self, description: impl Into<String>, state: T) -> io::Result<()> {
+        self.entries.push_back(Entry { description: description.into(), location: Location::InMemory(state) });
+        self.spill_overflow()?;
+        self.enforce_total_limit();
+        Ok(())
+    }
+
+    fn spill_overflow(&mut self) -> io::Result<()> {
+        let Some(spill_dir) = self.spill_dir.clone() else { return Ok(()) };
+        loop {
+            let in_memory_count = self.entries.iter().filter(|e| matches!(e.location, Location::InMemory(_))).count();
+            if in_memory_count <= self.in_memory_limit {
+                return Ok(());
+            }
+            let Some(entry) = self.entries.iter_mut().find(|e| matches!(e.location, Location::InMemory(_))) else {
+                return Ok(());
+            };
+            let Location::InMemory(value) = &entry.location else { unreachable!("just matched above") };
+            fs::create_dir_all(&spill_dir)?;
+            let path = spill_dir.join(format!("undo-{:016x}.bin", self.next_spill_id));
+            self.next_spill_id += 1;
+            fs::write(&path, (self.encode)(value))?;
+            entry.location = Location::OnDisk(path);
+        }
+    }
+
+    fn enforce_total_limit(&mut self) {
+        let Some(total_limit) = self.total_limit else { return };
+        while self.entries.len() > total_limit {
+            if let Some(dropped) = self.entries.pop_front() {
+                if let Location::OnDisk(path) = dropped.location {
+                    let _ = fs::remove_file(path);
+                }
+            }
+        }
+    }
+
+    /// Pops the most recent entry, transparently reading it back from disk
+    /// if it had been spilled there, and returns its description and
+    /// state. Returns `Ok(None)` once the history is empty.
+    pub fn undo(&mut self) -> io::Result<Option<(String, T)>> {
+        let Some(entry) = self.entries.pop_back() else { return Ok(None) };
+        let state = match entry.location {
+            Location::InMemory(value) => value,
+            Location::OnDisk(path) => {
+                let bytes = fs::read(&path)?;
+                let _ = fs::remove_file(&path);
+                (self.decode)(&bytes)
+            }
+        };
+        Ok(Some((entry.description, state)))
+    }
+}
+
+impl<T> Drop for UndoHistory<T> {
+    fn drop(&mut self) {
+        for entry in self.entries.drain(..) {
+            if let Location::OnDisk(path) = entry.location {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_u32(value: &u32) -> Vec<u8> {
+        value.to_le_bytes().to_vec()
+    }
+
+    fn decode_u32(bytes: &[u8]) -> u32 {
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dropseed_undo_history_test_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn undo_without_a_spill_dir_simply_keeps_everything_in_memory() {
+        let mut history = UndoHistory::new(2, encode_u32, decode_u32);
+        history.push("one", 1).unwrap();
+        history.push("two", 2).unwrap();
+        history.push("three", 3).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.undo().unwrap(), Some(("three".to_string(), 3)));
+    }
+
+    #[test]
+    fn entries_older_than_the_window_spill_to_disk_and_restore_transparently_on_undo() {
+        let dir = temp_dir("spill");
+        let mut history = UndoHistory::new(1, encode_u32, decode_u32);
+        history.set_spill_dir(Some(dir.clone()));
+
+        history.push("one", 1).unwrap();
+        history.push("two", 2).unwrap();
+        history.push("three", 3).unwrap();
+        // "one" and "two" should have spilled, leaving "three" resident.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        assert_eq!(history.undo().unwrap(), Some(("three".to_string(), 3)));
+        assert_eq!(history.undo().unwrap(), Some(("two".to_string(), 2)));
+        assert_eq!(history.undo().unwrap(), Some(("one".to_string(), 1)));
+        assert_eq!(history.undo().unwrap(), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn enabling_the_spill_dir_late_catches_up_the_whole_backlog_in_one_push() {
+        let dir = temp_dir("late_spill_catch_up");
+        let mut history = UndoHistory::new(1, encode_u32, decode_u32);
+
+        for i in 0..10 {
+            history.push(i.to_string(), i).unwrap();
+        }
+        assert_eq!(history.len(), 10);
+
+        history.set_spill_dir(Some(dir.clone()));
+        history.push("ten", 10).unwrap();
+
+        // Only the in-memory window should still be resident; the rest of
+        // the backlog must spill in this one push, not trickle out one
+        // entry per subsequent push.
+        let in_memory_count = history.entries.iter().filter(|e| matches!(e.location, Location::InMemory(_))).count();
+        assert_eq!(in_memory_count, 1);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 10);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_total_limit_discards_the_oldest_entry_even_if_it_was_spilled() {
+        let dir = temp_dir("total_limit");
+        let mut history = UndoHistory::new(1, encode_u32, decode_u32);
+        history.set_spill_dir(Some(dir.clone()));
+        history.set_total_limit(Some(2));
+
+        history.push("one", 1).unwrap();
+        history.push("two", 2).unwrap();
+        history.push("three", 3).unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.undo().unwrap(), Some(("three".to_string(), 3)));
+        assert_eq!(history.undo().unwrap(), Some(("two".to_string(), 2)));
+        assert_eq!(history.undo().unwrap(), None, "\"one\" was discarded once the total cap was exceeded");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dropping_the_history_cleans_up_any_remaining_spill_files() {
+        let dir = temp_dir("drop_cleanup");
+        {
+            let mut history = UndoHistory::new(1, encode_u32, decode_u32);
+            history.set_spill_dir(Some(dir.clone()));
+            history.push("one", 1).unwrap();
+            history.push("two", 2).unwrap();
+            assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
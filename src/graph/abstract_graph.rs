@@ -0,0 +1,87 @@
+//! An immutable, cloneable snapshot of the audio graph's topology, used as
+//! the input to [`compile`](crate::graph::schedule::compile) so compilation
+//! can run against a stable view while the live graph keeps changing.
+
+use crate::id::PluginInstanceID;
+
+/// The set of nodes and directed edges (audio/event dependencies) that make
+/// up the graph, independent of any compiled processing order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AbstractGraph {
+    nodes: Vec<PluginInstanceID>,
+    edges: Vec<(PluginInstanceID, PluginInstanceID)>,
+}
+
+impl AbstractGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `node` if it isn't already present.
+    pub fn add_node(&mut self, node: PluginInstanceID) {
+        if !self.nodes.contains(&node) {
+            self.nodes.push(node);
+        }
+    }
+
+    /// Records a dependency edge: `to` must be processed after `from`.
+    /// Both endpoints must already have been added with [`add_node`].
+    pub fn connect(&mut self, from: PluginInstanceID, to: PluginInstanceID) {
+        self.edges.push((from, to));
+    }
+
+    /// Removes a previously recorded dependency edge, if present.
+    pub fn disconnect(&mut self, from: PluginInstanceID, to: PluginInstanceID) {
+        self.edges.retain(|&edge| edge != (from, to));
+    }
+
+    pub fn nodes(&self) -> &[PluginInstanceID] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[(PluginInstanceID, PluginInstanceID)] {
+        &self.edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_the_same_node_twice_keeps_a_single_entry() {
+        let mut graph = AbstractGraph::new();
+        let node = PluginInstanceID::new();
+        graph.add_node(node);
+        graph.add_node(node);
+        assert_eq!(graph.nodes().len(), 1);
+    }
+
+    #[test]
+    fn disconnecting_an_edge_removes_only_that_edge() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.add_node(c);
+        graph.connect(a, b);
+        graph.connect(a, c);
+
+        graph.disconnect(a, b);
+        assert_eq!(graph.edges(), &[(a, c)]);
+    }
+
+    #[test]
+    fn disconnecting_an_edge_that_does_not_exist_is_a_no_op() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        graph.disconnect(a, b);
+        assert!(graph.edges().is_empty());
+    }
+}
@@ -0,0 +1,402 @@
+//! Declared audio port layout per plugin: how many audio ports a plugin
+//! exposes, and whether each is its main signal path or an auxiliary
+//! (sidechain) input/output.
+//!
+//! [`AbstractGraph`](super::abstract_graph::AbstractGraph)'s dependency
+//! edges only order *plugins*; they don't say which port on either side a
+//! signal actually lands on. This table is the surface a host consults (or
+//! a plugin's CLAP `audio-ports` extension populates) to find a plugin's
+//! sidechain input before targeting it with
+//! [`GraphEditRequest::connect_port`](super::edit::GraphEditRequest::connect_port).
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+
+/// Whether an audio port is a plugin's primary signal path or an auxiliary
+/// input/output such as a sidechain, mirroring CLAP's
+/// `CLAP_AUDIO_PORT_IS_MAIN` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioPortKind {
+    Main,
+    Sidechain,
+}
+
+/// An audio port's channel layout, read from CLAP port-info (port-type and,
+/// for ambisonic ports, the ambisonic extension's order field).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    Mono,
+    Stereo,
+    /// A named surround configuration (5.1, 7.1, ...), identified by its
+    /// channel count since that's what distinguishes routing compatibility.
+    Surround { channel_count: u32 },
+    /// A full-sphere ambisonic signal at the given order, with
+    /// `(order + 1)^2` channels.
+    Ambisonic { order: u32 },
+}
+
+impl ChannelLayout {
+    /// How many audio channels this layout occupies.
+    pub fn channel_count(self) -> u32 {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+            ChannelLayout::Surround { channel_count } => channel_count,
+            ChannelLayout::Ambisonic { order } => (order + 1) * (order + 1),
+        }
+    }
+}
+
+/// One audio port's declared shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioPortInfo {
+    pub port_index: u32,
+    pub kind: AudioPortKind,
+    pub channel_count: u32,
+    pub layout: ChannelLayout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PortKey {
+    plugin: PluginInstanceID,
+    port_index: u32,
+}
+
+/// One alternate port layout a plugin offers, mirroring CLAP's
+/// `audio-ports-config` extension (e.g. a synth offering mono, stereo, and
+/// quad output configurations).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioPortsConfig {
+    pub config_id: u32,
+    pub name: String,
+    pub inputs: Vec<AudioPortInfo>,
+    pub outputs: Vec<AudioPortInfo>,
+}
+
+#[derive(Debug, Default)]
+struct ConfigState {
+    configs: Vec<AudioPortsConfig>,
+    active: Option<u32>,
+}
+
+/// Which aspects of a plugin's audio ports changed, mirroring the
+/// `CLAP_AUDIO_PORTS_RESCAN_*` flags passed to the `clap.audio-ports` host
+/// callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AudioPortsRescanFlags {
+    /// A port's display name changed.
+    pub names: bool,
+    /// A port's [`AudioPortKind`] (main vs. sidechain) changed.
+    pub port_kind: bool,
+    /// A port's channel count or [`ChannelLayout`] changed.
+    pub channel_count: bool,
+    /// Ports were added or removed.
+    pub list: bool,
+    /// The in-place-processing input/output pairing hints changed.
+    pub in_place_pair: bool,
+}
+
+impl AudioPortsRescanFlags {
+    /// Whether these flags require the heavyweight deactivate/reactivate
+    /// cycle: the port list itself moved, or the in-place-pair hints did,
+    /// neither of which can be patched into a running plugin. Everything
+    /// else (names, kind, channel count on an unchanged list) can be
+    /// applied in place.
+    pub fn requires_restart(self) -> bool {
+        self.list || self.in_place_pair
+    }
+}
+
+/// What [`PluginAudioPorts::apply_rescan`] did with a rescan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioPortsRescanOutcome {
+    /// The new port info was patched into the cache directly; no restart
+    /// was needed.
+    AppliedInPlace,
+    /// `flags` demanded the heavyweight path; nothing was changed. The
+    /// caller is responsible for deactivating and reactivating the plugin
+    /// and then re-declaring its ports from scratch.
+    RequiresRestart,
+}
+
+/// A registry of every plugin's declared input and output audio ports.
+#[derive(Debug, Default)]
+pub struct PluginAudioPorts {
+    inputs: HashMap<PortKey, AudioPortInfo>,
+    outputs: HashMap<PortKey, AudioPortInfo>,
+    configs: HashMap<PluginInstanceID, ConfigState>,
+}
+
+impl PluginAudioPorts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn declare_input(&mut self, plugin: PluginInstanceID, info: AudioPortInfo) {
+        self.inputs.insert(PortKey { plugin, port_index: info.port_index }, info);
+    }
+
+    pub fn declare_output(&mut self, plugin: PluginInstanceID, info: AudioPortInfo) {
+        self.outputs.insert(PortKey { plugin, port_index: info.port_index }, info);
+    }
+
+    pub fn input(&self, plugin: PluginInstanceID, port_index: u32) -> Option<AudioPortInfo> {
+        self.inputs.get(&PortKey { plugin, port_index }).copied()
+    }
+
+    pub fn output(&self, plugin: PluginInstanceID, port_index: u32) -> Option<AudioPortInfo> {
+        self.outputs.get(&PortKey { plugin, port_index }).copied()
+    }
+
+    /// Every declared input port on `plugin` whose kind is
+    /// [`AudioPortKind::Sidechain`], the candidates a host would offer when
+    /// letting the user pick a sidechain routing target.
+    pub fn sidechain_inputs(&self, plugin: PluginInstanceID) -> Vec<AudioPortInfo> {
+        let mut ports: Vec<AudioPortInfo> = self
+            .inputs
+            .iter()
+            .filter(|(key, info)| key.plugin == plugin && info.kind == AudioPortKind::Sidechain)
+            .map(|(_, info)| *info)
+            .collect();
+        ports.sort_by_key(|info| info.port_index);
+        ports
+    }
+
+    /// Drops every declared port for a plugin, e.g. when it is removed from
+    /// the graph.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.inputs.retain(|key, _| key.plugin != plugin);
+        self.outputs.retain(|key, _| key.plugin != plugin);
+        self.configs.remove(&plugin);
+    }
+
+    /// Declares the alternate port configurations `plugin`'s `audio-ports-config`
+    /// extension reports (e.g. mono/stereo/surround), without changing its
+    /// currently active ports.
+    pub fn declare_configs(&mut self, plugin: PluginInstanceID, configs: Vec<AudioPortsConfig>) {
+        self.configs.entry(plugin).or_default().configs = configs;
+    }
+
+    /// Every alternate port configuration declared for `plugin`.
+    pub fn configs(&self, plugin: PluginInstanceID) -> &[AudioPortsConfig] {
+        self.configs.get(&plugin).map(|state| state.configs.as_slice()).unwrap_or(&[])
+    }
+
+    /// The `config_id` of `plugin`'s currently active configuration, if one
+    /// has been selected via [`select_config`](Self::select_config).
+    pub fn active_config(&self, plugin: PluginInstanceID) -> Option<u32> {
+        self.configs.get(&plugin).and_then(|state| state.active)
+    }
+
+    /// Switches `plugin` to the configuration `config_id`, replacing its
+    /// declared input/output ports with that configuration's ports.
+    ///
+    /// Returns `false` if no configuration with that id was declared. A
+    /// real plugin's `audio-ports-config` extension requires a
+    /// deactivate/activate cycle for a switch to take effect; this only
+    /// updates the declared port layout the host reads afterward. The
+    /// caller is responsible for driving the actual restart, e.g. by
+    /// deactivating and reactivating the plugins in its isolation group.
+    pub fn select_config(&mut self, plugin: PluginInstanceID, config_id: u32) -> bool {
+        let Some(config) = self.configs.get(&plugin).and_then(|state| {
+            state.configs.iter().find(|config| config.config_id == config_id).cloned()
+        }) else {
+            return false;
+        };
+
+        self.inputs.retain(|key, _| key.plugin != plugin);
+        self.outputs.retain(|key, _| key.plugin != plugin);
+        for input in &config.inputs {
+            self.declare_input(plugin, *input);
+        }
+        for output in &config.outputs {
+            self.declare_output(plugin, *output);
+        }
+        self.configs.entry(plugin).or_default().active = Some(config_id);
+        true
+    }
+
+    /// Applies a `clap.audio-ports` rescan: when `flags` don't require a
+    /// restart (per [`AudioPortsRescanFlags::requires_restart`]), overwrites
+    /// `plugin`'s cached port info with `inputs`/`outputs` in place and
+    /// returns [`AudioPortsRescanOutcome::AppliedInPlace`]. Otherwise
+    /// changes nothing and returns
+    /// [`AudioPortsRescanOutcome::RequiresRestart`], leaving the
+    /// deactivate/reactivate cycle (and re-declaring ports from scratch
+    /// afterward) to the caller, the same way a full port-list change
+    /// always has.
+    pub fn apply_rescan(
+        &mut self,
+        plugin: PluginInstanceID,
+        flags: AudioPortsRescanFlags,
+        inputs: &[AudioPortInfo],
+        outputs: &[AudioPortInfo],
+    ) -> AudioPortsRescanOutcome {
+        if flags.requires_restart() {
+            return AudioPortsRescanOutcome::RequiresRestart;
+        }
+        for &input in inputs {
+            self.declare_input(plugin, input);
+        }
+        for &output in outputs {
+            self.declare_output(plugin, output);
+        }
+        AudioPortsRescanOutcome::AppliedInPlace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout_for(channels: u32) -> ChannelLayout {
+        match channels {
+            1 => ChannelLayout::Mono,
+            2 => ChannelLayout::Stereo,
+            n => ChannelLayout::Surround { channel_count: n },
+        }
+    }
+
+    fn main_in(index: u32, channels: u32) -> AudioPortInfo {
+        AudioPortInfo { port_index: index, kind: AudioPortKind::Main, channel_count: channels, layout: layout_for(channels) }
+    }
+
+    fn sidechain_in(index: u32, channels: u32) -> AudioPortInfo {
+        AudioPortInfo {
+            port_index: index,
+            kind: AudioPortKind::Sidechain,
+            channel_count: channels,
+            layout: layout_for(channels),
+        }
+    }
+
+    fn mono_config() -> AudioPortsConfig {
+        AudioPortsConfig { config_id: 0, name: "Mono".into(), inputs: vec![], outputs: vec![main_in(0, 1)] }
+    }
+
+    fn stereo_config() -> AudioPortsConfig {
+        AudioPortsConfig { config_id: 1, name: "Stereo".into(), inputs: vec![], outputs: vec![main_in(0, 2)] }
+    }
+
+    #[test]
+    fn selecting_a_declared_config_replaces_the_active_ports() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_output(plugin, main_in(0, 1));
+        ports.declare_configs(plugin, vec![mono_config(), stereo_config()]);
+
+        assert!(ports.select_config(plugin, 1));
+        assert_eq!(ports.output(plugin, 0), Some(main_in(0, 2)));
+        assert_eq!(ports.active_config(plugin), Some(1));
+    }
+
+    #[test]
+    fn selecting_an_unknown_config_id_is_rejected_and_leaves_ports_unchanged() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_output(plugin, main_in(0, 1));
+        ports.declare_configs(plugin, vec![mono_config()]);
+
+        assert!(!ports.select_config(plugin, 99));
+        assert_eq!(ports.output(plugin, 0), Some(main_in(0, 1)));
+        assert_eq!(ports.active_config(plugin), None);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_declared_configs() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_configs(plugin, vec![mono_config()]);
+        ports.remove_plugin(plugin);
+        assert!(ports.configs(plugin).is_empty());
+    }
+
+    #[test]
+    fn ambisonic_channel_count_follows_the_order() {
+        assert_eq!(ChannelLayout::Ambisonic { order: 0 }.channel_count(), 1);
+        assert_eq!(ChannelLayout::Ambisonic { order: 1 }.channel_count(), 4);
+        assert_eq!(ChannelLayout::Ambisonic { order: 3 }.channel_count(), 16);
+    }
+
+    #[test]
+    fn declared_ports_are_retrievable_by_index() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_input(plugin, main_in(0, 2));
+        assert_eq!(ports.input(plugin, 0), Some(main_in(0, 2)));
+        assert_eq!(ports.input(plugin, 1), None);
+    }
+
+    #[test]
+    fn sidechain_inputs_filters_out_main_ports_and_other_plugins() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        let other = PluginInstanceID::new();
+        ports.declare_input(plugin, main_in(0, 2));
+        ports.declare_input(plugin, sidechain_in(1, 2));
+        ports.declare_input(other, sidechain_in(0, 2));
+
+        assert_eq!(ports.sidechain_inputs(plugin), vec![sidechain_in(1, 2)]);
+    }
+
+    #[test]
+    fn sidechain_inputs_are_sorted_by_port_index() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_input(plugin, sidechain_in(2, 1));
+        ports.declare_input(plugin, sidechain_in(1, 1));
+
+        assert_eq!(ports.sidechain_inputs(plugin), vec![sidechain_in(1, 1), sidechain_in(2, 1)]);
+    }
+
+    #[test]
+    fn a_light_rescan_patches_channel_count_in_place() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_output(plugin, main_in(0, 1));
+
+        let flags = AudioPortsRescanFlags { channel_count: true, ..Default::default() };
+        let outcome = ports.apply_rescan(plugin, flags, &[], &[main_in(0, 2)]);
+
+        assert_eq!(outcome, AudioPortsRescanOutcome::AppliedInPlace);
+        assert_eq!(ports.output(plugin, 0), Some(main_in(0, 2)));
+    }
+
+    #[test]
+    fn a_list_rescan_requires_a_restart_and_changes_nothing() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_output(plugin, main_in(0, 1));
+
+        let flags = AudioPortsRescanFlags { list: true, ..Default::default() };
+        let outcome = ports.apply_rescan(plugin, flags, &[], &[main_in(0, 2)]);
+
+        assert_eq!(outcome, AudioPortsRescanOutcome::RequiresRestart);
+        assert_eq!(ports.output(plugin, 0), Some(main_in(0, 1)), "nothing should change without the caller restarting");
+    }
+
+    #[test]
+    fn an_in_place_pair_rescan_also_requires_a_restart() {
+        let flags = AudioPortsRescanFlags { in_place_pair: true, ..Default::default() };
+        assert!(flags.requires_restart());
+    }
+
+    #[test]
+    fn names_and_kind_changes_dont_require_a_restart() {
+        let flags = AudioPortsRescanFlags { names: true, port_kind: true, ..Default::default() };
+        assert!(!flags.requires_restart());
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_ports() {
+        let mut ports = PluginAudioPorts::new();
+        let plugin = PluginInstanceID::new();
+        ports.declare_input(plugin, main_in(0, 2));
+        ports.declare_output(plugin, main_in(0, 2));
+        ports.remove_plugin(plugin);
+
+        assert_eq!(ports.input(plugin, 0), None);
+        assert_eq!(ports.output(plugin, 0), None);
+    }
+}
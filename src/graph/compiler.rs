@@ -0,0 +1,136 @@
+//! Runs [`compile`] on a worker thread so a large graph never blocks the
+//! main thread long enough to miss a timer deadline. The previously
+//! compiled [`Schedule`] stays active (and is what the audio thread keeps
+//! running) until a newly requested compile finishes and is swapped in.
+
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::graph::abstract_graph::AbstractGraph;
+use crate::graph::schedule::{self, CompileError, Schedule};
+
+/// Reports the outcome of a background compile once it finishes.
+#[derive(Debug, Clone)]
+pub struct CompileEvent {
+    pub duration: Duration,
+    pub result: Result<(), CompileError>,
+}
+
+struct CompileOutcome {
+    schedule: Result<Schedule, CompileError>,
+    duration: Duration,
+}
+
+/// Owns the schedule the audio thread should run and, while a compile is in
+/// flight, the channel its result will arrive on.
+pub struct GraphCompiler {
+    active_schedule: Arc<Schedule>,
+    pending: Option<Receiver<CompileOutcome>>,
+}
+
+impl GraphCompiler {
+    pub fn new(initial: Schedule) -> Self {
+        Self { active_schedule: Arc::new(initial), pending: None }
+    }
+
+    /// The schedule currently in effect; unchanged until a background
+    /// compile started with [`begin_compile`](Self::begin_compile)
+    /// succeeds and is observed via [`poll`](Self::poll).
+    pub fn active_schedule(&self) -> Arc<Schedule> {
+        self.active_schedule.clone()
+    }
+
+    /// Starts compiling `snapshot` on a worker thread. A compile already in
+    /// flight is left to finish, but its result is discarded in favor of
+    /// this newer request once both are polled.
+    pub fn begin_compile(&mut self, snapshot: AbstractGraph) {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let start = Instant::now();
+            let schedule = schedule::compile(&snapshot);
+            let _ = tx.send(CompileOutcome { schedule, duration: start.elapsed() });
+        });
+        self.pending = Some(rx);
+    }
+
+    /// Non-blocking check for a finished compile. On success, swaps it in
+    /// as the active schedule; on failure, keeps the previous schedule
+    /// running. Returns `None` if no compile is in flight or it hasn't
+    /// finished yet.
+    pub fn poll(&mut self) -> Option<CompileEvent> {
+        let rx = self.pending.as_ref()?;
+        let outcome = match rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(_) => return None,
+        };
+        self.pending = None;
+
+        let result = match outcome.schedule {
+            Ok(schedule) => {
+                self.active_schedule = Arc::new(schedule);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        };
+        Some(CompileEvent { duration: outcome.duration, result })
+    }
+
+    pub fn is_compiling(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::PluginInstanceID;
+    use std::time::Duration;
+
+    fn wait_for<T>(mut f: impl FnMut() -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(v) = f() {
+                return v;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for background compile");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn a_successful_compile_swaps_in_the_new_schedule() {
+        let mut compiler = GraphCompiler::new(Schedule::default());
+        assert!(compiler.active_schedule().order().is_empty());
+
+        let node = PluginInstanceID::new();
+        let mut graph = AbstractGraph::new();
+        graph.add_node(node);
+        compiler.begin_compile(graph);
+
+        let event = wait_for(|| compiler.poll());
+        assert_eq!(event.result, Ok(()));
+        assert_eq!(compiler.active_schedule().order(), &[node]);
+        assert!(!compiler.is_compiling());
+    }
+
+    #[test]
+    fn a_failed_compile_leaves_the_previous_schedule_active() {
+        let a = PluginInstanceID::new();
+        let previous = Schedule::default();
+        let mut compiler = GraphCompiler::new(previous.clone());
+
+        let b = PluginInstanceID::new();
+        let mut cyclic = AbstractGraph::new();
+        cyclic.add_node(a);
+        cyclic.add_node(b);
+        cyclic.connect(a, b);
+        cyclic.connect(b, a);
+        compiler.begin_compile(cyclic);
+
+        let event = wait_for(|| compiler.poll());
+        assert_eq!(event.result, Err(CompileError::Cycle));
+        assert_eq!(*compiler.active_schedule(), previous);
+    }
+}
@@ -0,0 +1,94 @@
+//! Control-rate (per-block) output streams for analysis nodes.
+//!
+//! Some internal nodes (pitch detectors, beat detectors, envelope
+//! followers, ...) don't produce audio themselves, but want to publish a
+//! single value once per process block for the host or other nodes to read
+//! (e.g. to drive automation or a GUI meter). A [`ControlOutputPort`] is a
+//! lock-free single-value mailbox for exactly that: the audio thread writes
+//! once per block, and any number of readers can poll the latest value
+//! without blocking the audio thread.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// A single lock-free control-rate output value, written once per block by
+/// the audio thread and read at any rate by other threads.
+#[derive(Debug)]
+pub struct ControlOutputPort {
+    bits: AtomicU32,
+}
+
+impl ControlOutputPort {
+    pub fn new(initial: f32) -> Self {
+        Self { bits: AtomicU32::new(initial.to_bits()) }
+    }
+
+    /// Publishes a new value. Intended to be called at most once per
+    /// process block from the audio thread.
+    pub fn write(&self, value: f32) {
+        self.bits.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Reads the most recently published value.
+    pub fn read(&self) -> f32 {
+        f32::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for ControlOutputPort {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+/// A named collection of control-rate output ports belonging to one node,
+/// e.g. `"detected_pitch_hz"` and `"confidence"` for a pitch-detector node.
+#[derive(Debug, Default)]
+pub struct ControlOutputBank {
+    ports: Vec<(String, Arc<ControlOutputPort>)>,
+}
+
+impl ControlOutputBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a new named control output, returning a shared handle that
+    /// the node keeps to write to it, while the bank owns the canonical
+    /// list of outputs for lookup by name.
+    pub fn declare(&mut self, name: &str, initial: f32) -> Arc<ControlOutputPort> {
+        let port = Arc::new(ControlOutputPort::new(initial));
+        self.ports.push((name.to_string(), port.clone()));
+        port
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<ControlOutputPort>> {
+        self.ports.iter().find(|(n, _)| n == name).map(|(_, p)| p)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.ports.iter().map(|(n, _)| n.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_latest_written_value() {
+        let port = ControlOutputPort::new(0.0);
+        assert_eq!(port.read(), 0.0);
+        port.write(440.0);
+        assert_eq!(port.read(), 440.0);
+    }
+
+    #[test]
+    fn bank_looks_up_declared_ports_by_name() {
+        let mut bank = ControlOutputBank::new();
+        let pitch = bank.declare("detected_pitch_hz", 0.0);
+        pitch.write(220.0);
+        assert_eq!(bank.get("detected_pitch_hz").unwrap().read(), 220.0);
+        assert!(bank.get("missing").is_none());
+    }
+}
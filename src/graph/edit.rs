@@ -0,0 +1,454 @@
+//! Batch edge-connection requests against an [`AbstractGraph`], with a
+//! structured per-edge result instead of logging each failure and moving
+//! on to the next one.
+
+use std::collections::HashSet;
+
+use crate::graph::abstract_graph::AbstractGraph;
+use crate::graph::audio_ports::PluginAudioPorts;
+use crate::id::PluginInstanceID;
+
+/// A directed edge: `to` must be processed after `from`.
+type Edge = (PluginInstanceID, PluginInstanceID);
+
+/// A directed edge that targets a specific port on each side instead of a
+/// plugin's default main port, e.g. routing one plugin's aux output into
+/// another's sidechain input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortEdge {
+    pub from: PluginInstanceID,
+    pub from_port: u32,
+    pub to: PluginInstanceID,
+    pub to_port: u32,
+}
+
+/// A batch of edges to connect in a single graph modification.
+#[derive(Debug, Clone, Default)]
+pub struct GraphEditRequest {
+    edges: Vec<Edge>,
+    port_edges: Vec<PortEdge>,
+}
+
+impl GraphEditRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues an edge to connect when this request is applied.
+    pub fn connect(mut self, from: PluginInstanceID, to: PluginInstanceID) -> Self {
+        self.edges.push((from, to));
+        self
+    }
+
+    /// Queues a port-targeted edge, e.g. connecting `from`'s output port
+    /// `from_port` into `to`'s sidechain input at `to_port`. This still
+    /// orders `to` after `from` in the compiled schedule exactly like
+    /// [`connect`](Self::connect); the port indices are extra routing
+    /// metadata surfaced through [`GraphEditReport::port_results`] for the
+    /// host to wire the actual signal path with.
+    pub fn connect_port(mut self, from: PluginInstanceID, from_port: u32, to: PluginInstanceID, to_port: u32) -> Self {
+        self.port_edges.push(PortEdge { from, from_port, to, to_port });
+        self
+    }
+}
+
+/// The outcome of one edge within a [`GraphEditRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdgeOutcome {
+    /// The edge was added to the graph.
+    Added,
+    /// The edge already existed (either already in the graph, or requested
+    /// more than once in the same batch); the request left it alone.
+    SkippedDuplicate,
+    /// The edge could not be added, with a human-readable reason.
+    Failed(String),
+}
+
+/// One requested edge and what happened to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeResult {
+    pub edge: (PluginInstanceID, PluginInstanceID),
+    pub outcome: EdgeOutcome,
+}
+
+/// One requested port-targeted edge and what happened to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortEdgeResult {
+    pub edge: PortEdge,
+    pub outcome: EdgeOutcome,
+}
+
+/// The result of applying a [`GraphEditRequest`]: one [`EdgeResult`] (or
+/// [`PortEdgeResult`] for a port-targeted edge) per distinct requested
+/// edge, sorted by endpoint so a host can show the user exactly which
+/// connections failed and why without scraping logs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphEditReport {
+    results: Vec<EdgeResult>,
+    port_results: Vec<PortEdgeResult>,
+}
+
+impl GraphEditReport {
+    pub fn results(&self) -> &[EdgeResult] {
+        &self.results
+    }
+
+    pub fn port_results(&self) -> &[PortEdgeResult] {
+        &self.port_results
+    }
+
+    /// Whether every edge in the request was added or already present; if
+    /// `false`, at least one edge in [`results`](Self::results) or
+    /// [`port_results`](Self::port_results) failed.
+    pub fn all_succeeded(&self) -> bool {
+        self.results.iter().all(|r| !matches!(r.outcome, EdgeOutcome::Failed(_)))
+            && self.port_results.iter().all(|r| !matches!(r.outcome, EdgeOutcome::Failed(_)))
+    }
+}
+
+/// Adds the dependency edge `(from, to)` to `graph` if both endpoints are
+/// known nodes and it isn't already present, returning what happened.
+fn apply_dependency(graph: &mut AbstractGraph, from: PluginInstanceID, to: PluginInstanceID) -> EdgeOutcome {
+    if !graph.nodes().contains(&from) || !graph.nodes().contains(&to) {
+        EdgeOutcome::Failed("unknown plugin instance".to_string())
+    } else if graph.edges().contains(&(from, to)) {
+        EdgeOutcome::SkippedDuplicate
+    } else {
+        graph.connect(from, to);
+        EdgeOutcome::Added
+    }
+}
+
+/// Applies `request` to `graph`, adding each edge whose endpoints are
+/// already-known nodes and that isn't already present, and returns a
+/// sorted, deduplicated report of what happened to every requested edge.
+pub fn apply_graph_edit(graph: &mut AbstractGraph, request: GraphEditRequest) -> GraphEditReport {
+    apply_graph_edit_inner(graph, request, |_| Ok(()))
+}
+
+/// Builds a request that daisy-chains `ids` in order: `ids[0] -> ids[1] ->
+/// ids[2] -> ...`, the common case of wiring a stereo effects chain in
+/// series without writing out each `connect` call by hand.
+pub fn connect_stereo_chain(ids: &[PluginInstanceID]) -> GraphEditRequest {
+    ids.windows(2).fold(GraphEditRequest::new(), |request, pair| request.connect(pair[0], pair[1]))
+}
+
+/// Builds a request connecting `src` to every plugin in `dsts`, e.g.
+/// sending one source to several parallel effect sends at once.
+pub fn fan_out(src: PluginInstanceID, dsts: &[PluginInstanceID]) -> GraphEditRequest {
+    dsts.iter().fold(GraphEditRequest::new(), |request, &dst| request.connect(src, dst))
+}
+
+/// Removes every edge touching `plugin`, as either source or destination,
+/// from `graph`. Returns how many edges were removed.
+pub fn disconnect_all(graph: &mut AbstractGraph, plugin: PluginInstanceID) -> usize {
+    let to_remove: Vec<_> =
+        graph.edges().iter().copied().filter(|&(from, to)| from == plugin || to == plugin).collect();
+    for &(from, to) in &to_remove {
+        graph.disconnect(from, to);
+    }
+    to_remove.len()
+}
+
+/// Checks that `port_edge`'s source and destination ports have the same
+/// channel count, so a higher channel-count output (e.g. 5.1) isn't
+/// silently wired into a narrower input (e.g. stereo) channel-by-channel.
+fn check_layout(ports: &PluginAudioPorts, port_edge: PortEdge) -> Result<(), String> {
+    let output = ports.output(port_edge.from, port_edge.from_port).ok_or("source port not declared")?;
+    let input = ports.input(port_edge.to, port_edge.to_port).ok_or("destination port not declared")?;
+    if output.channel_count != input.channel_count {
+        return Err(format!(
+            "channel layout mismatch: {:?} ({} ch) into {:?} ({} ch)",
+            output.layout, output.channel_count, input.layout, input.channel_count
+        ));
+    }
+    Ok(())
+}
+
+/// Like [`apply_graph_edit`], but also validates each port-targeted edge's
+/// channel layout against `ports`' declared port shapes, failing a
+/// [`PortEdge`] whose source and destination channel counts don't match
+/// instead of connecting it anyway.
+pub fn apply_graph_edit_with_layout_checks(
+    graph: &mut AbstractGraph,
+    request: GraphEditRequest,
+    ports: &PluginAudioPorts,
+) -> GraphEditReport {
+    apply_graph_edit_inner(graph, request, |port_edge| check_layout(ports, port_edge))
+}
+
+fn apply_graph_edit_inner(
+    graph: &mut AbstractGraph,
+    request: GraphEditRequest,
+    validate_port: impl Fn(PortEdge) -> Result<(), String>,
+) -> GraphEditReport {
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+
+    for edge in request.edges {
+        if !seen.insert(edge) {
+            continue;
+        }
+        let (from, to) = edge;
+        results.push(EdgeResult { edge, outcome: apply_dependency(graph, from, to) });
+    }
+    results.sort_by_key(|r| r.edge);
+
+    let mut seen_ports = HashSet::new();
+    let mut port_results = Vec::new();
+    for port_edge in request.port_edges {
+        if !seen_ports.insert(port_edge) {
+            continue;
+        }
+        let outcome = match validate_port(port_edge) {
+            Err(reason) => EdgeOutcome::Failed(reason),
+            Ok(()) => apply_dependency(graph, port_edge.from, port_edge.to),
+        };
+        port_results.push(PortEdgeResult { edge: port_edge, outcome });
+    }
+    port_results.sort_by_key(|r| (r.edge.from, r.edge.from_port, r.edge.to, r.edge.to_port));
+
+    GraphEditReport { results, port_results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_edges_are_added_and_reported() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let report = apply_graph_edit(&mut graph, GraphEditRequest::new().connect(a, b));
+        assert_eq!(report.results(), &[EdgeResult { edge: (a, b), outcome: EdgeOutcome::Added }]);
+        assert!(report.all_succeeded());
+        assert_eq!(graph.edges(), &[(a, b)]);
+    }
+
+    #[test]
+    fn duplicate_edges_within_a_request_are_reported_once() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let report = apply_graph_edit(&mut graph, GraphEditRequest::new().connect(a, b).connect(a, b));
+        assert_eq!(report.results().len(), 1);
+        assert_eq!(graph.edges(), &[(a, b)]);
+    }
+
+    #[test]
+    fn an_already_existing_edge_is_skipped_not_failed() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.connect(a, b);
+
+        let report = apply_graph_edit(&mut graph, GraphEditRequest::new().connect(a, b));
+        assert_eq!(report.results(), &[EdgeResult { edge: (a, b), outcome: EdgeOutcome::SkippedDuplicate }]);
+        assert!(report.all_succeeded());
+    }
+
+    #[test]
+    fn an_edge_to_an_unknown_node_fails_without_aborting_the_rest() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let unknown = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let report = apply_graph_edit(&mut graph, GraphEditRequest::new().connect(a, unknown).connect(a, b));
+        assert!(!report.all_succeeded());
+        assert_eq!(graph.edges(), &[(a, b)]);
+        assert!(report
+            .results()
+            .iter()
+            .any(|r| r.edge == (a, unknown) && matches!(r.outcome, EdgeOutcome::Failed(_))));
+    }
+
+    #[test]
+    fn a_port_edge_is_added_as_a_dependency_and_reported_with_its_ports() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let report = apply_graph_edit(&mut graph, GraphEditRequest::new().connect_port(a, 1, b, 2));
+        assert_eq!(
+            report.port_results(),
+            &[PortEdgeResult { edge: PortEdge { from: a, from_port: 1, to: b, to_port: 2 }, outcome: EdgeOutcome::Added }]
+        );
+        assert!(report.all_succeeded());
+        assert_eq!(graph.edges(), &[(a, b)]);
+    }
+
+    #[test]
+    fn a_port_edge_to_an_unknown_node_fails_without_affecting_plain_edges() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let unknown = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let report =
+            apply_graph_edit(&mut graph, GraphEditRequest::new().connect(a, b).connect_port(a, 0, unknown, 0));
+        assert!(!report.all_succeeded());
+        assert_eq!(report.results(), &[EdgeResult { edge: (a, b), outcome: EdgeOutcome::Added }]);
+        assert!(matches!(report.port_results()[0].outcome, EdgeOutcome::Failed(_)));
+    }
+
+    #[test]
+    fn results_are_sorted_by_edge_regardless_of_request_order() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let report = apply_graph_edit(&mut graph, GraphEditRequest::new().connect(b, a).connect(a, b));
+        let edges: Vec<_> = report.results().iter().map(|r| r.edge).collect();
+        let mut sorted = edges.clone();
+        sorted.sort();
+        assert_eq!(edges, sorted);
+    }
+
+    fn port_info(index: u32, channels: u32) -> crate::graph::audio_ports::AudioPortInfo {
+        use crate::graph::audio_ports::{AudioPortKind, ChannelLayout};
+        let layout = match channels {
+            1 => ChannelLayout::Mono,
+            2 => ChannelLayout::Stereo,
+            n => ChannelLayout::Surround { channel_count: n },
+        };
+        crate::graph::audio_ports::AudioPortInfo { port_index: index, kind: AudioPortKind::Main, channel_count: channels, layout }
+    }
+
+    #[test]
+    fn a_port_edge_with_matching_channel_counts_is_accepted() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let mut ports = PluginAudioPorts::new();
+        ports.declare_output(a, port_info(0, 2));
+        ports.declare_input(b, port_info(0, 2));
+
+        let report = apply_graph_edit_with_layout_checks(&mut graph, GraphEditRequest::new().connect_port(a, 0, b, 0), &ports);
+        assert!(report.all_succeeded());
+        assert_eq!(graph.edges(), &[(a, b)]);
+    }
+
+    #[test]
+    fn a_surround_output_into_a_stereo_input_is_rejected() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let mut ports = PluginAudioPorts::new();
+        ports.declare_output(a, port_info(0, 6));
+        ports.declare_input(b, port_info(0, 2));
+
+        let report = apply_graph_edit_with_layout_checks(&mut graph, GraphEditRequest::new().connect_port(a, 0, b, 0), &ports);
+        assert!(!report.all_succeeded());
+        assert!(matches!(report.port_results()[0].outcome, EdgeOutcome::Failed(_)));
+        assert!(graph.edges().is_empty());
+    }
+
+    #[test]
+    fn a_port_edge_to_an_undeclared_port_is_rejected() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+
+        let ports = PluginAudioPorts::new();
+        let report = apply_graph_edit_with_layout_checks(&mut graph, GraphEditRequest::new().connect_port(a, 0, b, 0), &ports);
+        assert!(!report.all_succeeded());
+    }
+
+    #[test]
+    fn connect_stereo_chain_wires_each_plugin_to_the_next() {
+        let mut graph = AbstractGraph::new();
+        let ids: Vec<_> = (0..4).map(|_| PluginInstanceID::new()).collect();
+        for &id in &ids {
+            graph.add_node(id);
+        }
+
+        let report = apply_graph_edit(&mut graph, connect_stereo_chain(&ids));
+        assert!(report.all_succeeded());
+        assert_eq!(graph.edges(), &[(ids[0], ids[1]), (ids[1], ids[2]), (ids[2], ids[3])]);
+    }
+
+    #[test]
+    fn connect_stereo_chain_with_fewer_than_two_ids_connects_nothing() {
+        let request = connect_stereo_chain(&[PluginInstanceID::new()]);
+        let mut graph = AbstractGraph::new();
+        let report = apply_graph_edit(&mut graph, request);
+        assert!(report.results().is_empty());
+    }
+
+    #[test]
+    fn fan_out_connects_the_source_to_every_destination() {
+        let mut graph = AbstractGraph::new();
+        let src = PluginInstanceID::new();
+        let dsts: Vec<_> = (0..3).map(|_| PluginInstanceID::new()).collect();
+        graph.add_node(src);
+        for &dst in &dsts {
+            graph.add_node(dst);
+        }
+
+        let report = apply_graph_edit(&mut graph, fan_out(src, &dsts));
+        assert!(report.all_succeeded());
+        let mut edges = graph.edges().to_vec();
+        edges.sort();
+        let mut expected: Vec<_> = dsts.iter().map(|&dst| (src, dst)).collect();
+        expected.sort();
+        assert_eq!(edges, expected);
+    }
+
+    #[test]
+    fn disconnect_all_removes_every_edge_touching_the_plugin_in_either_direction() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        for &id in &[a, b, c] {
+            graph.add_node(id);
+        }
+        graph.connect(a, b);
+        graph.connect(c, a);
+        graph.connect(b, c);
+
+        let removed = disconnect_all(&mut graph, a);
+        assert_eq!(removed, 2);
+        assert_eq!(graph.edges(), &[(b, c)]);
+    }
+
+    #[test]
+    fn disconnect_all_on_an_unconnected_plugin_removes_nothing() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.connect(a, b);
+
+        let removed = disconnect_all(&mut graph, PluginInstanceID::new());
+        assert_eq!(removed, 0);
+        assert_eq!(graph.edges(), &[(a, b)]);
+    }
+}
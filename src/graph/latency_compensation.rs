@@ -0,0 +1,201 @@
+//! Automatic plugin delay compensation (PDC).
+//!
+//! A plugin that reports processing latency (e.g. a linear-phase EQ, or
+//! [`SidechainCompressorNode`](crate::nodes::compressor::SidechainCompressorNode)'s
+//! lookahead) shifts its output later in time relative to a parallel path
+//! that bypasses it. Left uncompensated, recombining those paths downstream
+//! smears transients and breaks phase alignment. This computes, for every
+//! edge in the graph, how many extra samples of delay to insert so every
+//! path feeding a given node arrives compensated to the slowest one.
+
+use std::collections::HashMap;
+
+use crate::graph::abstract_graph::AbstractGraph;
+use crate::graph::schedule::Schedule;
+use crate::id::PluginInstanceID;
+
+/// Per-plugin reported processing latency, in samples.
+#[derive(Debug, Default)]
+pub struct PluginLatencies {
+    latencies: HashMap<PluginInstanceID, u32>,
+}
+
+impl PluginLatencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_latency(&mut self, plugin: PluginInstanceID, latency_samples: u32) {
+        self.latencies.insert(plugin, latency_samples);
+    }
+
+    pub fn latency(&self, plugin: PluginInstanceID) -> u32 {
+        self.latencies.get(&plugin).copied().unwrap_or(0)
+    }
+
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.latencies.remove(&plugin);
+    }
+}
+
+/// How much extra delay to insert on each edge, and the resulting
+/// compensated latency arriving at each node, so that every parallel path
+/// into a node lines up with the slowest one feeding it.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DelayCompensationPlan {
+    edge_delays: HashMap<(PluginInstanceID, PluginInstanceID), u32>,
+    output_latency: HashMap<PluginInstanceID, u32>,
+}
+
+impl DelayCompensationPlan {
+    /// The extra delay (samples) to insert on the edge from `from` to `to`,
+    /// `0` if that edge needs no compensation.
+    pub fn edge_delay(&self, from: PluginInstanceID, to: PluginInstanceID) -> u32 {
+        self.edge_delays.get(&(from, to)).copied().unwrap_or(0)
+    }
+
+    /// `node`'s total compensated output latency: the worst-case delay
+    /// from the start of the graph to this node's output, including its
+    /// own reported latency.
+    pub fn output_latency(&self, node: PluginInstanceID) -> u32 {
+        self.output_latency.get(&node).copied().unwrap_or(0)
+    }
+}
+
+/// Computes a [`DelayCompensationPlan`] for `graph`, processed in the order
+/// given by `schedule`, given each node's reported latency in `latencies`.
+///
+/// `schedule` must be a valid topological order for `graph` (as produced by
+/// [`compile`](crate::graph::schedule::compile)); nodes are visited in that
+/// order so every predecessor's output latency is known before it's needed.
+pub fn compute_delay_compensation(
+    schedule: &Schedule,
+    graph: &AbstractGraph,
+    latencies: &PluginLatencies,
+) -> DelayCompensationPlan {
+    let mut input_latency: HashMap<PluginInstanceID, u32> = HashMap::new();
+    let mut output_latency: HashMap<PluginInstanceID, u32> = HashMap::new();
+
+    for &node in schedule.order() {
+        let incoming = input_latency_for(node, graph, &output_latency);
+        input_latency.insert(node, incoming);
+        output_latency.insert(node, incoming + latencies.latency(node));
+    }
+
+    let mut edge_delays = HashMap::new();
+    for &(from, to) in graph.edges() {
+        let needed = input_latency.get(&to).copied().unwrap_or(0) - output_latency.get(&from).copied().unwrap_or(0);
+        if needed > 0 {
+            edge_delays.insert((from, to), needed);
+        }
+    }
+
+    DelayCompensationPlan { edge_delays, output_latency }
+}
+
+fn input_latency_for(
+    node: PluginInstanceID,
+    graph: &AbstractGraph,
+    output_latency: &HashMap<PluginInstanceID, u32>,
+) -> u32 {
+    graph
+        .edges()
+        .iter()
+        .filter(|&&(_, to)| to == node)
+        .map(|&(from, _)| output_latency.get(&from).copied().unwrap_or(0))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::schedule::compile;
+
+    #[test]
+    fn a_single_node_with_no_edges_needs_no_compensation() {
+        let node = PluginInstanceID::new();
+        let mut graph = AbstractGraph::new();
+        graph.add_node(node);
+        let schedule = compile(&graph).unwrap();
+
+        let mut latencies = PluginLatencies::new();
+        latencies.set_latency(node, 64);
+
+        let plan = compute_delay_compensation(&schedule, &graph, &latencies);
+        assert_eq!(plan.output_latency(node), 64);
+    }
+
+    #[test]
+    fn a_linear_chain_accumulates_latency_with_no_edge_delays() {
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let mut graph = AbstractGraph::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.connect(a, b);
+        let schedule = compile(&graph).unwrap();
+
+        let mut latencies = PluginLatencies::new();
+        latencies.set_latency(a, 32);
+        latencies.set_latency(b, 10);
+
+        let plan = compute_delay_compensation(&schedule, &graph, &latencies);
+        assert_eq!(plan.output_latency(a), 32);
+        assert_eq!(plan.output_latency(b), 42);
+        assert_eq!(plan.edge_delay(a, b), 0);
+    }
+
+    #[test]
+    fn a_parallel_bypass_path_is_delayed_to_match_the_slower_branch() {
+        // dry --------------------\
+        //                          -> mix
+        // wet -> (latency 50) ----/
+        let dry = PluginInstanceID::new();
+        let wet = PluginInstanceID::new();
+        let mix = PluginInstanceID::new();
+        let mut graph = AbstractGraph::new();
+        for n in [dry, wet, mix] {
+            graph.add_node(n);
+        }
+        graph.connect(dry, mix);
+        graph.connect(wet, mix);
+        let schedule = compile(&graph).unwrap();
+
+        let mut latencies = PluginLatencies::new();
+        latencies.set_latency(wet, 50);
+
+        let plan = compute_delay_compensation(&schedule, &graph, &latencies);
+        assert_eq!(plan.edge_delay(dry, mix), 50);
+        assert_eq!(plan.edge_delay(wet, mix), 0);
+        assert_eq!(plan.output_latency(mix), 50);
+    }
+
+    #[test]
+    fn an_already_balanced_graph_needs_no_delay_on_either_path() {
+        let dry = PluginInstanceID::new();
+        let wet = PluginInstanceID::new();
+        let mix = PluginInstanceID::new();
+        let mut graph = AbstractGraph::new();
+        for n in [dry, wet, mix] {
+            graph.add_node(n);
+        }
+        graph.connect(dry, mix);
+        graph.connect(wet, mix);
+        let schedule = compile(&graph).unwrap();
+
+        let latencies = PluginLatencies::new();
+        let plan = compute_delay_compensation(&schedule, &graph, &latencies);
+        assert_eq!(plan.edge_delay(dry, mix), 0);
+        assert_eq!(plan.edge_delay(wet, mix), 0);
+    }
+
+    #[test]
+    fn removing_a_plugins_latency_resets_it_to_zero() {
+        let node = PluginInstanceID::new();
+        let mut latencies = PluginLatencies::new();
+        latencies.set_latency(node, 100);
+        latencies.remove_plugin(node);
+        assert_eq!(latencies.latency(node), 0);
+    }
+}
@@ -0,0 +1,35 @@
+//! The audio graph: ports, nodes, and edges between them.
+
+pub mod abstract_graph;
+pub mod audio_ports;
+pub mod compiler;
+pub mod control_output;
+pub mod edit;
+pub mod latency_compensation;
+pub mod note_routing;
+pub mod parallel_schedule;
+pub mod port;
+pub mod routing_snapshot;
+pub mod schedule;
+pub mod terminal_ports;
+pub mod trace;
+
+pub use abstract_graph::AbstractGraph;
+pub use audio_ports::{
+    AudioPortInfo, AudioPortKind, AudioPortsConfig, AudioPortsRescanFlags, AudioPortsRescanOutcome, ChannelLayout,
+    PluginAudioPorts,
+};
+pub use compiler::{CompileEvent, GraphCompiler};
+pub use control_output::{ControlOutputBank, ControlOutputPort};
+pub use edit::{
+    apply_graph_edit, apply_graph_edit_with_layout_checks, connect_stereo_chain, disconnect_all, fan_out,
+    EdgeOutcome, EdgeResult, GraphEditRequest, GraphEditReport, PortEdge, PortEdgeResult,
+};
+pub use latency_compensation::{compute_delay_compensation, DelayCompensationPlan, PluginLatencies};
+pub use note_routing::NoteRoutingTable;
+pub use parallel_schedule::{compile_parallel, execute_parallel, EngineThreadSettings, ParallelSchedule, ParallelWorkerPool};
+pub use port::{CustomPortTypeID, CustomPortTypeRegistry, PortType};
+pub use routing_snapshot::RoutingSnapshot;
+pub use schedule::{compile, CompileError, Schedule};
+pub use terminal_ports::{TerminalDirection, TerminalPortID, TerminalPortKind, TerminalPortNames};
+pub use trace::{ConstantFlags, ScheduleTrace, TraceCapture, TraceRecorder, TracedTask};
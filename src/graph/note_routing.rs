@@ -0,0 +1,128 @@
+//! Routes CLAP note events between the graph's virtual MIDI in/out
+//! terminal ports and plugin note ports.
+//!
+//! [`AbstractGraph`](super::abstract_graph::AbstractGraph)'s edges only
+//! connect two [`PluginInstanceID`]s, and a terminal port (the host's
+//! virtual "graph in"/"graph out" boundary, see
+//! [`terminal_ports`](super::terminal_ports)) isn't one — there's no
+//! instantiated plugin backing it. So routing external MIDI into an
+//! instrument, or a plugin's note output back out to the host, is recorded
+//! here as a declarative mapping rather than a graph edge: it doesn't order
+//! anything in the compiled [`Schedule`](super::schedule::Schedule), it
+//! just tells the host (or the note-event dispatch step of a process call)
+//! which plugin note port a terminal port's events should be copied
+//! to/from.
+
+use std::collections::HashMap;
+
+use crate::graph::terminal_ports::TerminalPortID;
+use crate::id::PluginInstanceID;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PluginNotePort {
+    plugin: PluginInstanceID,
+    port_index: u32,
+}
+
+/// A registry of terminal-port-to-plugin-note-port routes.
+#[derive(Debug, Default)]
+pub struct NoteRoutingTable {
+    inputs: HashMap<TerminalPortID, PluginNotePort>,
+    outputs: HashMap<PluginNotePort, TerminalPortID>,
+}
+
+impl NoteRoutingTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Routes the virtual MIDI input terminal port `terminal` into note
+    /// port `plugin_port` on `plugin`, e.g. so host-converted CLAP note
+    /// events reach an instrument. Replaces any existing route for
+    /// `terminal`.
+    pub fn route_input(&mut self, terminal: TerminalPortID, plugin: PluginInstanceID, plugin_port: u32) {
+        self.inputs.insert(terminal, PluginNotePort { plugin, port_index: plugin_port });
+    }
+
+    /// Routes note port `plugin_port` on `plugin` out to the virtual MIDI
+    /// output terminal port `terminal`. Replaces any existing route for
+    /// that plugin note port.
+    pub fn route_output(&mut self, plugin: PluginInstanceID, plugin_port: u32, terminal: TerminalPortID) {
+        self.outputs.insert(PluginNotePort { plugin, port_index: plugin_port }, terminal);
+    }
+
+    /// The plugin note port (if any) that `terminal`'s incoming note events
+    /// should be delivered to.
+    pub fn input_route(&self, terminal: TerminalPortID) -> Option<(PluginInstanceID, u32)> {
+        self.inputs.get(&terminal).map(|route| (route.plugin, route.port_index))
+    }
+
+    /// The terminal port (if any) that `plugin`'s note port `plugin_port`
+    /// is routed out to.
+    pub fn output_route(&self, plugin: PluginInstanceID, plugin_port: u32) -> Option<TerminalPortID> {
+        self.outputs.get(&PluginNotePort { plugin, port_index: plugin_port }).copied()
+    }
+
+    /// Drops every route touching `plugin`, e.g. when it is removed from
+    /// the graph.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.inputs.retain(|_, route| route.plugin != plugin);
+        self.outputs.retain(|route, _| route.plugin != plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::terminal_ports::TerminalDirection;
+
+    #[test]
+    fn an_input_route_resolves_to_its_plugin_note_port() {
+        let mut table = NoteRoutingTable::new();
+        let midi_in = TerminalPortID::for_note_channel(TerminalDirection::GraphIn, 0);
+        let synth = PluginInstanceID::new();
+        table.route_input(midi_in, synth, 0);
+
+        assert_eq!(table.input_route(midi_in), Some((synth, 0)));
+    }
+
+    #[test]
+    fn an_output_route_resolves_to_its_terminal_port() {
+        let mut table = NoteRoutingTable::new();
+        let midi_out = TerminalPortID::for_note_channel(TerminalDirection::GraphOut, 0);
+        let arp = PluginInstanceID::new();
+        table.route_output(arp, 0, midi_out);
+
+        assert_eq!(table.output_route(arp, 0), Some(midi_out));
+    }
+
+    #[test]
+    fn routing_a_terminal_port_again_replaces_the_previous_route() {
+        let mut table = NoteRoutingTable::new();
+        let midi_in = TerminalPortID::for_note_channel(TerminalDirection::GraphIn, 0);
+        let first = PluginInstanceID::new();
+        let second = PluginInstanceID::new();
+        table.route_input(midi_in, first, 0);
+        table.route_input(midi_in, second, 1);
+
+        assert_eq!(table.input_route(midi_in), Some((second, 1)));
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_routes_without_touching_others() {
+        let mut table = NoteRoutingTable::new();
+        let midi_in = TerminalPortID::for_note_channel(TerminalDirection::GraphIn, 0);
+        let midi_out = TerminalPortID::for_note_channel(TerminalDirection::GraphOut, 0);
+        let synth = PluginInstanceID::new();
+        let other = PluginInstanceID::new();
+        table.route_input(midi_in, synth, 0);
+        table.route_output(synth, 0, midi_out);
+        table.route_input(TerminalPortID::for_note_channel(TerminalDirection::GraphIn, 1), other, 0);
+
+        table.remove_plugin(synth);
+
+        assert_eq!(table.input_route(midi_in), None);
+        assert_eq!(table.output_route(synth, 0), None);
+        assert_eq!(table.input_route(TerminalPortID::for_note_channel(TerminalDirection::GraphIn, 1)), Some((other, 0)));
+    }
+}
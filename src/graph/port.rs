@@ -0,0 +1,78 @@
+//! Port types for the audio graph.
+//!
+//! CLAP plugins identify a port's data kind with a string (`"audio"`,
+//! `"note"`, or a plugin-defined string for anything else). To keep
+//! comparisons and routing checks cheap on the audio thread, host-defined
+//! port kinds are interned into a [`CustomPortTypeID`] the first time they're
+//! seen, rather than comparing strings at connect time.
+
+use std::collections::HashMap;
+
+/// The kind of data carried by a graph port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortType {
+    Audio,
+    Note,
+    /// A host-defined data stream (e.g. CV, OSC bridging) that isn't one of
+    /// the built-in CLAP port types.
+    Custom(CustomPortTypeID),
+}
+
+/// An interned identifier for a custom, host-defined port type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomPortTypeID(u32);
+
+/// Interns custom port type name strings into [`CustomPortTypeID`]s.
+///
+/// Registering the same name twice returns the same ID, so graph nodes from
+/// different plugins that agree on a port type string (e.g. `"cv"`) can
+/// still be connected to each other.
+#[derive(Debug, Default)]
+pub struct CustomPortTypeRegistry {
+    ids_by_name: HashMap<String, CustomPortTypeID>,
+    names: Vec<String>,
+}
+
+impl CustomPortTypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interns `name`, returning its stable ID.
+    pub fn register(&mut self, name: &str) -> CustomPortTypeID {
+        if let Some(id) = self.ids_by_name.get(name) {
+            return *id;
+        }
+        let id = CustomPortTypeID(self.names.len() as u32);
+        self.names.push(name.to_string());
+        self.ids_by_name.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn name(&self, id: CustomPortTypeID) -> Option<&str> {
+        self.names.get(id.0 as usize).map(|s| s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registering_the_same_name_twice_returns_the_same_id() {
+        let mut registry = CustomPortTypeRegistry::new();
+        let a = registry.register("cv");
+        let b = registry.register("cv");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn different_names_get_different_ids() {
+        let mut registry = CustomPortTypeRegistry::new();
+        let a = registry.register("cv");
+        let b = registry.register("osc");
+        assert_ne!(a, b);
+        assert_eq!(registry.name(a), Some("cv"));
+        assert_eq!(registry.name(b), Some("osc"));
+    }
+}
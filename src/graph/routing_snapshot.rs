@@ -0,0 +1,169 @@
+//! A lightweight snapshot of the graph's routing topology plus per-plugin
+//! bypass/gain state, for comparing whole mix routings (A/B) with the
+//! fewest possible schedule recompiles on each toggle.
+//!
+//! Unlike [`ProjectSaveState`](crate::engine::ProjectSaveState), this isn't
+//! a save format: it references the same live [`PluginInstanceID`]s on
+//! both sides of the comparison rather than saved-index indirection, and
+//! it doesn't touch plugins' own internal state at all — only the routing
+//! and the host-owned bypass/gain stages around them.
+
+use std::collections::HashSet;
+
+use crate::graph::abstract_graph::AbstractGraph;
+use crate::graph::edit::{apply_graph_edit, GraphEditRequest};
+use crate::id::PluginInstanceID;
+use crate::plugin::{PluginBypassStates, PluginGainStages};
+
+/// A captured routing + bypass/gain state, ready to be re-applied later to
+/// toggle back to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingSnapshot {
+    edges: Vec<(PluginInstanceID, PluginInstanceID)>,
+    bypassed: Vec<PluginInstanceID>,
+    gains_db: Vec<(PluginInstanceID, f32, f32)>,
+}
+
+impl RoutingSnapshot {
+    /// Captures `graph`'s current edges, `bypass`'s bypassed plugins, and
+    /// `gains`'s non-default gains.
+    pub fn capture(graph: &AbstractGraph, bypass: &PluginBypassStates, gains: &PluginGainStages) -> Self {
+        Self { edges: graph.edges().to_vec(), bypassed: bypass.bypassed_plugins(), gains_db: gains.entries() }
+    }
+
+    pub fn edges(&self) -> &[(PluginInstanceID, PluginInstanceID)] {
+        &self.edges
+    }
+
+    pub fn bypassed(&self) -> &[PluginInstanceID] {
+        &self.bypassed
+    }
+
+    pub fn gains_db(&self) -> &[(PluginInstanceID, f32, f32)] {
+        &self.gains_db
+    }
+
+    /// Applies this snapshot's routing to `graph`, removing only the edges
+    /// it doesn't have and adding only the ones it's missing, instead of
+    /// rebuilding the topology from scratch — the minimal edit a compiled
+    /// schedule needs to catch up to this snapshot.
+    pub fn apply_routing(&self, graph: &mut AbstractGraph) {
+        let target: HashSet<(PluginInstanceID, PluginInstanceID)> = self.edges.iter().copied().collect();
+        for &edge in graph.edges().to_vec().iter() {
+            if !target.contains(&edge) {
+                graph.disconnect(edge.0, edge.1);
+            }
+        }
+
+        let mut request = GraphEditRequest::new();
+        for &(from, to) in &self.edges {
+            request = request.connect(from, to);
+        }
+        // Edges already present come back as `SkippedDuplicate`, not an
+        // error, so this is safe to apply unconditionally.
+        apply_graph_edit(graph, request);
+    }
+
+    /// Applies this snapshot's bypass state to `bypass`. `nodes` should be
+    /// every plugin currently in the graph, so a plugin bypassed in the
+    /// *other* snapshot gets un-bypassed rather than left stuck.
+    pub fn apply_bypass(&self, bypass: &mut PluginBypassStates, nodes: &[PluginInstanceID]) {
+        let target: HashSet<PluginInstanceID> = self.bypassed.iter().copied().collect();
+        for &id in nodes {
+            bypass.set_bypassed(id, target.contains(&id));
+        }
+    }
+
+    /// Applies this snapshot's gains to `gains`.
+    pub fn apply_gains(&self, gains: &mut PluginGainStages) {
+        for &(id, input_db, output_db) in &self.gains_db {
+            gains.set_input_gain_db(id, input_db);
+            gains.set_output_gain_db(id, output_db);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capturing_then_applying_to_a_fresh_graph_reproduces_the_topology() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.connect(a, b);
+
+        let bypass = PluginBypassStates::new();
+        let gains = PluginGainStages::new(8);
+        let snapshot = RoutingSnapshot::capture(&graph, &bypass, &gains);
+
+        let mut other = AbstractGraph::new();
+        other.add_node(a);
+        other.add_node(b);
+        snapshot.apply_routing(&mut other);
+        assert_eq!(other.edges(), &[(a, b)]);
+    }
+
+    #[test]
+    fn applying_a_snapshot_removes_edges_it_does_not_have() {
+        let mut graph = AbstractGraph::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.add_node(c);
+
+        let bypass = PluginBypassStates::new();
+        let gains = PluginGainStages::new(8);
+        graph.connect(a, b);
+        let snapshot_ab = RoutingSnapshot::capture(&graph, &bypass, &gains);
+
+        graph.disconnect(a, b);
+        graph.connect(a, c);
+
+        snapshot_ab.apply_routing(&mut graph);
+        assert_eq!(graph.edges(), &[(a, b)]);
+    }
+
+    #[test]
+    fn applying_bypass_un_bypasses_plugins_not_in_the_snapshot() {
+        let plugin_a = PluginInstanceID::new();
+        let plugin_b = PluginInstanceID::new();
+        let mut bypass = PluginBypassStates::new();
+        bypass.set_bypassed(plugin_a, true);
+
+        let graph = AbstractGraph::new();
+        let gains = PluginGainStages::new(8);
+        let snapshot = RoutingSnapshot::capture(&graph, &bypass, &gains);
+
+        bypass.set_bypassed(plugin_a, false);
+        bypass.set_bypassed(plugin_b, true);
+
+        snapshot.apply_bypass(&mut bypass, &[plugin_a, plugin_b]);
+        assert!(bypass.is_bypassed(plugin_a));
+        assert!(!bypass.is_bypassed(plugin_b));
+    }
+
+    #[test]
+    fn applying_gains_restores_the_captured_values() {
+        let mut gains = PluginGainStages::new(1);
+        let plugin = PluginInstanceID::new();
+        gains.set_input_gain_db(plugin, -6.0);
+        let mut scratch = vec![0.0_f32; 10];
+        gains.process_input(plugin, &mut scratch);
+
+        let graph = AbstractGraph::new();
+        let bypass = PluginBypassStates::new();
+        let snapshot = RoutingSnapshot::capture(&graph, &bypass, &gains);
+
+        let mut other_gains = PluginGainStages::new(1);
+        snapshot.apply_gains(&mut other_gains);
+        let mut scratch = vec![0.0_f32; 10];
+        other_gains.process_input(plugin, &mut scratch);
+        assert!((other_gains.input_gain_db(plugin) - -6.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,98 @@
+//! Compiling an [`AbstractGraph`] snapshot into a [`Schedule`]: a linear
+//! order the audio thread can process nodes in without violating any
+//! dependency edge.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::abstract_graph::AbstractGraph;
+use crate::id::PluginInstanceID;
+
+/// A compiled, sequential processing order for the audio thread.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schedule {
+    order: Vec<PluginInstanceID>,
+}
+
+impl Schedule {
+    pub fn order(&self) -> &[PluginInstanceID] {
+        &self.order
+    }
+}
+
+/// Why [`compile`] could not produce a [`Schedule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompileError {
+    /// The graph contains a dependency cycle, so no valid processing order
+    /// exists.
+    Cycle,
+}
+
+/// Topologically sorts `graph` into a [`Schedule`] via Kahn's algorithm.
+pub fn compile(graph: &AbstractGraph) -> Result<Schedule, CompileError> {
+    let mut in_degree: HashMap<PluginInstanceID, usize> =
+        graph.nodes().iter().map(|&n| (n, 0)).collect();
+    for &(_, to) in graph.edges() {
+        *in_degree.entry(to).or_insert(0) += 1;
+    }
+
+    let mut ready: VecDeque<PluginInstanceID> =
+        graph.nodes().iter().copied().filter(|n| in_degree[n] == 0).collect();
+
+    let mut order = Vec::with_capacity(graph.nodes().len());
+    while let Some(node) = ready.pop_front() {
+        order.push(node);
+        for &(from, to) in graph.edges() {
+            if from != node {
+                continue;
+            }
+            let degree = in_degree.get_mut(&to).expect("edge endpoint must be a known node");
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(to);
+            }
+        }
+    }
+
+    if order.len() != graph.nodes().len() {
+        return Err(CompileError::Cycle);
+    }
+    Ok(Schedule { order })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_nodes_after_their_dependencies() {
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+
+        let mut graph = AbstractGraph::new();
+        for n in [a, b, c] {
+            graph.add_node(n);
+        }
+        graph.connect(a, b);
+        graph.connect(b, c);
+
+        let schedule = compile(&graph).unwrap();
+        let pos = |n: PluginInstanceID| schedule.order().iter().position(|&x| x == n).unwrap();
+        assert!(pos(a) < pos(b));
+        assert!(pos(b) < pos(c));
+    }
+
+    #[test]
+    fn a_cycle_is_reported_instead_of_an_incomplete_schedule() {
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+
+        let mut graph = AbstractGraph::new();
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.connect(a, b);
+        graph.connect(b, a);
+
+        assert_eq!(compile(&graph), Err(CompileError::Cycle));
+    }
+}
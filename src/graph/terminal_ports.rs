@@ -0,0 +1,179 @@
+//! Stable IDs and host-settable display names for the graph's input/output
+//! terminal ports.
+//!
+//! Terminal ports (the host's physical channels feeding into or out of the
+//! graph) used to all share a single placeholder stable ID and were
+//! distinguished only by their channel index, which breaks the moment
+//! something downstream needs a genuinely unique per-port identifier (e.g.
+//! a CLAP `clap_id`). Each terminal port's [`TerminalPortID`] is now derived
+//! deterministically from its direction and channel index instead, so it
+//! stays stable without needing an allocator or a registry. A terminal
+//! port can also be given a host-settable display name (e.g. "Mic 1",
+//! "Main L") that round-trips through project save state.
+
+use std::collections::HashMap;
+
+/// Which side of the graph boundary a terminal port sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalDirection {
+    GraphIn,
+    GraphOut,
+}
+
+/// What kind of data a terminal port carries. Mirrors [`PortType`](super::port::PortType)'s
+/// built-in variants; terminal ports don't support custom port types since
+/// they're host-defined boundary channels, not plugin-declared ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalPortKind {
+    Audio,
+    Note,
+}
+
+/// A stable identifier for one terminal port, derived deterministically
+/// from its direction, kind, and channel index so it never changes across
+/// runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TerminalPortID(u32);
+
+impl TerminalPortID {
+    /// The stable ID for `channel` on the given `direction` and `kind`. The
+    /// direction is packed into the top bit and the kind into bit 30 so
+    /// every combination of direction/kind/channel stays distinct, while
+    /// channel indices remain contiguous within each.
+    fn for_port(direction: TerminalDirection, kind: TerminalPortKind, channel: u32) -> Self {
+        let direction_bit = match direction {
+            TerminalDirection::GraphIn => 0,
+            TerminalDirection::GraphOut => 1 << 31,
+        };
+        let kind_bit = match kind {
+            TerminalPortKind::Audio => 0,
+            TerminalPortKind::Note => 1 << 30,
+        };
+        Self(direction_bit | kind_bit | channel)
+    }
+
+    /// The stable ID for `channel` on the given `direction`. Kept as the
+    /// audio-port constructor (kind bit `0`, unchanged from before
+    /// [`TerminalPortKind`] existed) so every already-persisted ID keeps
+    /// resolving to the exact same ID.
+    pub fn for_channel(direction: TerminalDirection, channel: u32) -> Self {
+        Self::for_port(direction, TerminalPortKind::Audio, channel)
+    }
+
+    /// The stable ID for note channel `channel` on the given `direction`,
+    /// e.g. the virtual MIDI input the host feeds CLAP note events into.
+    pub fn for_note_channel(direction: TerminalDirection, channel: u32) -> Self {
+        Self::for_port(direction, TerminalPortKind::Note, channel)
+    }
+
+    /// Whether this is a note terminal port, as opposed to an audio one.
+    pub fn is_note(&self) -> bool {
+        self.0 & (1 << 30) != 0
+    }
+}
+
+/// Host-settable display names for terminal ports (e.g. "Mic 1", "Main L"),
+/// keyed by their stable [`TerminalPortID`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalPortNames {
+    names: HashMap<TerminalPortID, String>,
+}
+
+impl TerminalPortNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds a name table from a persisted snapshot, e.g. when loading
+    /// project save state.
+    pub fn from_entries(entries: impl IntoIterator<Item = (TerminalPortID, String)>) -> Self {
+        Self { names: entries.into_iter().collect() }
+    }
+
+    /// Sets (or replaces) the display name for a terminal port.
+    pub fn set(&mut self, port: TerminalPortID, name: impl Into<String>) {
+        self.names.insert(port, name.into());
+    }
+
+    /// Clears a terminal port's display name, reverting it to its default
+    /// (channel-index-based) label.
+    pub fn clear(&mut self, port: TerminalPortID) {
+        self.names.remove(&port);
+    }
+
+    pub fn get(&self, port: TerminalPortID) -> Option<&str> {
+        self.names.get(&port).map(|s| s.as_str())
+    }
+
+    /// A snapshot of every named port sorted by ID, for persisting into
+    /// project save state in a deterministic order.
+    pub fn entries(&self) -> Vec<(TerminalPortID, String)> {
+        let mut entries: Vec<_> = self.names.iter().map(|(id, name)| (*id, name.clone())).collect();
+        entries.sort_by_key(|(id, _)| *id);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_for_the_same_direction_and_channel_are_stable() {
+        let a = TerminalPortID::for_channel(TerminalDirection::GraphIn, 2);
+        let b = TerminalPortID::for_channel(TerminalDirection::GraphIn, 2);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn graph_in_and_graph_out_never_collide_on_the_same_channel() {
+        let input = TerminalPortID::for_channel(TerminalDirection::GraphIn, 0);
+        let output = TerminalPortID::for_channel(TerminalDirection::GraphOut, 0);
+        assert_ne!(input, output);
+    }
+
+    #[test]
+    fn note_and_audio_terminal_ports_never_collide_on_the_same_channel() {
+        let audio = TerminalPortID::for_channel(TerminalDirection::GraphIn, 0);
+        let note = TerminalPortID::for_note_channel(TerminalDirection::GraphIn, 0);
+        assert_ne!(audio, note);
+        assert!(!audio.is_note());
+        assert!(note.is_note());
+    }
+
+    #[test]
+    fn for_channel_is_unchanged_from_before_note_terminal_ports_existed() {
+        // Bit 30 was always implicitly 0 before `TerminalPortKind` existed,
+        // so this must keep producing the exact same IDs already persisted
+        // in project save state.
+        assert_eq!(TerminalPortID::for_channel(TerminalDirection::GraphIn, 5).0, 5);
+        assert_eq!(TerminalPortID::for_channel(TerminalDirection::GraphOut, 5).0, (1 << 31) | 5);
+    }
+
+    #[test]
+    fn setting_and_clearing_a_name_round_trips() {
+        let mut names = TerminalPortNames::new();
+        let mic = TerminalPortID::for_channel(TerminalDirection::GraphIn, 0);
+        names.set(mic, "Mic 1");
+        assert_eq!(names.get(mic), Some("Mic 1"));
+
+        names.clear(mic);
+        assert_eq!(names.get(mic), None);
+    }
+
+    #[test]
+    fn entries_round_trip_through_from_entries_in_sorted_order() {
+        let mut names = TerminalPortNames::new();
+        let out_l = TerminalPortID::for_channel(TerminalDirection::GraphOut, 0);
+        let in_l = TerminalPortID::for_channel(TerminalDirection::GraphIn, 0);
+        names.set(out_l, "Main L");
+        names.set(in_l, "Mic 1");
+
+        let entries = names.entries();
+        assert_eq!(entries, vec![(in_l, "Mic 1".to_string()), (out_l, "Main L".to_string())]);
+
+        let restored = TerminalPortNames::from_entries(entries);
+        assert_eq!(restored.get(in_l), Some("Mic 1"));
+        assert_eq!(restored.get(out_l), Some("Main L"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Debug capture of one block's schedule execution trace: task order,
+//! durations, and each node's buffer-constant flags before and after it
+//! ran. Lets a running host spot ordering or performance regressions
+//! without attaching a profiler.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::id::PluginInstanceID;
+
+/// Whether a node's input/output buffer was known to be constant (e.g.
+/// silent), the same flag a constant-buffer optimization checks to skip
+/// real processing for a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantFlags {
+    pub input_constant: bool,
+    pub output_constant: bool,
+}
+
+/// One node's recorded execution within a traced block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedTask {
+    pub node: PluginInstanceID,
+    pub duration: Duration,
+    pub constant_flags: ConstantFlags,
+}
+
+/// A full execution trace for one processed block, in the order tasks
+/// actually ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScheduleTrace {
+    tasks: Vec<TracedTask>,
+}
+
+impl ScheduleTrace {
+    pub fn tasks(&self) -> &[TracedTask] {
+        &self.tasks
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.tasks.iter().map(|task| task.duration).sum()
+    }
+}
+
+/// Records a [`ScheduleTrace`] as tasks run, in processing order.
+#[derive(Debug, Default)]
+pub struct TraceRecorder {
+    trace: ScheduleTrace,
+}
+
+impl TraceRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, node: PluginInstanceID, duration: Duration, constant_flags: ConstantFlags) {
+        self.trace.tasks.push(TracedTask { node, duration, constant_flags });
+    }
+
+    pub fn finish(self) -> ScheduleTrace {
+        self.trace
+    }
+}
+
+/// A single-slot mailbox for on-demand block tracing: the main thread
+/// requests a capture, the audio thread checks that request at the start of
+/// its next block (so tracing costs nothing on blocks nobody asked about)
+/// and publishes the finished trace, and the main thread polls it back at
+/// any rate.
+#[derive(Debug, Clone, Default)]
+pub struct TraceCapture {
+    requested: Arc<AtomicBool>,
+    latest: Arc<Mutex<Option<ScheduleTrace>>>,
+}
+
+impl TraceCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that the next processed block be traced.
+    pub fn request_next_block(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Checked by the audio thread at the start of a block to decide
+    /// whether to record a [`TraceRecorder`] for it at all.
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+
+    /// Publishes a finished block trace and clears the pending request.
+    /// Intended to be called at most once per processed block from the
+    /// audio thread.
+    pub fn publish(&self, trace: ScheduleTrace) {
+        self.requested.store(false, Ordering::Relaxed);
+        *self.latest.lock().unwrap() = Some(trace);
+    }
+
+    /// Takes the most recently published trace, leaving nothing behind.
+    /// Returns `None` if no block has been captured yet (or it was already
+    /// taken).
+    pub fn take(&self) -> Option<ScheduleTrace> {
+        self.latest.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags() -> ConstantFlags {
+        ConstantFlags { input_constant: false, output_constant: false }
+    }
+
+    #[test]
+    fn a_recorder_captures_tasks_in_the_order_they_ran() {
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let mut recorder = TraceRecorder::new();
+        recorder.record(a, Duration::from_micros(10), flags());
+        recorder.record(b, Duration::from_micros(20), flags());
+
+        let trace = recorder.finish();
+        assert_eq!(trace.tasks().iter().map(|t| t.node).collect::<Vec<_>>(), vec![a, b]);
+        assert_eq!(trace.total_duration(), Duration::from_micros(30));
+    }
+
+    #[test]
+    fn capturing_is_only_armed_after_a_request_and_clears_once_published() {
+        let capture = TraceCapture::new();
+        assert!(!capture.is_requested());
+        assert!(capture.take().is_none());
+
+        capture.request_next_block();
+        assert!(capture.is_requested());
+
+        capture.publish(ScheduleTrace::default());
+        assert!(!capture.is_requested());
+        assert_eq!(capture.take(), Some(ScheduleTrace::default()));
+        assert!(capture.take().is_none());
+    }
+
+    #[test]
+    fn cloned_handles_share_the_same_capture() {
+        let capture = TraceCapture::new();
+        let handle = capture.clone();
+
+        handle.request_next_block();
+        assert!(capture.is_requested());
+
+        let a = PluginInstanceID::new();
+        let mut recorder = TraceRecorder::new();
+        recorder.record(a, Duration::from_micros(5), flags());
+        handle.publish(recorder.finish());
+
+        let trace = capture.take().unwrap();
+        assert_eq!(trace.tasks().len(), 1);
+    }
+}
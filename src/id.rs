@@ -0,0 +1,43 @@
+//! Identifiers shared across the engine, graph, and plugin hosting layers.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_PLUGIN_INSTANCE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A unique, process-lifetime identifier for a single instantiated plugin
+/// (internal or external).
+///
+/// IDs are never reused, so they remain valid as stable keys even after the
+/// plugin they refer to has been removed from the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PluginInstanceID(u64);
+
+impl PluginInstanceID {
+    /// Allocates a new, never-before-used plugin instance ID.
+    pub fn new() -> Self {
+        Self(NEXT_PLUGIN_INSTANCE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for PluginInstanceID {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A stable identifier for a single parameter on a plugin, as assigned by
+/// that plugin (the CLAP `clap_id`, or the internal plugin's own scheme).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ParamID(pub u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin_instance_ids_are_unique() {
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,26 @@
+//! Dropseed is an audio graph engine, plugin hosting engine, system IO, and
+//! general purpose DAW engine. See `DESIGN_DOC.md` for the full goals and
+//! architecture rationale.
+
+pub mod automation;
+pub mod engine;
+pub mod graph;
+pub mod nodes;
+pub mod id;
+pub mod metering;
+pub mod plugin;
+pub mod prelude;
+pub mod resource;
+pub mod transport;
+pub mod util;
+
+pub use engine::DSEngineMainThread;
+pub use id::{ParamID, PluginInstanceID};
+pub use transport::{TempoMap, TimeSignatureMap};
+
+/// Installed only when the `alloc-detector` feature is enabled, so
+/// development builds can catch realtime-safety regressions; see
+/// [`util::alloc_detector`].
+#[cfg(feature = "alloc-detector")]
+#[global_allocator]
+static ALLOC_DETECTOR: util::alloc_detector::AllocationDetector = util::alloc_detector::AllocationDetector;
@@ -0,0 +1,514 @@
+//! EBU R128 / ITU-R BS.1770 loudness measurement for the graph's master
+//! output.
+//!
+//! [`LoudnessMeter`] K-weights incoming audio (the BS.1770 pre-filter
+//! shelf plus an RLB high-pass, cascaded per channel) and reports
+//! momentary, short-term, and (gated) integrated loudness in LUFS, plus an
+//! approximate true peak in dBTP. Two simplifications keep this tractable
+//! compared to a reference implementation: gating blocks are
+//! non-overlapping 400ms windows rather than the spec's 75%-overlapped
+//! ones, and true peak is estimated via 4x linear-interpolation
+//! oversampling rather than the spec's polyphase reconstruction filter.
+//! Both trade a little measurement smoothness/precision for a much
+//! simpler, still useful, implementation.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_OFFSET_LUFS: f64 = -10.0;
+
+/// Length of one gating block, in seconds (EBU R128 uses 400ms).
+pub const GATING_BLOCK_SECONDS: f64 = 0.4;
+/// Width of the short-term loudness window, in gating blocks (EBU R128
+/// uses a 3s window, i.e. 7.5 gating blocks; rounded down to whole blocks
+/// here).
+pub const SHORT_TERM_BLOCKS: usize = 7;
+/// Upper bound on how many gating blocks [`LoudnessMeter::integrated_lufs`]
+/// remembers (at the default 400ms block this is a little over an hour).
+/// Past this, the oldest block is dropped as a new one arrives rather than
+/// growing the history forever, trading a little accuracy on extremely
+/// long-running sessions for bounded, allocation-free steady-state memory.
+pub const MAX_INTEGRATED_HISTORY_BLOCKS: usize = 9_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct BiquadCoeffs {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct BiquadState {
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl BiquadState {
+    fn process(&mut self, c: &BiquadCoeffs, x0: f64) -> f64 {
+        let y0 = c.b0 * x0 + c.b1 * self.x1 + c.b2 * self.x2 - c.a1 * self.y1 - c.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x0;
+        self.y2 = self.y1;
+        self.y1 = y0;
+        y0
+    }
+}
+
+/// BS.1770's pre-filter: a high shelf boosting above ~1.7kHz to
+/// approximate the head's acoustic effect.
+fn shelf_coeffs(sample_rate: f64) -> BiquadCoeffs {
+    let f0 = 1_681.974_450_955_532;
+    let g = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let denom = 1.0 + k / q + k * k;
+
+    BiquadCoeffs {
+        b0: (vh + vb * k / q + k * k) / denom,
+        b1: 2.0 * (k * k - vh) / denom,
+        b2: (vh - vb * k / q + k * k) / denom,
+        a1: 2.0 * (k * k - 1.0) / denom,
+        a2: (1.0 - k / q + k * k) / denom,
+    }
+}
+
+/// BS.1770's RLB filter: a high-pass modeling the ear's reduced
+/// sensitivity to very low frequencies.
+fn highpass_coeffs(sample_rate: f64) -> BiquadCoeffs {
+    let f0 = 38.135_470_876_139_82;
+    let q = 0.500_327_037_323_877_3;
+
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let denom = 1.0 + k / q + k * k;
+
+    BiquadCoeffs { b0: 1.0, b1: -2.0, b2: 1.0, a1: 2.0 * (k * k - 1.0) / denom, a2: (1.0 - k / q + k * k) / denom }
+}
+
+/// One momentary/short-term/integrated/true-peak reading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessReading {
+    /// Loudness of the most recently completed gating block, in LUFS.
+    pub momentary_lufs: f64,
+    /// Loudness averaged over the last [`SHORT_TERM_BLOCKS`] gating
+    /// blocks, in LUFS.
+    pub short_term_lufs: f64,
+    /// Gated program loudness across the whole measurement so far, in
+    /// LUFS.
+    pub integrated_lufs: f64,
+    /// Estimated true peak across the whole measurement so far, in dBTP.
+    pub true_peak_dbtp: f32,
+}
+
+impl Default for LoudnessReading {
+    fn default() -> Self {
+        Self {
+            momentary_lufs: f64::NEG_INFINITY,
+            short_term_lufs: f64::NEG_INFINITY,
+            integrated_lufs: f64::NEG_INFINITY,
+            true_peak_dbtp: f32::NEG_INFINITY,
+        }
+    }
+}
+
+fn block_loudness_lufs(mean_square: f64) -> f64 {
+    if mean_square <= 0.0 {
+        f64::NEG_INFINITY
+    } else {
+        -0.691 + 10.0 * mean_square.log10()
+    }
+}
+
+/// Estimates a block's true peak via 4x linear-interpolation oversampling.
+fn estimate_true_peak(samples: &[f32]) -> f32 {
+    let mut peak = 0.0f32;
+    for window in samples.windows(2) {
+        let (a, b) = (window[0], window[1]);
+        for step in 0..4 {
+            let t = step as f32 / 4.0;
+            peak = peak.max((a + (b - a) * t).abs());
+        }
+    }
+    if let Some(&last) = samples.last() {
+        peak = peak.max(last.abs());
+    }
+    peak
+}
+
+/// K-weights and accumulates incoming audio into gating blocks, reporting
+/// momentary/short-term/integrated loudness and an estimated true peak.
+#[derive(Debug)]
+pub struct LoudnessMeter {
+    sample_rate: f64,
+    shelf: BiquadCoeffs,
+    highpass: BiquadCoeffs,
+    channel_states: Vec<(BiquadState, BiquadState)>,
+    block_frames: usize,
+    frames_in_block: usize,
+    sum_sq_in_block: f64,
+    block_loudness_lufs: VecDeque<f64>,
+    short_term_history: VecDeque<f64>,
+    true_peak_dbtp: f32,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: f64, channels: usize) -> Self {
+        Self {
+            sample_rate,
+            shelf: shelf_coeffs(sample_rate),
+            highpass: highpass_coeffs(sample_rate),
+            channel_states: vec![(BiquadState::default(), BiquadState::default()); channels.max(1)],
+            block_frames: (sample_rate * GATING_BLOCK_SECONDS).round().max(1.0) as usize,
+            frames_in_block: 0,
+            sum_sq_in_block: 0.0,
+            block_loudness_lufs: VecDeque::with_capacity(MAX_INTEGRATED_HISTORY_BLOCKS),
+            short_term_history: VecDeque::with_capacity(SHORT_TERM_BLOCKS),
+            true_peak_dbtp: f32::NEG_INFINITY,
+        }
+    }
+
+    /// Feeds one process block's per-channel samples (equal length)
+    /// through the meter, returning a fresh [`LoudnessReading`] whenever a
+    /// full gating block (400ms of audio) completes, `None` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel_samples.len()` doesn't match the channel count
+    /// this meter was created with, or the channels have unequal lengths.
+    pub fn process_block(&mut self, channel_samples: &[&[f32]]) -> Option<LoudnessReading> {
+        assert_eq!(channel_samples.len(), self.channel_states.len(), "channel count mismatch");
+        let frame_count = channel_samples.first().map(|c| c.len()).unwrap_or(0);
+        assert!(channel_samples.iter().all(|c| c.len() == frame_count), "channels must have equal length");
+
+        for (channel_index, &samples) in channel_samples.iter().enumerate() {
+            self.true_peak_dbtp = self.true_peak_dbtp.max(estimate_true_peak(samples).log10() * 20.0);
+            let (shelf_state, highpass_state) = &mut self.channel_states[channel_index];
+            for &sample in samples {
+                let shelved = shelf_state.process(&self.shelf, sample as f64);
+                let weighted = highpass_state.process(&self.highpass, shelved);
+                self.sum_sq_in_block += weighted * weighted;
+            }
+        }
+        self.frames_in_block += frame_count;
+
+        if self.frames_in_block < self.block_frames {
+            return None;
+        }
+
+        let channels = self.channel_states.len() as f64;
+        let mean_square = self.sum_sq_in_block / (self.frames_in_block as f64 * channels);
+        self.frames_in_block = 0;
+        self.sum_sq_in_block = 0.0;
+
+        let loudness = block_loudness_lufs(mean_square);
+        self.block_loudness_lufs.push_back(loudness);
+        if self.block_loudness_lufs.len() > MAX_INTEGRATED_HISTORY_BLOCKS {
+            self.block_loudness_lufs.pop_front();
+        }
+
+        self.short_term_history.push_back(loudness);
+        if self.short_term_history.len() > SHORT_TERM_BLOCKS {
+            self.short_term_history.pop_front();
+        }
+
+        Some(LoudnessReading {
+            momentary_lufs: loudness,
+            short_term_lufs: gated_mean(&self.short_term_history, ABSOLUTE_GATE_LUFS),
+            integrated_lufs: self.integrated_lufs(),
+            true_peak_dbtp: self.true_peak_dbtp,
+        })
+    }
+
+    /// The gated program loudness across the last [`MAX_INTEGRATED_HISTORY_BLOCKS`]
+    /// gating blocks measured: first an ungated mean over blocks above the
+    /// absolute gate, then a second mean over blocks above
+    /// `ungated_mean - 10 LU`, per EBU R128's two-stage gating.
+    pub fn integrated_lufs(&self) -> f64 {
+        let ungated_mean = gated_mean(&self.block_loudness_lufs, ABSOLUTE_GATE_LUFS);
+        if ungated_mean == f64::NEG_INFINITY {
+            return f64::NEG_INFINITY;
+        }
+        gated_mean(&self.block_loudness_lufs, ungated_mean + RELATIVE_GATE_OFFSET_LUFS)
+    }
+
+    /// The highest true peak estimated across the whole measurement so
+    /// far, in dBTP.
+    pub fn true_peak_dbtp(&self) -> f32 {
+        self.true_peak_dbtp
+    }
+
+    /// Clears all accumulated history, e.g. to start measuring a fresh
+    /// export rather than continuing a live session's running loudness.
+    pub fn reset_integration(&mut self) {
+        self.block_loudness_lufs.clear();
+        self.short_term_history.clear();
+        self.true_peak_dbtp = f32::NEG_INFINITY;
+        self.frames_in_block = 0;
+        self.sum_sq_in_block = 0.0;
+    }
+
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+}
+
+/// The mean loudness (in LUFS) of every block in `blocks` at or above
+/// `gate_lufs`, converted back from linear power before averaging per
+/// EBU R128 (loudness doesn't average correctly in the log domain).
+/// `f64::NEG_INFINITY` if nothing passes the gate.
+fn gated_mean<'a>(blocks: impl IntoIterator<Item = &'a f64>, gate_lufs: f64) -> f64 {
+    let passing: Vec<f64> = blocks.into_iter().copied().filter(|&l| l >= gate_lufs && l.is_finite()).collect();
+    if passing.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+    let mean_power: f64 = passing.iter().map(|&l| 10f64.powf((l + 0.691) / 10.0)).sum::<f64>() / passing.len() as f64;
+    block_loudness_lufs(mean_power)
+}
+
+/// A measured loudness summary for an offline export, suitable for
+/// embedding in a render report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessReport {
+    pub integrated_lufs: f64,
+    pub true_peak_dbtp: f32,
+}
+
+/// Measures the integrated loudness and true peak of a complete,
+/// already-rendered signal in one pass, for embedding in an export's
+/// render report.
+///
+/// `channel_samples` must all have equal length. A tail shorter than one
+/// gating block is discarded, matching how a partial final block is
+/// dropped from a live measurement.
+///
+/// # Panics
+///
+/// Panics if `channel_samples` have unequal lengths.
+pub fn measure_offline(sample_rate: f64, channel_samples: &[&[f32]]) -> LoudnessReport {
+    let mut meter = LoudnessMeter::new(sample_rate, channel_samples.len());
+    meter.process_block(channel_samples);
+    LoudnessReport { integrated_lufs: meter.integrated_lufs(), true_peak_dbtp: meter.true_peak_dbtp() }
+}
+
+/// A single lock-free loudness reading, written by the audio thread and
+/// readable from any thread without blocking, the same mailbox shape as
+/// [`MeterHandle`](super::meter_tap::MeterHandle).
+#[derive(Debug)]
+pub struct LoudnessHandle {
+    momentary_bits: AtomicU64,
+    short_term_bits: AtomicU64,
+    integrated_bits: AtomicU64,
+    true_peak_bits: AtomicU32,
+}
+
+impl LoudnessHandle {
+    pub fn new() -> Self {
+        let reading = LoudnessReading::default();
+        Self {
+            momentary_bits: AtomicU64::new(reading.momentary_lufs.to_bits()),
+            short_term_bits: AtomicU64::new(reading.short_term_lufs.to_bits()),
+            integrated_bits: AtomicU64::new(reading.integrated_lufs.to_bits()),
+            true_peak_bits: AtomicU32::new(reading.true_peak_dbtp.to_bits()),
+        }
+    }
+
+    /// Publishes a new reading. Intended to be called at most once per
+    /// process block from the audio thread.
+    pub fn write(&self, reading: LoudnessReading) {
+        self.momentary_bits.store(reading.momentary_lufs.to_bits(), Ordering::Relaxed);
+        self.short_term_bits.store(reading.short_term_lufs.to_bits(), Ordering::Relaxed);
+        self.integrated_bits.store(reading.integrated_lufs.to_bits(), Ordering::Relaxed);
+        self.true_peak_bits.store(reading.true_peak_dbtp.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Reads the most recently published reading.
+    pub fn read(&self) -> LoudnessReading {
+        LoudnessReading {
+            momentary_lufs: f64::from_bits(self.momentary_bits.load(Ordering::Relaxed)),
+            short_term_lufs: f64::from_bits(self.short_term_bits.load(Ordering::Relaxed)),
+            integrated_lufs: f64::from_bits(self.integrated_bits.load(Ordering::Relaxed)),
+            true_peak_dbtp: f32::from_bits(self.true_peak_bits.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Default for LoudnessHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An optional loudness metering task on the graph output: while enabled,
+/// the audio thread feeds it every process block and the main thread polls
+/// readings from the returned [`LoudnessHandle`] without touching the
+/// audio thread.
+#[derive(Debug, Default)]
+pub struct LoudnessMeterTask {
+    meter: Option<LoudnessMeter>,
+    handle: Option<std::sync::Arc<LoudnessHandle>>,
+}
+
+impl LoudnessMeterTask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables loudness metering on the graph output, returning a shared
+    /// handle the main thread polls. Replaces any previous measurement in
+    /// progress.
+    pub fn enable(&mut self, sample_rate: f64, channels: usize) -> std::sync::Arc<LoudnessHandle> {
+        let handle = std::sync::Arc::new(LoudnessHandle::new());
+        self.meter = Some(LoudnessMeter::new(sample_rate, channels));
+        self.handle = Some(handle.clone());
+        handle
+    }
+
+    pub fn disable(&mut self) {
+        self.meter = None;
+        self.handle = None;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.meter.is_some()
+    }
+
+    /// Feeds one process block's per-channel samples through the meter, a
+    /// no-op if metering isn't enabled. Publishes a fresh reading to the
+    /// handle whenever a gating block completes.
+    pub fn process_block(&mut self, channel_samples: &[&[f32]]) {
+        if let (Some(meter), Some(handle)) = (self.meter.as_mut(), self.handle.as_ref()) {
+            if let Some(reading) = meter.process_block(channel_samples) {
+                handle.write(reading);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn silence(sample_rate: f64, channels: usize, seconds: f64) -> Vec<Vec<f32>> {
+        let frames = (sample_rate * seconds) as usize;
+        vec![vec![0.0; frames]; channels]
+    }
+
+    fn full_scale_square_wave(sample_rate: f64, channels: usize, seconds: f64) -> Vec<Vec<f32>> {
+        let frames = (sample_rate * seconds) as usize;
+        let samples: Vec<f32> = (0..frames).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        vec![samples; channels]
+    }
+
+    fn refs(channels: &[Vec<f32>]) -> Vec<&[f32]> {
+        channels.iter().map(|c| c.as_slice()).collect()
+    }
+
+    #[test]
+    fn silence_reports_negative_infinity_loudness() {
+        let mut meter = LoudnessMeter::new(48_000.0, 2);
+        let block = silence(48_000.0, 2, GATING_BLOCK_SECONDS);
+        let reading = meter.process_block(&refs(&block)).expect("one full gating block");
+        assert_eq!(reading.momentary_lufs, f64::NEG_INFINITY);
+        assert_eq!(reading.integrated_lufs, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn no_reading_until_a_full_gating_block_has_accumulated() {
+        let mut meter = LoudnessMeter::new(48_000.0, 1);
+        let half_block = vec![vec![0.5; (48_000.0 * GATING_BLOCK_SECONDS / 2.0) as usize]];
+        assert!(meter.process_block(&refs(&half_block)).is_none());
+        assert!(meter.process_block(&refs(&half_block)).is_some());
+    }
+
+    #[test]
+    fn a_loud_signal_measures_louder_than_a_quiet_one() {
+        let mut loud = LoudnessMeter::new(48_000.0, 1);
+        let mut quiet = LoudnessMeter::new(48_000.0, 1);
+
+        let loud_block = full_scale_square_wave(48_000.0, 1, GATING_BLOCK_SECONDS);
+        let quiet_block: Vec<Vec<f32>> = loud_block.iter().map(|c| c.iter().map(|&s| s * 0.1).collect()).collect();
+
+        let loud_reading = loud.process_block(&refs(&loud_block)).unwrap();
+        let quiet_reading = quiet.process_block(&refs(&quiet_block)).unwrap();
+        assert!(loud_reading.momentary_lufs > quiet_reading.momentary_lufs);
+    }
+
+    #[test]
+    fn true_peak_tracks_the_highest_estimated_peak_seen_so_far() {
+        let mut meter = LoudnessMeter::new(48_000.0, 1);
+        let quiet = vec![vec![0.1; (48_000.0 * GATING_BLOCK_SECONDS) as usize]];
+        let loud = vec![vec![0.9; (48_000.0 * GATING_BLOCK_SECONDS) as usize]];
+
+        meter.process_block(&refs(&loud));
+        let peak_after_loud = meter.true_peak_dbtp();
+        meter.process_block(&refs(&quiet));
+        // A later quiet block must not lower the running true peak.
+        assert_eq!(meter.true_peak_dbtp(), peak_after_loud);
+    }
+
+    #[test]
+    fn a_constant_level_signal_settles_to_a_stable_integrated_loudness() {
+        let mut meter = LoudnessMeter::new(48_000.0, 1);
+        let block = full_scale_square_wave(48_000.0, 1, GATING_BLOCK_SECONDS);
+        for _ in 0..5 {
+            meter.process_block(&refs(&block));
+        }
+        let first = meter.integrated_lufs();
+        meter.process_block(&refs(&block));
+        let second = meter.integrated_lufs();
+        assert!((first - second).abs() < 0.01, "a steady signal's integrated loudness should stabilize");
+    }
+
+    #[test]
+    fn reset_integration_clears_accumulated_history() {
+        let mut meter = LoudnessMeter::new(48_000.0, 1);
+        let block = full_scale_square_wave(48_000.0, 1, GATING_BLOCK_SECONDS);
+        meter.process_block(&refs(&block));
+        assert!(meter.integrated_lufs().is_finite());
+
+        meter.reset_integration();
+        assert_eq!(meter.integrated_lufs(), f64::NEG_INFINITY);
+        assert_eq!(meter.true_peak_dbtp(), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn measure_offline_reports_a_loudness_report_for_a_complete_signal() {
+        let block = full_scale_square_wave(48_000.0, 2, GATING_BLOCK_SECONDS * 3.0);
+        let report = measure_offline(48_000.0, &refs(&block));
+        assert!(report.integrated_lufs.is_finite());
+        assert!(report.true_peak_dbtp > -1.0, "a full-scale square wave should read close to 0 dBTP");
+    }
+
+    #[test]
+    fn the_loudness_handle_round_trips_a_published_reading() {
+        let handle = LoudnessHandle::new();
+        assert_eq!(handle.read(), LoudnessReading::default());
+
+        let reading = LoudnessReading { momentary_lufs: -18.0, short_term_lufs: -17.0, integrated_lufs: -16.0, true_peak_dbtp: -1.0 };
+        handle.write(reading);
+        assert_eq!(handle.read(), reading);
+    }
+
+    #[test]
+    fn the_task_only_publishes_once_enabled() {
+        let mut task = LoudnessMeterTask::new();
+        assert!(!task.is_enabled());
+        task.process_block(&[&[0.5; 64]]);
+
+        let handle = task.enable(48_000.0, 1);
+        assert!(task.is_enabled());
+        let block = full_scale_square_wave(48_000.0, 1, GATING_BLOCK_SECONDS);
+        task.process_block(&refs(&block));
+        assert!(handle.read().momentary_lufs.is_finite());
+
+        task.disable();
+        assert!(!task.is_enabled());
+    }
+}
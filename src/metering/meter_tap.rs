@@ -0,0 +1,201 @@
+//! Lock-free peak/RMS metering taps on arbitrary graph edges.
+//!
+//! A mixer UI wants to show a level meter for any plugin output or graph
+//! output channel without round-tripping through the main thread every
+//! block. The host registers a [`MeterPoint`] to get back a shared
+//! [`MeterHandle`]: the audio thread writes the latest peak/RMS into it
+//! once per block, and the UI polls it from any thread without blocking,
+//! the same lock-free mailbox shape as
+//! [`ParamReadout`](crate::plugin::param_readout::ParamReadout).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::graph::{Schedule, TerminalPortID};
+use crate::id::PluginInstanceID;
+
+/// One point in the graph a meter can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeterPoint {
+    /// An output port on a plugin instance.
+    PluginOutput { plugin: PluginInstanceID, port_index: u32 },
+    /// One of the graph's own output channels.
+    GraphOut(TerminalPortID),
+}
+
+/// A single lock-free peak/RMS reading, written by the audio thread and
+/// readable from any thread without blocking.
+#[derive(Debug)]
+pub struct MeterHandle {
+    peak_bits: AtomicU32,
+    rms_bits: AtomicU32,
+}
+
+impl MeterHandle {
+    pub fn new() -> Self {
+        Self { peak_bits: AtomicU32::new(0f32.to_bits()), rms_bits: AtomicU32::new(0f32.to_bits()) }
+    }
+
+    /// Publishes a new reading. Intended to be called at most once per
+    /// process block from the audio thread.
+    pub fn write(&self, peak: f32, rms: f32) {
+        self.peak_bits.store(peak.to_bits(), Ordering::Relaxed);
+        self.rms_bits.store(rms.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn peak(&self) -> f32 {
+        f32::from_bits(self.peak_bits.load(Ordering::Relaxed))
+    }
+
+    pub fn rms(&self) -> f32 {
+        f32::from_bits(self.rms_bits.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for MeterHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Computes `(peak, rms)` for a block of samples, `(0.0, 0.0)` for an empty
+/// block.
+pub fn measure(samples: &[f32]) -> (f32, f32) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    let peak = samples.iter().fold(0.0f32, |acc, &s| acc.max(s.abs()));
+    let sum_sq: f32 = samples.iter().map(|&s| s * s).sum();
+    (peak, (sum_sq / samples.len() as f32).sqrt())
+}
+
+/// One unit of metering work for the audio thread to perform against a
+/// freshly compiled [`Schedule`]: the point to measure, and the handle to
+/// publish the result to.
+#[derive(Debug, Clone)]
+pub struct MeterTask {
+    pub point: MeterPoint,
+    pub handle: Arc<MeterHandle>,
+}
+
+/// A registry of metering taps the host has requested.
+#[derive(Debug, Default)]
+pub struct MeterTaps {
+    handles: HashMap<MeterPoint, Arc<MeterHandle>>,
+}
+
+impl MeterTaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a meter at `point`, returning a shared handle the audio
+    /// thread publishes readings to. Calling this again for an
+    /// already-registered point returns the existing handle.
+    pub fn register(&mut self, point: MeterPoint) -> Arc<MeterHandle> {
+        self.handles.entry(point).or_insert_with(|| Arc::new(MeterHandle::new())).clone()
+    }
+
+    pub fn remove(&mut self, point: MeterPoint) {
+        self.handles.remove(&point);
+    }
+
+    /// Drops every registered tap on a plugin's outputs, e.g. when it is
+    /// removed from the graph. Graph-output taps are untouched.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.handles.retain(|point, _| !matches!(point, MeterPoint::PluginOutput { plugin: p, .. } if *p == plugin));
+    }
+
+    pub fn handle(&self, point: MeterPoint) -> Option<Arc<MeterHandle>> {
+        self.handles.get(&point).cloned()
+    }
+
+    /// The lightweight metering work a freshly compiled `schedule` implies:
+    /// every registered tap, paired with its handle, whose plugin (for a
+    /// [`MeterPoint::PluginOutput`] tap) is still part of the graph.
+    /// [`MeterPoint::GraphOut`] taps are always included. A tap left
+    /// behind by a removed plugin simply stops appearing here until either
+    /// it is explicitly [`remove`](Self::remove)d or a new plugin
+    /// registers at that point.
+    pub fn tasks_for_schedule(&self, schedule: &Schedule) -> Vec<MeterTask> {
+        self.handles
+            .iter()
+            .filter(|(point, _)| match point {
+                MeterPoint::PluginOutput { plugin, .. } => schedule.order().contains(plugin),
+                MeterPoint::GraphOut(_) => true,
+            })
+            .map(|(&point, handle)| MeterTask { point, handle: handle.clone() })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{AbstractGraph, TerminalDirection};
+
+    fn schedule_with(nodes: &[PluginInstanceID]) -> Schedule {
+        let mut graph = AbstractGraph::new();
+        for &n in nodes {
+            graph.add_node(n);
+        }
+        crate::graph::compile(&graph).unwrap()
+    }
+
+    #[test]
+    fn measuring_silence_reports_zero() {
+        assert_eq!(measure(&[]), (0.0, 0.0));
+        assert_eq!(measure(&[0.0, 0.0, 0.0]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn measures_peak_and_rms_of_a_block() {
+        let (peak, rms) = measure(&[1.0, -1.0, 0.0, 0.0]);
+        assert_eq!(peak, 1.0);
+        assert!((rms - (0.5f32).sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn registering_twice_returns_the_same_handle() {
+        let mut taps = MeterTaps::new();
+        let point = MeterPoint::GraphOut(TerminalPortID::for_channel(TerminalDirection::GraphOut, 0));
+        let a = taps.register(point);
+        a.write(0.8, 0.4);
+        let b = taps.register(point);
+        assert_eq!(b.peak(), 0.8);
+        assert_eq!(b.rms(), 0.4);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_only_its_output_taps() {
+        let mut taps = MeterTaps::new();
+        let plugin = PluginInstanceID::new();
+        let out = MeterPoint::GraphOut(TerminalPortID::for_channel(TerminalDirection::GraphOut, 0));
+        let on_plugin = MeterPoint::PluginOutput { plugin, port_index: 0 };
+        taps.register(out);
+        taps.register(on_plugin);
+
+        taps.remove_plugin(plugin);
+        assert!(taps.handle(on_plugin).is_none());
+        assert!(taps.handle(out).is_some());
+    }
+
+    #[test]
+    fn tasks_for_schedule_excludes_taps_on_plugins_no_longer_in_the_graph() {
+        let mut taps = MeterTaps::new();
+        let still_here = PluginInstanceID::new();
+        let removed = PluginInstanceID::new();
+        taps.register(MeterPoint::PluginOutput { plugin: still_here, port_index: 0 });
+        taps.register(MeterPoint::PluginOutput { plugin: removed, port_index: 0 });
+        let graph_out = MeterPoint::GraphOut(TerminalPortID::for_channel(TerminalDirection::GraphOut, 0));
+        taps.register(graph_out);
+
+        let schedule = schedule_with(&[still_here]);
+        let tasks = taps.tasks_for_schedule(&schedule);
+        let points: Vec<MeterPoint> = tasks.iter().map(|t| t.point).collect();
+        assert_eq!(points.len(), 2);
+        assert!(points.contains(&MeterPoint::PluginOutput { plugin: still_here, port_index: 0 }));
+        assert!(points.contains(&graph_out));
+    }
+}
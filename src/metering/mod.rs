@@ -0,0 +1,104 @@
+//! Main-thread GUI feedback for metering and parameter values.
+//!
+//! Plugin GUIs want to see metering/parameter feedback line up with what's
+//! actually heard from the speakers. Since the engine's reported output
+//! latency describes how far behind the speakers are from the audio
+//! actually being processed, this module can optionally delay the feedback
+//! sent to a plugin's GUI by that same amount so the two stay in sync.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::util::delay_line::DelayLine;
+
+pub mod loudness;
+pub mod meter_tap;
+pub mod mono_compat;
+pub mod stereo_tap;
+
+pub use loudness::{
+    measure_offline, LoudnessHandle, LoudnessMeter, LoudnessMeterTask, LoudnessReading, LoudnessReport,
+    GATING_BLOCK_SECONDS, SHORT_TERM_BLOCKS,
+};
+pub use meter_tap::{measure, MeterHandle, MeterPoint, MeterTask, MeterTaps};
+pub use mono_compat::{analyze_mono_compatibility, MonoCompatReport, MonoCompatSection};
+pub use stereo_tap::{
+    analyze_stereo_block, GoniometerPoint, StereoCorrelationTap, StereoCorrelationTaps,
+    DEFAULT_GONIOMETER_HISTORY,
+};
+
+/// Per-plugin monitor-latency compensation for GUI feedback values.
+#[derive(Debug)]
+struct PluginMonitor {
+    enabled: bool,
+    delay: DelayLine<f32>,
+}
+
+/// Tracks monitor-latency compensation settings and delay buffers for every
+/// plugin's GUI feedback (e.g. metering, or an echoed parameter value).
+///
+/// The delay is expressed in idle ticks rather than audio frames, since
+/// feedback is pushed once per main-thread idle callback rather than once
+/// per sample.
+#[derive(Debug, Default)]
+pub struct MonitorLatencyCompensation {
+    plugins: HashMap<PluginInstanceID, PluginMonitor>,
+}
+
+impl MonitorLatencyCompensation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables monitor-latency compensation for a plugin, and
+    /// sets the delay (in idle ticks) to apply while enabled.
+    pub fn set_enabled(&mut self, plugin_id: PluginInstanceID, enabled: bool, delay_ticks: usize) {
+        let monitor = self
+            .plugins
+            .entry(plugin_id)
+            .or_insert_with(|| PluginMonitor { enabled: false, delay: DelayLine::new(delay_ticks) });
+        monitor.enabled = enabled;
+        monitor.delay.set_delay(delay_ticks);
+    }
+
+    /// Feeds a new metering/parameter feedback value for a plugin on this
+    /// idle tick, returning the value that should actually be sent to the
+    /// plugin's GUI this tick (delayed, if compensation is enabled for this
+    /// plugin).
+    ///
+    /// Returns `None` when compensation is enabled but the delay buffer
+    /// hasn't filled up yet, meaning nothing should be sent to the GUI this
+    /// tick.
+    pub fn tick(&mut self, plugin_id: PluginInstanceID, value: f32) -> Option<f32> {
+        match self.plugins.get_mut(&plugin_id) {
+            Some(monitor) if monitor.enabled => monitor.delay.push(value),
+            _ => Some(value),
+        }
+    }
+
+    pub fn remove_plugin(&mut self, plugin_id: PluginInstanceID) {
+        self.plugins.remove(&plugin_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_through_when_disabled() {
+        let mut mon = MonitorLatencyCompensation::new();
+        let plugin = PluginInstanceID::new();
+        assert_eq!(mon.tick(plugin, 0.5), Some(0.5));
+    }
+
+    #[test]
+    fn delays_feedback_when_enabled() {
+        let mut mon = MonitorLatencyCompensation::new();
+        let plugin = PluginInstanceID::new();
+        mon.set_enabled(plugin, true, 2);
+        assert_eq!(mon.tick(plugin, 1.0), None);
+        assert_eq!(mon.tick(plugin, 2.0), None);
+        assert_eq!(mon.tick(plugin, 3.0), Some(1.0));
+    }
+}
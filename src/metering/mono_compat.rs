@@ -0,0 +1,150 @@
+//! Offline mono compatibility analysis: how much of a stereo signal would
+//! be lost or phase-cancelled if it were summed to mono, broken down by
+//! section so a mastering-oriented host can flag the parts of a mix that
+//! won't translate to a mono system.
+
+/// Per-section mono compatibility metrics for one fixed-length chunk of a
+/// stereo signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonoCompatSection {
+    /// Sample offset (within the analyzed signal) this section starts at.
+    pub start_sample: usize,
+    /// Pearson correlation between the left and right channels over this
+    /// section, from `-1.0` (fully out of phase) to `1.0` (identical).
+    pub correlation: f32,
+    /// How much quieter the mono sum is than the average channel level,
+    /// in dB. `0.0` means no loss; large negative values mean heavy
+    /// phase cancellation when summed to mono.
+    pub cancellation_db: f32,
+}
+
+/// A full mono compatibility analysis of a stereo signal, one section at a
+/// time, in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonoCompatReport {
+    sections: Vec<MonoCompatSection>,
+}
+
+impl MonoCompatReport {
+    pub fn sections(&self) -> &[MonoCompatSection] {
+        &self.sections
+    }
+
+    /// The section with the worst (most negative) mono cancellation, if
+    /// any sections were analyzed.
+    pub fn worst_section(&self) -> Option<&MonoCompatSection> {
+        self.sections.iter().min_by(|a, b| a.cancellation_db.total_cmp(&b.cancellation_db))
+    }
+
+    /// The average left/right correlation across all sections.
+    pub fn mean_correlation(&self) -> f32 {
+        if self.sections.is_empty() {
+            return 1.0;
+        }
+        self.sections.iter().map(|s| s.correlation).sum::<f32>() / self.sections.len() as f32
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+fn analyze_section(left: &[f32], right: &[f32]) -> (f32, f32) {
+    let dot: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+    let left_energy: f32 = left.iter().map(|l| l * l).sum();
+    let right_energy: f32 = right.iter().map(|r| r * r).sum();
+    let denom = (left_energy * right_energy).sqrt();
+    let correlation = if denom > f32::EPSILON { (dot / denom).clamp(-1.0, 1.0) } else { 1.0 };
+
+    let mono: Vec<f32> = left.iter().zip(right).map(|(l, r)| (l + r) * 0.5).collect();
+    let mono_rms = rms(&mono);
+    let average_channel_rms = (rms(left) + rms(right)) * 0.5;
+    let cancellation_db = if average_channel_rms > f32::EPSILON {
+        20.0 * (mono_rms.max(1e-10) / average_channel_rms).log10()
+    } else {
+        0.0
+    };
+
+    (correlation, cancellation_db)
+}
+
+/// Analyzes `left`/`right` (equal-length stereo channels) in consecutive
+/// chunks of `section_len` samples, reporting left/right correlation and
+/// mono summing loss for each. The final, possibly shorter, section is
+/// still analyzed.
+///
+/// # Panics
+///
+/// Panics if `left.len() != right.len()` or `section_len == 0`.
+pub fn analyze_mono_compatibility(left: &[f32], right: &[f32], section_len: usize) -> MonoCompatReport {
+    assert_eq!(left.len(), right.len(), "left and right channels must have the same length");
+    assert!(section_len > 0, "section_len must be non-zero");
+
+    let sections = left
+        .chunks(section_len)
+        .zip(right.chunks(section_len))
+        .enumerate()
+        .map(|(i, (left_chunk, right_chunk))| {
+            let (correlation, cancellation_db) = analyze_section(left_chunk, right_chunk);
+            MonoCompatSection { start_sample: i * section_len, correlation, cancellation_db }
+        })
+        .collect();
+
+    MonoCompatReport { sections }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_channels_sum_to_mono_with_no_loss() {
+        let signal = vec![0.5, -0.25, 0.75, -0.5];
+        let report = analyze_mono_compatibility(&signal, &signal, 4);
+        assert_eq!(report.sections().len(), 1);
+        let section = &report.sections()[0];
+        assert!((section.correlation - 1.0).abs() < 1e-4);
+        assert!(section.cancellation_db.abs() < 0.1);
+    }
+
+    #[test]
+    fn fully_out_of_phase_channels_cancel_to_near_silence_in_mono() {
+        let left = vec![0.5, -0.25, 0.75, -0.5];
+        let right: Vec<f32> = left.iter().map(|s| -s).collect();
+        let report = analyze_mono_compatibility(&left, &right, 4);
+        let section = &report.sections()[0];
+        assert!((section.correlation - -1.0).abs() < 1e-4);
+        assert!(section.cancellation_db < -40.0);
+    }
+
+    #[test]
+    fn sections_are_split_at_the_configured_length_including_a_short_tail() {
+        let signal = vec![1.0_f32; 10];
+        let report = analyze_mono_compatibility(&signal, &signal, 4);
+        assert_eq!(report.sections().len(), 3);
+        assert_eq!(report.sections()[0].start_sample, 0);
+        assert_eq!(report.sections()[1].start_sample, 4);
+        assert_eq!(report.sections()[2].start_sample, 8);
+    }
+
+    #[test]
+    fn worst_section_picks_the_most_cancelled_one() {
+        let mut left = vec![1.0_f32; 4];
+        left.extend(vec![1.0_f32; 4]);
+        let mut right = vec![1.0_f32; 4];
+        right.extend(vec![-1.0_f32; 4]);
+
+        let report = analyze_mono_compatibility(&left, &right, 4);
+        let worst = report.worst_section().unwrap();
+        assert_eq!(worst.start_sample, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn mismatched_channel_lengths_panics() {
+        analyze_mono_compatibility(&[0.0, 0.0], &[0.0], 1);
+    }
+}
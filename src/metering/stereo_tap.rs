@@ -0,0 +1,229 @@
+//! Lock-free stereo correlation + decimated goniometer (Lissajous) feed.
+//!
+//! A stereo imaging display wants a live correlation coefficient and a
+//! scatter plot of the signal's mid/side axes without round-tripping
+//! through the main thread every block, the same need [`MeterTaps`] fills
+//! for peak/RMS. The correlation coefficient is published through the same
+//! lock-free single-value mailbox [`MeterHandle`] uses; the goniometer
+//! points are decimated (sampled far below the block rate) so a short
+//! mutex-guarded ring buffer is cheap enough to write from the audio
+//! thread without it becoming a bottleneck.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::metering::meter_tap::MeterPoint;
+
+/// One decimated point plotted from a stereo signal's mid/side axes, ready
+/// for a goniometer display.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoniometerPoint {
+    pub mid: f32,
+    pub side: f32,
+}
+
+/// Default number of goniometer points retained for drawing; older points
+/// drop off as new ones arrive.
+pub const DEFAULT_GONIOMETER_HISTORY: usize = 512;
+
+#[derive(Debug)]
+struct GoniometerHistory {
+    points: VecDeque<GoniometerPoint>,
+    capacity: usize,
+}
+
+/// A realtime-safe stereo analysis tap for one point in the graph: the
+/// audio thread publishes a correlation coefficient once per process block
+/// and a handful of decimated goniometer points, and any thread can poll
+/// both without blocking the audio thread for long.
+#[derive(Debug)]
+pub struct StereoCorrelationTap {
+    correlation_bits: AtomicU32,
+    history: Mutex<GoniometerHistory>,
+}
+
+impl StereoCorrelationTap {
+    pub fn new(history_len: usize) -> Self {
+        let capacity = history_len.max(1);
+        Self {
+            correlation_bits: AtomicU32::new(1f32.to_bits()),
+            history: Mutex::new(GoniometerHistory { points: VecDeque::with_capacity(capacity), capacity }),
+        }
+    }
+
+    /// Publishes a new correlation coefficient. Intended to be called at
+    /// most once per process block from the audio thread.
+    pub fn write_correlation(&self, correlation: f32) {
+        self.correlation_bits.store(correlation.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Reads the most recently published correlation coefficient, `1.0`
+    /// (fully correlated, i.e. mono-compatible) before anything has been
+    /// published.
+    pub fn correlation(&self) -> f32 {
+        f32::from_bits(self.correlation_bits.load(Ordering::Relaxed))
+    }
+
+    /// Pushes one decimated goniometer point, dropping the oldest point
+    /// once the history is full.
+    pub fn push_point(&self, point: GoniometerPoint) {
+        let mut history = self.history.lock().unwrap();
+        if history.points.len() == history.capacity {
+            history.points.pop_front();
+        }
+        history.points.push_back(point);
+    }
+
+    /// A snapshot of the currently retained goniometer points, oldest
+    /// first.
+    pub fn goniometer_points(&self) -> Vec<GoniometerPoint> {
+        self.history.lock().unwrap().points.iter().copied().collect()
+    }
+}
+
+impl Default for StereoCorrelationTap {
+    fn default() -> Self {
+        Self::new(DEFAULT_GONIOMETER_HISTORY)
+    }
+}
+
+/// Analyzes one process block of equal-length `left`/`right` samples and
+/// publishes the results to `tap`: the block's overall correlation
+/// coefficient, plus one goniometer point every `decimation` frames (a
+/// `decimation` of `0` is treated as `1`, publishing every frame).
+///
+/// # Panics
+///
+/// Panics if `left.len() != right.len()`.
+pub fn analyze_stereo_block(tap: &StereoCorrelationTap, left: &[f32], right: &[f32], decimation: usize) {
+    assert_eq!(left.len(), right.len(), "left and right channels must have the same length");
+    if left.is_empty() {
+        return;
+    }
+
+    let dot: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+    let left_energy: f32 = left.iter().map(|l| l * l).sum();
+    let right_energy: f32 = right.iter().map(|r| r * r).sum();
+    let denom = (left_energy * right_energy).sqrt();
+    let correlation = if denom > f32::EPSILON { (dot / denom).clamp(-1.0, 1.0) } else { 1.0 };
+    tap.write_correlation(correlation);
+
+    for (&l, &r) in left.iter().zip(right).step_by(decimation.max(1)) {
+        tap.push_point(GoniometerPoint {
+            mid: (l + r) * std::f32::consts::FRAC_1_SQRT_2,
+            side: (l - r) * std::f32::consts::FRAC_1_SQRT_2,
+        });
+    }
+}
+
+/// A registry of stereo correlation taps the host has requested, keyed by
+/// the same [`MeterPoint`]s peak/RMS metering uses.
+#[derive(Debug, Default)]
+pub struct StereoCorrelationTaps {
+    handles: HashMap<MeterPoint, Arc<StereoCorrelationTap>>,
+}
+
+impl StereoCorrelationTaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a stereo tap at `point`, returning a shared handle the
+    /// audio thread publishes readings to. Calling this again for an
+    /// already-registered point returns the existing handle.
+    pub fn register(&mut self, point: MeterPoint, history_len: usize) -> Arc<StereoCorrelationTap> {
+        self.handles.entry(point).or_insert_with(|| Arc::new(StereoCorrelationTap::new(history_len))).clone()
+    }
+
+    pub fn remove(&mut self, point: MeterPoint) {
+        self.handles.remove(&point);
+    }
+
+    /// Drops every registered tap on a plugin's outputs, e.g. when it is
+    /// removed from the graph. Graph-output taps are untouched.
+    pub fn remove_plugin(&mut self, plugin: crate::id::PluginInstanceID) {
+        self.handles.retain(|point, _| !matches!(point, MeterPoint::PluginOutput { plugin: p, .. } if *p == plugin));
+    }
+
+    pub fn handle(&self, point: MeterPoint) -> Option<Arc<StereoCorrelationTap>> {
+        self.handles.get(&point).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::TerminalDirection;
+    use crate::graph::TerminalPortID;
+
+    #[test]
+    fn identical_channels_are_fully_correlated() {
+        let tap = StereoCorrelationTap::new(8);
+        let signal = vec![0.5, -0.25, 0.75, -0.5];
+        analyze_stereo_block(&tap, &signal, &signal, 1);
+        assert!((tap.correlation() - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fully_out_of_phase_channels_report_negative_one() {
+        let tap = StereoCorrelationTap::new(8);
+        let left = vec![0.5, -0.25, 0.75, -0.5];
+        let right: Vec<f32> = left.iter().map(|s| -s).collect();
+        analyze_stereo_block(&tap, &left, &right, 1);
+        assert!((tap.correlation() - -1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn goniometer_points_are_decimated() {
+        let tap = StereoCorrelationTap::new(64);
+        let left = vec![1.0_f32; 10];
+        let right = vec![1.0_f32; 10];
+        analyze_stereo_block(&tap, &left, &right, 4);
+        assert_eq!(tap.goniometer_points().len(), 3);
+    }
+
+    #[test]
+    fn the_history_ring_drops_the_oldest_point_once_full() {
+        let tap = StereoCorrelationTap::new(2);
+        tap.push_point(GoniometerPoint { mid: 0.0, side: 0.0 });
+        tap.push_point(GoniometerPoint { mid: 1.0, side: 0.0 });
+        tap.push_point(GoniometerPoint { mid: 2.0, side: 0.0 });
+
+        let points = tap.goniometer_points();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].mid, 1.0);
+        assert_eq!(points[1].mid, 2.0);
+    }
+
+    #[test]
+    fn registering_twice_returns_the_same_handle() {
+        let mut taps = StereoCorrelationTaps::new();
+        let point = MeterPoint::GraphOut(TerminalPortID::for_channel(TerminalDirection::GraphOut, 0));
+        let a = taps.register(point, 8);
+        a.write_correlation(0.3);
+        let b = taps.register(point, 8);
+        assert_eq!(b.correlation(), 0.3);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_only_its_taps() {
+        let mut taps = StereoCorrelationTaps::new();
+        let plugin = crate::id::PluginInstanceID::new();
+        let out = MeterPoint::GraphOut(TerminalPortID::for_channel(TerminalDirection::GraphOut, 0));
+        let on_plugin = MeterPoint::PluginOutput { plugin, port_index: 0 };
+        taps.register(out, 8);
+        taps.register(on_plugin, 8);
+
+        taps.remove_plugin(plugin);
+        assert!(taps.handle(on_plugin).is_none());
+        assert!(taps.handle(out).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn mismatched_channel_lengths_panics() {
+        let tap = StereoCorrelationTap::new(8);
+        analyze_stereo_block(&tap, &[0.0, 0.0], &[0.0], 1);
+    }
+}
@@ -0,0 +1,238 @@
+//! A declarative builder for small internal nodes.
+//!
+//! Hand-writing a node like [`OnsetDetectorNode`](super::onset_detector::OnsetDetectorNode)
+//! or [`PitchDetectorNode`](super::pitch_detector::PitchDetectorNode) means
+//! repeating the same boilerplate: a struct field per parameter, a
+//! [`ControlOutputBank`] wired up in `new`, and a hand-rolled `process`
+//! method. [`NodeBuilder`] lets a node declare its range-clamped, smoothed
+//! parameters and control outputs once, then supply only the per-block
+//! process closure that's actually specific to it.
+
+use std::collections::HashMap;
+
+use crate::graph::control_output::ControlOutputBank;
+
+/// One declared parameter: a range-clamped default value, smoothed toward
+/// its target over `smoothing_samples` samples (`0` or `1` applies a
+/// change immediately).
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub smoothing_samples: u32,
+}
+
+impl ParamSpec {
+    pub fn new(name: &'static str, min: f32, max: f32, default: f32) -> Self {
+        Self { name, min, max, default: default.clamp(min, max), smoothing_samples: 1 }
+    }
+
+    pub fn smoothed_over(mut self, smoothing_samples: u32) -> Self {
+        self.smoothing_samples = smoothing_samples;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Ramp {
+    min: f32,
+    max: f32,
+    current: f32,
+    target: f32,
+    step: f32,
+    smoothing_samples: u32,
+}
+
+impl Ramp {
+    fn from_spec(spec: &ParamSpec) -> Self {
+        Self {
+            min: spec.min,
+            max: spec.max,
+            current: spec.default,
+            target: spec.default,
+            step: 0.0,
+            smoothing_samples: spec.smoothing_samples.max(1),
+        }
+    }
+
+    fn set_target(&mut self, value: f32) {
+        self.target = value.clamp(self.min, self.max);
+        self.step = (self.target - self.current) / self.smoothing_samples as f32;
+        if self.smoothing_samples <= 1 {
+            self.current = self.target;
+        }
+    }
+
+    fn next(&mut self) -> f32 {
+        if (self.target - self.current).abs() <= self.step.abs().max(f32::EPSILON) {
+            self.current = self.target;
+        } else {
+            self.current += self.step;
+        }
+        self.current
+    }
+}
+
+/// A declared node's smoothed parameter values, by name.
+#[derive(Debug, Default)]
+pub struct Params {
+    ramps: HashMap<&'static str, Ramp>,
+}
+
+impl Params {
+    /// Sets a new target value for `name`, to be approached over its
+    /// declared smoothing window. Out-of-range values are clamped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` wasn't declared on the [`NodeBuilder`] this came
+    /// from.
+    pub fn set(&mut self, name: &str, value: f32) {
+        self.ramps.get_mut(name).unwrap_or_else(|| panic!("undeclared param {name:?}")).set_target(value);
+    }
+
+    /// The current smoothed value, without advancing it.
+    pub fn get(&self, name: &str) -> f32 {
+        self.ramps.get(name).unwrap_or_else(|| panic!("undeclared param {name:?}")).current
+    }
+
+    /// Advances `name`'s ramp by one sample toward its target, returning
+    /// the new current value. Call once per sample from a process closure
+    /// that needs sample-accurate smoothing.
+    pub fn next(&mut self, name: &str) -> f32 {
+        self.ramps.get_mut(name).unwrap_or_else(|| panic!("undeclared param {name:?}")).next()
+    }
+}
+
+/// Declares a node's parameters and control outputs, then builds a
+/// [`GenericNode`] around a process closure.
+#[derive(Default)]
+pub struct NodeBuilder {
+    params: Vec<ParamSpec>,
+    control_outputs: Vec<(&'static str, f32)>,
+}
+
+impl NodeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn param(mut self, spec: ParamSpec) -> Self {
+        self.params.push(spec);
+        self
+    }
+
+    pub fn control_output(mut self, name: &'static str, default: f32) -> Self {
+        self.control_outputs.push((name, default));
+        self
+    }
+
+    /// Wires up the declared params and control outputs around `process`,
+    /// which is called once per block with the live param table, the
+    /// control output bank to publish to, and the input block.
+    pub fn build<F>(self, process: F) -> GenericNode<F>
+    where
+        F: FnMut(&mut Params, &ControlOutputBank, &[f32]),
+    {
+        let mut params = Params::default();
+        for spec in &self.params {
+            params.ramps.insert(spec.name, Ramp::from_spec(spec));
+        }
+
+        let mut control_outputs = ControlOutputBank::new();
+        for (name, default) in &self.control_outputs {
+            control_outputs.declare(name, *default);
+        }
+
+        GenericNode { params, control_outputs, process }
+    }
+}
+
+/// A node assembled by [`NodeBuilder::build`]: declared params and control
+/// outputs, driven by a user-supplied process closure.
+pub struct GenericNode<F> {
+    params: Params,
+    control_outputs: ControlOutputBank,
+    process: F,
+}
+
+impl<F> GenericNode<F>
+where
+    F: FnMut(&mut Params, &ControlOutputBank, &[f32]),
+{
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        self.params.set(name, value);
+    }
+
+    pub fn param(&self, name: &str) -> f32 {
+        self.params.get(name)
+    }
+
+    pub fn control_outputs(&self) -> &ControlOutputBank {
+        &self.control_outputs
+    }
+
+    /// Runs the declared process closure over one block.
+    pub fn process(&mut self, block: &[f32]) {
+        (self.process)(&mut self.params, &self.control_outputs, block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_params_default_to_their_clamped_initial_value() {
+        let node = NodeBuilder::new()
+            .param(ParamSpec::new("gain", 0.0, 2.0, 1.0))
+            .build(|_, _, _| {});
+        assert_eq!(node.param("gain"), 1.0);
+    }
+
+    #[test]
+    fn setting_a_param_out_of_range_clamps_to_the_declared_bounds() {
+        let mut node = NodeBuilder::new()
+            .param(ParamSpec::new("gain", 0.0, 2.0, 1.0).smoothed_over(1))
+            .build(|_, _, _| {});
+        node.set_param("gain", 10.0);
+        assert_eq!(node.param("gain"), 2.0);
+    }
+
+    #[test]
+    fn a_smoothed_param_ramps_toward_its_target_instead_of_jumping() {
+        let node = NodeBuilder::new().param(ParamSpec::new("gain", 0.0, 2.0, 0.0).smoothed_over(4)).build(
+            |params, _, block: &[f32]| {
+                for _ in block {
+                    params.next("gain");
+                }
+            },
+        );
+        let mut node = node;
+        node.set_param("gain", 1.0);
+        node.process(&[0.0; 2]);
+        let mid = node.param("gain");
+        assert!(mid > 0.0 && mid < 1.0, "expected a partial ramp, got {mid}");
+        node.process(&[0.0; 2]);
+        assert!((node.param("gain") - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn control_outputs_declared_on_the_builder_are_writable_from_the_process_closure() {
+        let mut node = NodeBuilder::new().control_output("peak", 0.0).build(|_, outputs, block: &[f32]| {
+            let peak = block.iter().cloned().fold(0.0_f32, f32::max);
+            outputs.get("peak").unwrap().write(peak);
+        });
+        node.process(&[0.1, 0.9, 0.3]);
+        assert_eq!(node.control_outputs().get("peak").unwrap().read(), 0.9);
+    }
+
+    #[test]
+    #[should_panic(expected = "undeclared param")]
+    fn reading_an_undeclared_param_panics() {
+        let node = NodeBuilder::new().build(|_: &mut Params, _, _: &[f32]| {});
+        node.param("missing");
+    }
+}
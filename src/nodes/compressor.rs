@@ -0,0 +1,174 @@
+//! A sidechain-aware dynamics compressor, shipped as a reference internal
+//! effect.
+//!
+//! Unlike the single-input analysis nodes in this module,
+//! [`SidechainCompressorNode`] takes two input blocks (the signal being
+//! compressed, and the sidechain signal whose level drives the gain
+//! reduction) and writes a processed output block, exercising multi-port
+//! routing through a built-in. It also reports a fixed lookahead as
+//! [`SidechainCompressorNode::latency_samples`] so a host can delay-align
+//! the rest of the graph against it, the same way an external plugin would
+//! report processing latency.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::graph::control_output::{ControlOutputBank, ControlOutputPort};
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn linear_to_db(linear: f32) -> f32 {
+    20.0 * linear.max(1e-8).log10()
+}
+
+pub struct SidechainCompressorNode {
+    /// Level above which the sidechain signal triggers gain reduction.
+    threshold_db: f32,
+    /// How strongly level above the threshold is reduced; `4.0` means a 4:1
+    /// ratio.
+    ratio: f32,
+    attack_samples: u32,
+    release_samples: u32,
+    /// Delays the main input so the detector can react to a transient
+    /// before it reaches the output, at the cost of this much latency.
+    lookahead_samples: u32,
+    /// Current smoothed gain reduction, in dB (always `>= 0`).
+    reduction_db: f32,
+    lookahead_buffer: VecDeque<f32>,
+    control_outputs: ControlOutputBank,
+    gain_reduction_port: Arc<ControlOutputPort>,
+}
+
+impl SidechainCompressorNode {
+    pub fn new(
+        threshold_db: f32,
+        ratio: f32,
+        attack_samples: u32,
+        release_samples: u32,
+        lookahead_samples: u32,
+    ) -> Self {
+        let mut control_outputs = ControlOutputBank::new();
+        let gain_reduction_port = control_outputs.declare("gain_reduction_db", 0.0);
+        Self {
+            threshold_db,
+            ratio: ratio.max(1.0),
+            attack_samples: attack_samples.max(1),
+            release_samples: release_samples.max(1),
+            lookahead_samples,
+            reduction_db: 0.0,
+            lookahead_buffer: VecDeque::from(vec![0.0; lookahead_samples as usize]),
+            control_outputs,
+            gain_reduction_port,
+        }
+    }
+
+    pub fn control_outputs(&self) -> &ControlOutputBank {
+        &self.control_outputs
+    }
+
+    /// The number of samples of output delay this node introduces, for the
+    /// host to compensate elsewhere in the graph.
+    pub fn latency_samples(&self) -> u32 {
+        self.lookahead_samples
+    }
+
+    /// Compresses `main` against the level of `sidechain` into `out`, one
+    /// block at a time. All three slices must be the same length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `main`, `sidechain`, and `out` aren't all the same length.
+    pub fn process(&mut self, main: &[f32], sidechain: &[f32], out: &mut [f32]) {
+        assert_eq!(main.len(), sidechain.len(), "main and sidechain blocks must be the same length");
+        assert_eq!(main.len(), out.len(), "output block must match the input block length");
+
+        let mut peak_reduction_db = 0.0f32;
+        for i in 0..main.len() {
+            let delayed = if self.lookahead_samples == 0 {
+                main[i]
+            } else {
+                self.lookahead_buffer.push_back(main[i]);
+                self.lookahead_buffer.pop_front().unwrap_or(0.0)
+            };
+
+            let detected_db = linear_to_db(sidechain[i].abs());
+            let target_reduction_db = if detected_db > self.threshold_db {
+                (detected_db - self.threshold_db) * (1.0 - 1.0 / self.ratio)
+            } else {
+                0.0
+            };
+
+            // One-pole smoothing toward the instantaneous target, attacking
+            // faster than it releases.
+            let time_constant_samples = if target_reduction_db > self.reduction_db {
+                self.attack_samples
+            } else {
+                self.release_samples
+            };
+            let coefficient = (-1.0 / time_constant_samples as f32).exp();
+            self.reduction_db = target_reduction_db + (self.reduction_db - target_reduction_db) * coefficient;
+
+            peak_reduction_db = peak_reduction_db.max(self.reduction_db);
+            out[i] = delayed * db_to_linear(-self.reduction_db);
+        }
+
+        self.gain_reduction_port.write(peak_reduction_db);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_loud_sidechain_reduces_the_main_signal_below_threshold() {
+        let mut node = SidechainCompressorNode::new(-12.0, 4.0, 8, 64, 0);
+        let main = vec![0.5f32; 512];
+        let sidechain = vec![0.9f32; 512];
+        let mut out = vec![0.0f32; 512];
+
+        node.process(&main, &sidechain, &mut out);
+
+        let settled = &out[400..];
+        let settled_rms = (settled.iter().map(|s| s * s).sum::<f32>() / settled.len() as f32).sqrt();
+        assert!(settled_rms < 0.5, "expected gain reduction to bring the level down, got rms {settled_rms}");
+    }
+
+    #[test]
+    fn a_quiet_sidechain_leaves_the_main_signal_untouched() {
+        let mut node = SidechainCompressorNode::new(-12.0, 4.0, 8, 64, 0);
+        let main = vec![0.3f32; 512];
+        let sidechain = vec![0.001f32; 512];
+        let mut out = vec![0.0f32; 512];
+
+        node.process(&main, &sidechain, &mut out);
+
+        let settled = &out[400..];
+        for &sample in settled {
+            assert!((sample - 0.3).abs() < 0.01, "expected near-unity gain, got {sample}");
+        }
+    }
+
+    #[test]
+    fn lookahead_delays_the_output_by_the_configured_sample_count() {
+        let mut node = SidechainCompressorNode::new(0.0, 1.0, 8, 8, 4);
+        let main = vec![1.0f32; 8];
+        let sidechain = vec![0.0f32; 8];
+        let mut out = vec![0.0f32; 8];
+
+        node.process(&main, &sidechain, &mut out);
+
+        assert_eq!(&out[..4], &[0.0; 4]);
+        assert_eq!(node.latency_samples(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn mismatched_block_lengths_panic() {
+        let mut node = SidechainCompressorNode::new(-12.0, 4.0, 8, 64, 0);
+        let mut out = vec![0.0f32; 4];
+        node.process(&[0.0; 4], &[0.0; 2], &mut out);
+    }
+}
@@ -0,0 +1,174 @@
+//! A stereo gain + pan utility node: basic mixer-channel functionality a
+//! host can offer without needing an external plugin for it.
+//!
+//! Hand-written rather than built on [`NodeBuilder`](super::builder::NodeBuilder),
+//! since its equal-power pan law needs both channels of a stereo block
+//! advanced through the same smoothed parameters in lockstep, not one
+//! shared process closure called independently per channel.
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Ramp {
+    current: f32,
+    target: f32,
+    step: f32,
+}
+
+impl Ramp {
+    fn new(initial: f32) -> Self {
+        Self { current: initial, target: initial, step: 0.0 }
+    }
+
+    fn set_target(&mut self, target: f32, ramp_samples: u32) {
+        self.target = target;
+        self.step = (self.target - self.current) / ramp_samples.max(1) as f32;
+    }
+
+    fn next(&mut self) -> f32 {
+        if (self.target - self.current).abs() <= self.step.abs().max(f32::EPSILON) {
+            self.current = self.target;
+        } else {
+            self.current += self.step;
+        }
+        self.current
+    }
+}
+
+/// A stereo gain + pan node: overall level in dB, and equal-power pan from
+/// `-1.0` (full left) through `0.0` (center) to `1.0` (full right). Both
+/// parameters are smoothed over a fixed ramp so a host changing them live
+/// doesn't click.
+pub struct GainPanNode {
+    gain_db: Ramp,
+    pan: Ramp,
+    ramp_samples: u32,
+}
+
+impl GainPanNode {
+    pub fn new(ramp_samples: u32) -> Self {
+        Self { gain_db: Ramp::new(0.0), pan: Ramp::new(0.0), ramp_samples: ramp_samples.max(1) }
+    }
+
+    /// Sets a new gain target in dB, approached over the node's ramp.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db.set_target(gain_db, self.ramp_samples);
+    }
+
+    /// Sets a new pan target, clamped to `[-1.0, 1.0]`, approached over the
+    /// node's ramp.
+    pub fn set_pan(&mut self, pan: f32) {
+        self.pan.set_target(pan.clamp(-1.0, 1.0), self.ramp_samples);
+    }
+
+    /// Applies gain and equal-power pan to a stereo block in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `left` and `right` aren't the same length.
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        assert_eq!(left.len(), right.len(), "left and right blocks must be the same length");
+        for i in 0..left.len() {
+            let gain = db_to_linear(self.gain_db.next());
+            let pan = self.pan.next();
+            // Equal-power law: `pan` maps linearly onto a quarter-turn, so
+            // `sin^2 + cos^2 == 1` keeps perceived loudness constant as it
+            // sweeps across the stereo field.
+            let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+            let (right_gain, left_gain) = angle.sin_cos();
+            left[i] *= gain * left_gain;
+            right[i] *= gain * right_gain;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(node: &mut GainPanNode, samples: usize) {
+        let mut left = vec![1.0f32; samples];
+        let mut right = vec![1.0f32; samples];
+        node.process(&mut left, &mut right);
+    }
+
+    #[test]
+    fn centered_unity_gain_splits_equal_power_between_channels() {
+        let mut node = GainPanNode::new(1);
+        settle(&mut node, 4);
+        let mut left = [1.0f32];
+        let mut right = [1.0f32];
+        node.process(&mut left, &mut right);
+        assert!((left[0] - right[0]).abs() < 1e-6);
+        assert!((left[0] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn hard_left_pan_silences_the_right_channel() {
+        let mut node = GainPanNode::new(1);
+        node.set_pan(-1.0);
+        settle(&mut node, 4);
+        let mut left = [1.0f32];
+        let mut right = [1.0f32];
+        node.process(&mut left, &mut right);
+        assert!((left[0] - 1.0).abs() < 1e-4);
+        assert!(right[0].abs() < 1e-4);
+    }
+
+    #[test]
+    fn hard_right_pan_silences_the_left_channel() {
+        let mut node = GainPanNode::new(1);
+        node.set_pan(1.0);
+        settle(&mut node, 4);
+        let mut left = [1.0f32];
+        let mut right = [1.0f32];
+        node.process(&mut left, &mut right);
+        assert!(left[0].abs() < 1e-4);
+        assert!((right[0] - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn gain_in_db_attenuates_both_channels() {
+        let mut node = GainPanNode::new(1);
+        node.set_gain_db(-6.0);
+        settle(&mut node, 4);
+        let mut left = [1.0f32];
+        let mut right = [1.0f32];
+        node.process(&mut left, &mut right);
+        let expected = db_to_linear(-6.0) * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((left[0] - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn pan_out_of_range_is_clamped() {
+        let mut node = GainPanNode::new(1);
+        node.set_pan(5.0);
+        settle(&mut node, 4);
+        let mut left = [1.0f32];
+        let mut right = [1.0f32];
+        node.process(&mut left, &mut right);
+        assert!(left[0].abs() < 1e-4);
+        assert!((right[0] - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn changing_gain_ramps_instead_of_jumping() {
+        let mut node = GainPanNode::new(8);
+        node.set_gain_db(-24.0);
+        let mut left = vec![1.0f32; 2];
+        let mut right = vec![1.0f32; 2];
+        node.process(&mut left, &mut right);
+        assert!(left[0] > db_to_linear(-24.0) * std::f32::consts::FRAC_1_SQRT_2, "expected a partial ramp");
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn mismatched_block_lengths_panic() {
+        let mut node = GainPanNode::new(1);
+        let mut left = [0.0f32; 4];
+        let mut right = [0.0f32; 2];
+        node.process(&mut left, &mut right);
+    }
+}
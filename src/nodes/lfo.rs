@@ -0,0 +1,202 @@
+//! A built-in low-frequency oscillator node.
+//!
+//! Rather than a real audio-rate signal, an [`LfoNode`] publishes its
+//! current waveform value once per block through a
+//! [`ControlOutputPort`](crate::graph::control_output::ControlOutputPort),
+//! the same control-rate mechanism [`PitchDetectorNode`](super::pitch_detector::PitchDetectorNode)
+//! uses to publish its detected pitch. A host wires that port into whatever
+//! it wants modulated — another node's parameter, or forwarded on to a
+//! hosted plugin's own parameter automation — without the LFO needing to
+//! know anything about its destination.
+
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::graph::control_output::{ControlOutputBank, ControlOutputPort};
+use crate::transport::TempoMap;
+
+/// The waveform an [`LfoNode`] generates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Square,
+    /// Sample & hold: a new random value in `-1.0..=1.0` on every cycle.
+    SampleAndHold,
+}
+
+/// How fast an [`LfoNode`] cycles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LfoRate {
+    /// A free-running rate, independent of tempo.
+    Hz(f32),
+    /// Tempo-synced: one full cycle every `beats_per_cycle` beats of the
+    /// transport (e.g. `4.0` for one cycle per bar in 4/4, `0.25` for one
+    /// cycle per sixteenth note).
+    TempoSynced { beats_per_cycle: f64 },
+}
+
+/// A low-frequency modulation source, publishing its waveform value at
+/// control rate once per processed block.
+pub struct LfoNode {
+    sample_rate: f64,
+    shape: LfoShape,
+    rate: LfoRate,
+    /// `0.0..1.0`, wrapping on every cycle.
+    phase: f64,
+    rng_state: u32,
+    held_value: f32,
+    control_outputs: ControlOutputBank,
+    value_port: Arc<ControlOutputPort>,
+}
+
+impl LfoNode {
+    pub fn new(sample_rate: f64, shape: LfoShape, rate: LfoRate) -> Self {
+        let mut control_outputs = ControlOutputBank::new();
+        let value_port = control_outputs.declare("value", 0.0);
+        Self {
+            sample_rate,
+            shape,
+            rate,
+            phase: 0.0,
+            rng_state: 0x9E3779B9,
+            held_value: 0.0,
+            control_outputs,
+            value_port,
+        }
+    }
+
+    pub fn control_outputs(&self) -> &ControlOutputBank {
+        &self.control_outputs
+    }
+
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+
+    pub fn set_rate(&mut self, rate: LfoRate) {
+        self.rate = rate;
+    }
+
+    fn next_random_bipolar(&mut self) -> f32 {
+        // xorshift32: no external `rand` dependency needed for a
+        // not-cryptographic, just-needs-to-look-random S&H value.
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        (self.rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    fn phase_increment_per_sample(&self, num_samples: usize, tempo_map: Option<&TempoMap>, start_sample: u64) -> f64 {
+        match self.rate {
+            LfoRate::Hz(hz) => hz as f64 / self.sample_rate,
+            LfoRate::TempoSynced { beats_per_cycle } => {
+                if num_samples == 0 || beats_per_cycle <= 0.0 {
+                    return 0.0;
+                }
+                let Some(tempo_map) = tempo_map else { return 0.0 };
+                let beats =
+                    tempo_map.beat_at_sample(start_sample + num_samples as u64) - tempo_map.beat_at_sample(start_sample);
+                (beats / beats_per_cycle) / num_samples as f64
+            }
+        }
+    }
+
+    /// Advances the LFO by `num_samples` and publishes the resulting
+    /// waveform value (bipolar, `-1.0..=1.0`) to its `"value"` control
+    /// output. `tempo_map`/`start_sample` are only consulted for
+    /// [`LfoRate::TempoSynced`] rates; pass `None` for a free-running one.
+    pub fn process(&mut self, num_samples: usize, tempo_map: Option<&TempoMap>, start_sample: u64) {
+        let phase_increment = self.phase_increment_per_sample(num_samples, tempo_map, start_sample);
+        for _ in 0..num_samples {
+            self.phase += phase_increment;
+            if self.phase >= 1.0 {
+                self.phase -= self.phase.floor();
+                if self.shape == LfoShape::SampleAndHold {
+                    self.held_value = self.next_random_bipolar();
+                }
+            }
+        }
+        self.value_port.write(self.sample_shape());
+    }
+
+    fn sample_shape(&self) -> f32 {
+        match self.shape {
+            LfoShape::Sine => (2.0 * PI * self.phase as f32).sin(),
+            LfoShape::Triangle => 1.0 - 4.0 * (self.phase as f32 - 0.5).abs(),
+            LfoShape::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            LfoShape::SampleAndHold => self.held_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_free_running_sine_completes_one_cycle_in_its_period() {
+        let sample_rate = 1000.0;
+        let mut lfo = LfoNode::new(sample_rate, LfoShape::Sine, LfoRate::Hz(10.0));
+        // 10Hz at 1000 samples/sec: one full cycle every 100 samples.
+        lfo.process(25, None, 0);
+        let quarter = lfo.control_outputs().get("value").unwrap().read();
+        assert!(quarter > 0.9, "expected close to the sine's peak at a quarter cycle, got {quarter}");
+    }
+
+    #[test]
+    fn square_wave_is_high_for_the_first_half_of_its_cycle_and_low_for_the_second() {
+        let mut lfo = LfoNode::new(1000.0, LfoShape::Square, LfoRate::Hz(10.0));
+        lfo.process(10, None, 0);
+        assert_eq!(lfo.control_outputs().get("value").unwrap().read(), 1.0);
+        lfo.process(40, None, 0);
+        assert_eq!(lfo.control_outputs().get("value").unwrap().read(), -1.0);
+    }
+
+    #[test]
+    fn triangle_wave_peaks_at_the_midpoint_of_its_cycle() {
+        let mut lfo = LfoNode::new(1000.0, LfoShape::Triangle, LfoRate::Hz(10.0));
+        lfo.process(50, None, 0);
+        let peak = lfo.control_outputs().get("value").unwrap().read();
+        assert!((peak - 1.0).abs() < 1e-3, "expected the triangle's peak at half a cycle, got {peak}");
+    }
+
+    #[test]
+    fn sample_and_hold_changes_value_only_once_per_cycle() {
+        let mut lfo = LfoNode::new(1000.0, LfoShape::SampleAndHold, LfoRate::Hz(100.0));
+        lfo.process(5, None, 0);
+        let first = lfo.control_outputs().get("value").unwrap().read();
+        lfo.process(3, None, 0);
+        let still_within_cycle = lfo.control_outputs().get("value").unwrap().read();
+        assert_eq!(first, still_within_cycle, "should hold its value until the next cycle wraps");
+
+        lfo.process(2, None, 0);
+        let after_wrap = lfo.control_outputs().get("value").unwrap().read();
+        assert!((-1.0..=1.0).contains(&after_wrap));
+    }
+
+    #[test]
+    fn tempo_synced_rate_advances_with_the_transport_instead_of_a_fixed_hz() {
+        let tempo_map = TempoMap::new(1000.0, 120.0);
+        // At 120bpm, one beat is 500ms = 500 samples at 1000Hz; one cycle
+        // per beat means a full cycle every 500 samples.
+        let mut lfo = LfoNode::new(1000.0, LfoShape::Square, LfoRate::TempoSynced { beats_per_cycle: 1.0 });
+        lfo.process(100, Some(&tempo_map), 0);
+        assert_eq!(lfo.control_outputs().get("value").unwrap().read(), 1.0);
+        lfo.process(300, Some(&tempo_map), 100);
+        assert_eq!(lfo.control_outputs().get("value").unwrap().read(), -1.0);
+    }
+
+    #[test]
+    fn a_tempo_synced_rate_without_a_tempo_map_does_not_advance() {
+        let mut lfo = LfoNode::new(1000.0, LfoShape::Sine, LfoRate::TempoSynced { beats_per_cycle: 1.0 });
+        lfo.process(1000, None, 0);
+        assert_eq!(lfo.control_outputs().get("value").unwrap().read(), 0.0);
+    }
+}
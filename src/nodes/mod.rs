@@ -0,0 +1,10 @@
+//! Internal nodes shipped with Dropseed: small built-in processors that
+//! don't need an external plugin to host.
+
+pub mod builder;
+pub mod compressor;
+pub mod gain_pan;
+pub mod lfo;
+pub mod onset_detector;
+pub mod pitch_detector;
+pub mod sample_player;
@@ -0,0 +1,228 @@
+//! Sample-playback internal node, turning a loaded [`PcmRAM`] resource into
+//! an instrument.
+//!
+//! A note-on starts playback from [`SampleParams::start_frame`], resampled
+//! (via linear interpolation) relative to the sample's embedded root note
+//! so different note IDs play back at the correct pitch. A note-off stops
+//! it. This is the one internal node that reaches into [`resource`](crate::resource)
+//! rather than processing audio it's handed, since its whole job is
+//! auditioning a resource the host loaded through [`PcmLoader`](crate::resource::PcmLoader).
+
+use std::sync::Arc;
+
+use crate::plugin::NoteEvent;
+use crate::resource::PcmRAM;
+
+/// The default root note (middle C) assumed for a sample with no embedded
+/// root note metadata.
+const DEFAULT_ROOT_NOTE: i32 = 60;
+
+/// Playback start point and optional loop region, in sample frames.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SampleParams {
+    pub start_frame: usize,
+    /// When both are set, playback wraps back to `loop_start_frame` on
+    /// reaching `loop_end_frame` instead of stopping.
+    pub loop_start_frame: Option<usize>,
+    pub loop_end_frame: Option<usize>,
+}
+
+/// Plays back a single loaded [`PcmRAM`] resource in response to note
+/// events, pitch-shifting relative to its root note.
+pub struct SamplePlayerNode {
+    sample: Option<Arc<PcmRAM>>,
+    root_note: i32,
+    params: SampleParams,
+    playing: bool,
+    /// Fractional frame position into the sample, advanced by
+    /// `pitch_ratio` samples of output.
+    position: f64,
+    pitch_ratio: f64,
+}
+
+impl SamplePlayerNode {
+    pub fn new() -> Self {
+        Self {
+            sample: None,
+            root_note: DEFAULT_ROOT_NOTE,
+            params: SampleParams::default(),
+            playing: false,
+            position: 0.0,
+            pitch_ratio: 1.0,
+        }
+    }
+
+    /// Loads a resource to play back, stopping any playback in progress.
+    /// Its embedded root note is used for pitch-shifting, defaulting to
+    /// middle C if it has none.
+    pub fn load(&mut self, sample: Arc<PcmRAM>) {
+        self.root_note = sample.metadata().root_note.map(|n| n as i32).unwrap_or(DEFAULT_ROOT_NOTE);
+        self.sample = Some(sample);
+        self.playing = false;
+        self.position = 0.0;
+    }
+
+    pub fn set_params(&mut self, params: SampleParams) {
+        self.params = params;
+    }
+
+    /// Starts (note-on, `velocity > 0.0`) or stops (note-off) playback,
+    /// pitch-shifted relative to the loaded sample's root note.
+    pub fn handle_note(&mut self, note: NoteEvent) {
+        if note.velocity > 0.0 {
+            self.pitch_ratio = 2f64.powf((note.note_id - self.root_note) as f64 / 12.0);
+            self.position = self.params.start_frame as f64;
+            self.playing = self.sample.is_some();
+        } else {
+            self.playing = false;
+        }
+    }
+
+    /// Renders `out.len()` mono frames of the loaded sample's first
+    /// channel, advancing playback. Writes silence once nothing is loaded,
+    /// nothing is playing, or playback has run off the end of a
+    /// non-looping sample.
+    pub fn process(&mut self, out: &mut [f32]) {
+        let Some(sample) = self.sample.clone() else {
+            out.fill(0.0);
+            return;
+        };
+        let channel = sample.channel(0);
+        let loop_end = self.params.loop_end_frame.unwrap_or(channel.len());
+
+        for slot in out.iter_mut() {
+            if !self.playing || self.position >= channel.len() as f64 {
+                self.playing = false;
+                *slot = 0.0;
+                continue;
+            }
+
+            let frame_index = self.position as usize;
+            let frac = (self.position - frame_index as f64) as f32;
+            let a = channel[frame_index];
+            let b = channel.get(frame_index + 1).copied().unwrap_or(a);
+            *slot = a + (b - a) * frac;
+
+            self.position += self.pitch_ratio;
+            if let Some(loop_start) = self.params.loop_start_frame {
+                if self.position >= loop_end as f64 {
+                    self.position = loop_start as f64 + (self.position - loop_end as f64);
+                }
+            }
+        }
+    }
+}
+
+impl Default for SamplePlayerNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::PcmMetadata;
+
+    fn ramp_sample(num_frames: usize, root_note: Option<u8>) -> Arc<PcmRAM> {
+        let channel: Vec<f32> = (0..num_frames).map(|i| i as f32).collect();
+        Arc::new(PcmRAM::new(vec![channel], 48_000, PcmMetadata { root_note, ..Default::default() }))
+    }
+
+    fn note_on(note_id: i32) -> NoteEvent {
+        NoteEvent { sample_offset: 0, note_id, velocity: 1.0 }
+    }
+
+    fn note_off(note_id: i32) -> NoteEvent {
+        NoteEvent { sample_offset: 0, note_id, velocity: 0.0 }
+    }
+
+    #[test]
+    fn no_loaded_sample_renders_silence() {
+        let mut node = SamplePlayerNode::new();
+        node.handle_note(note_on(60));
+        let mut out = [1.0f32; 4];
+        node.process(&mut out);
+        assert_eq!(out, [0.0; 4]);
+    }
+
+    #[test]
+    fn playback_at_the_root_note_plays_at_unit_speed() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(10, Some(60)));
+        node.handle_note(note_on(60));
+
+        let mut out = [0.0f32; 4];
+        node.process(&mut out);
+        assert_eq!(out, [0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn playback_an_octave_up_consumes_source_frames_twice_as_fast() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(10, Some(60)));
+        node.handle_note(note_on(72));
+
+        let mut out = [0.0f32; 4];
+        node.process(&mut out);
+        assert_eq!(out, [0.0, 2.0, 4.0, 6.0]);
+    }
+
+    #[test]
+    fn note_off_stops_playback() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(10, Some(60)));
+        node.handle_note(note_on(60));
+        node.handle_note(note_off(60));
+
+        let mut out = [1.0f32; 4];
+        node.process(&mut out);
+        assert_eq!(out, [0.0; 4]);
+    }
+
+    #[test]
+    fn playback_stops_at_the_end_of_a_non_looping_sample() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(3, Some(60)));
+        node.handle_note(note_on(60));
+
+        let mut out = [0.0f32; 5];
+        node.process(&mut out);
+        assert_eq!(out, [0.0, 1.0, 2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn a_configured_loop_wraps_instead_of_stopping() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(4, Some(60)));
+        node.set_params(SampleParams { start_frame: 0, loop_start_frame: Some(1), loop_end_frame: Some(4) });
+        node.handle_note(note_on(60));
+
+        let mut out = [0.0f32; 7];
+        node.process(&mut out);
+        assert_eq!(out, [0.0, 1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn a_sample_with_no_embedded_root_note_defaults_to_middle_c() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(10, None));
+        node.handle_note(note_on(60));
+
+        let mut out = [0.0f32; 2];
+        node.process(&mut out);
+        assert_eq!(out, [0.0, 1.0]);
+    }
+
+    #[test]
+    fn starting_mid_sample_honors_the_configured_start_frame() {
+        let mut node = SamplePlayerNode::new();
+        node.load(ramp_sample(10, Some(60)));
+        node.set_params(SampleParams { start_frame: 5, ..Default::default() });
+        node.handle_note(note_on(60));
+
+        let mut out = [0.0f32; 3];
+        node.process(&mut out);
+        assert_eq!(out, [5.0, 6.0, 7.0]);
+    }
+}
@@ -0,0 +1,286 @@
+//! Tempo-synced arpeggiator note-effect.
+//!
+//! Every event is derived from the absolute musical beat of a step rather
+//! than advanced incrementally from the previous block, the same way
+//! [`AutomationLane::value_at`](crate::automation::AutomationLane::value_at)
+//! is a pure function of an absolute sample position rather than stateful
+//! playback. That makes the arpeggiator correct across loop-backs and
+//! seeks for free: whatever beat the transport lands on, the steps
+//! surrounding it are recomputed from scratch instead of drifting from
+//! wherever internal state last left off.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::plugin::event_quantizer::NoteEvent;
+use crate::transport::TempoMap;
+
+/// The order in which held notes are stepped through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArpPattern {
+    Up,
+    Down,
+    UpDown,
+    /// Deterministic per-step pseudo-random selection, seeded by
+    /// [`ArpSettings::seed`].
+    Random,
+}
+
+/// Per-plugin arpeggiator settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArpSettings {
+    pub pattern: ArpPattern,
+    /// Number of octaves the held chord is layered across, stepping
+    /// upward from the notes as played. Must be at least `1`.
+    pub octave_range: u8,
+    /// Step length as a fraction of a beat (e.g. `0.25` for 1/16th notes
+    /// against a quarter-note beat), matching
+    /// [`QuantizeGrid`](crate::plugin::QuantizeGrid)'s convention.
+    pub rate: f64,
+    /// Fraction of the step length a note stays held before its note-off,
+    /// in `(0.0, 1.0]`.
+    pub gate: f64,
+    /// Fraction of the step length every other step is delayed by, in
+    /// `[0.0, 1.0)`.
+    pub swing: f64,
+    pub seed: u64,
+}
+
+impl ArpSettings {
+    pub fn new(pattern: ArpPattern, octave_range: u8, rate: f64) -> Self {
+        assert!(octave_range >= 1);
+        assert!(rate > 0.0);
+        Self { pattern, octave_range, rate, gate: 0.5, swing: 0.0, seed: 0 }
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^ (x >> 31)
+}
+
+/// The held chord laid out across octaves, ascending.
+fn ascending_sequence(held: &[i32], octave_range: u8) -> Vec<i32> {
+    let mut sequence = Vec::with_capacity(held.len() * octave_range as usize);
+    for octave in 0..octave_range as i32 {
+        for &note in held {
+            sequence.push(note + 12 * octave);
+        }
+    }
+    sequence
+}
+
+/// Picks the note to play at absolute `step_index`, given the held chord
+/// already laid out as `sequence` by [`ascending_sequence`].
+fn note_for_step(pattern: ArpPattern, sequence: &[i32], step_index: i64, seed: u64) -> i32 {
+    let len = sequence.len() as i64;
+    match pattern {
+        ArpPattern::Up => sequence[step_index.rem_euclid(len) as usize],
+        ArpPattern::Down => sequence[(len - 1 - step_index.rem_euclid(len)) as usize],
+        ArpPattern::UpDown if len > 1 => {
+            let cycle = 2 * (len - 1);
+            let position = step_index.rem_euclid(cycle);
+            let index = if position < len { position } else { cycle - position };
+            sequence[index as usize]
+        }
+        ArpPattern::UpDown => sequence[0],
+        ArpPattern::Random => {
+            let hash = splitmix64(seed ^ step_index as u64);
+            sequence[(hash % len as u64) as usize]
+        }
+    }
+}
+
+/// The absolute beat a step starts on, including swing delay on odd
+/// steps.
+fn step_start_beat(settings: &ArpSettings, step_index: i64) -> f64 {
+    let base = step_index as f64 * settings.rate;
+    if step_index.rem_euclid(2) == 1 {
+        base + settings.swing * settings.rate
+    } else {
+        base
+    }
+}
+
+/// Per-plugin tempo-synced arpeggiator, turning a held chord into a
+/// stream of note-on/note-off [`NoteEvent`]s.
+#[derive(Debug, Default)]
+pub struct Arpeggiator {
+    settings: HashMap<PluginInstanceID, ArpSettings>,
+}
+
+impl Arpeggiator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_settings(&mut self, plugin: PluginInstanceID, settings: ArpSettings) {
+        self.settings.insert(plugin, settings);
+    }
+
+    pub fn clear_settings(&mut self, plugin: PluginInstanceID) {
+        self.settings.remove(&plugin);
+    }
+
+    /// Returns the note-on/note-off events (note-off encoded as a
+    /// zero-velocity event, matching [`midi_import`](crate::util::midi_import)'s
+    /// convention) that fall within `[block_start_sample, block_start_sample +
+    /// block_frames)`, arpeggiating `held_notes` (as currently-held note
+    /// IDs, in the order they were played). Returns nothing if `plugin`
+    /// has no settings configured or no notes are held.
+    pub fn events_for_block(
+        &self,
+        plugin: PluginInstanceID,
+        tempo_map: &TempoMap,
+        held_notes: &[i32],
+        block_start_sample: u64,
+        block_frames: u32,
+    ) -> Vec<NoteEvent> {
+        let Some(settings) = self.settings.get(&plugin) else {
+            return Vec::new();
+        };
+        if held_notes.is_empty() {
+            return Vec::new();
+        }
+        let sequence = ascending_sequence(held_notes, settings.octave_range);
+
+        let block_end_sample = block_start_sample + block_frames as u64;
+        let start_beat = tempo_map.beat_at_sample(block_start_sample);
+        let end_beat = tempo_map.beat_at_sample(block_end_sample);
+
+        // Start one step early so a previous step's note-off (which can
+        // land after its note-on's step boundary once gated) isn't missed.
+        let mut step_index = (start_beat / settings.rate).floor() as i64 - 1;
+        let mut events = Vec::new();
+        loop {
+            let on_beat = step_start_beat(settings, step_index);
+            if on_beat >= end_beat {
+                break;
+            }
+            let off_beat = on_beat + settings.rate * settings.gate;
+            let note_id = note_for_step(settings.pattern, &sequence, step_index, settings.seed);
+
+            // Compare in beat space rather than against the converted sample,
+            // since beats before the map's start clamp to sample 0 and would
+            // otherwise look like they fall inside every block.
+            if on_beat >= start_beat && on_beat < end_beat {
+                let on_sample = tempo_map.sample_at_beat(on_beat);
+                events.push(NoteEvent {
+                    sample_offset: (on_sample - block_start_sample) as u32,
+                    note_id,
+                    velocity: 1.0,
+                });
+            }
+            if off_beat >= start_beat && off_beat < end_beat {
+                let off_sample = tempo_map.sample_at_beat(off_beat);
+                events.push(NoteEvent {
+                    sample_offset: (off_sample - block_start_sample) as u32,
+                    note_id,
+                    velocity: 0.0,
+                });
+            }
+
+            step_index += 1;
+        }
+
+        events.sort_by_key(|e| e.sample_offset);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tempo_map() -> TempoMap {
+        TempoMap::new(48_000.0, 120.0)
+    }
+
+    #[test]
+    fn an_unconfigured_plugin_produces_no_events() {
+        let arp = Arpeggiator::new();
+        let events = arp.events_for_block(PluginInstanceID::new(), &tempo_map(), &[60], 0, 48_000);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn no_held_notes_produces_no_events() {
+        let mut arp = Arpeggiator::new();
+        let plugin = PluginInstanceID::new();
+        arp.set_settings(plugin, ArpSettings::new(ArpPattern::Up, 1, 0.25));
+        assert!(arp.events_for_block(plugin, &tempo_map(), &[], 0, 48_000).is_empty());
+    }
+
+    #[test]
+    fn up_pattern_steps_ascending_through_the_chord() {
+        let mut arp = Arpeggiator::new();
+        let plugin = PluginInstanceID::new();
+        // 120 BPM, rate 0.25 beats (1/16th note) = one step per 6000 samples.
+        arp.set_settings(plugin, ArpSettings::new(ArpPattern::Up, 1, 0.25));
+        let events = arp.events_for_block(plugin, &tempo_map(), &[60, 64, 67], 0, 24_000);
+
+        let note_ons: Vec<i32> = events.iter().filter(|e| e.velocity > 0.0).map(|e| e.note_id).collect();
+        assert_eq!(note_ons, vec![60, 64, 67, 60]);
+    }
+
+    #[test]
+    fn seeking_to_a_later_beat_resumes_at_the_correct_step_without_drift() {
+        let mut arp = Arpeggiator::new();
+        let plugin = PluginInstanceID::new();
+        arp.set_settings(plugin, ArpSettings::new(ArpPattern::Up, 1, 0.25));
+        let held = [60, 64, 67];
+
+        // Play the whole thing from the start...
+        let full = arp.events_for_block(plugin, &tempo_map(), &held, 0, 48_000);
+        // ...versus jumping straight to a block in the middle (a "seek").
+        let seeked = arp.events_for_block(plugin, &tempo_map(), &held, 24_000, 24_000);
+
+        let full_in_range: Vec<_> = full
+            .iter()
+            .filter_map(|e| {
+                let absolute = e.sample_offset as u64;
+                (absolute >= 24_000).then(|| NoteEvent { sample_offset: (absolute - 24_000) as u32, ..*e })
+            })
+            .collect();
+        assert_eq!(seeked, full_in_range);
+    }
+
+    #[test]
+    fn down_pattern_steps_descending_through_the_chord() {
+        let mut arp = Arpeggiator::new();
+        let plugin = PluginInstanceID::new();
+        arp.set_settings(plugin, ArpSettings::new(ArpPattern::Down, 1, 0.25));
+        let events = arp.events_for_block(plugin, &tempo_map(), &[60, 64, 67], 0, 24_000);
+        let note_ons: Vec<i32> = events.iter().filter(|e| e.velocity > 0.0).map(|e| e.note_id).collect();
+        assert_eq!(note_ons, vec![67, 64, 60, 67]);
+    }
+
+    #[test]
+    fn octave_range_layers_the_chord_upward() {
+        let mut arp = Arpeggiator::new();
+        let plugin = PluginInstanceID::new();
+        arp.set_settings(plugin, ArpSettings::new(ArpPattern::Up, 2, 0.25));
+        let events = arp.events_for_block(plugin, &tempo_map(), &[60], 0, 24_000);
+        let note_ons: Vec<i32> = events.iter().filter(|e| e.velocity > 0.0).map(|e| e.note_id).collect();
+        assert_eq!(note_ons, vec![60, 72, 60, 72]);
+    }
+
+    #[test]
+    fn the_same_seed_produces_the_same_random_sequence() {
+        let mut a = Arpeggiator::new();
+        let mut b = Arpeggiator::new();
+        let plugin = PluginInstanceID::new();
+        let mut settings = ArpSettings::new(ArpPattern::Random, 1, 0.25);
+        settings.seed = 99;
+        a.set_settings(plugin, settings);
+        b.set_settings(plugin, settings);
+        let held = [60, 64, 67, 71];
+
+        assert_eq!(
+            a.events_for_block(plugin, &tempo_map(), &held, 0, 48_000),
+            b.events_for_block(plugin, &tempo_map(), &held, 0, 48_000)
+        );
+    }
+}
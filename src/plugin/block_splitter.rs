@@ -0,0 +1,132 @@
+//! Splitting the engine's block into smaller sub-blocks for plugins that
+//! declare a maximum block size they'll accept.
+//!
+//! Some plugins only behave correctly (or perform best) when processed in
+//! small chunks, independent of the block size the rest of the graph runs
+//! at. Rather than forcing the whole graph down to the smallest common
+//! denominator, the plugin host processor for an affected plugin splits its
+//! share of the block into sub-blocks internally, re-basing events and the
+//! sub-block's own transport-relative start offset the same way a loop
+//! boundary split does.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::plugin::event_quantizer::NoteEvent;
+
+/// Per-plugin overrides for the largest block size (in frames) a plugin
+/// host processor will ever hand that plugin in one `process` call.
+/// Plugins with no override run at the engine's full block size.
+#[derive(Debug, Default)]
+pub struct PluginBlockLimits {
+    limits: HashMap<PluginInstanceID, u32>,
+}
+
+impl PluginBlockLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides `plugin`'s maximum block size. `0` is rejected (a plugin
+    /// can't process zero frames) and treated as no override.
+    pub fn set_max_block_size(&mut self, plugin: PluginInstanceID, max_frames: u32) {
+        if max_frames == 0 {
+            self.limits.remove(&plugin);
+            return;
+        }
+        self.limits.insert(plugin, max_frames);
+    }
+
+    pub fn clear(&mut self, plugin: PluginInstanceID) {
+        self.limits.remove(&plugin);
+    }
+
+    pub fn max_block_size(&self, plugin: PluginInstanceID) -> Option<u32> {
+        self.limits.get(&plugin).copied()
+    }
+}
+
+/// One sub-block of a larger engine block, re-based to start at sample `0`
+/// the way a plugin host processor expects each `process` call to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubBlock {
+    /// This sub-block's start offset within the original, unsplit block.
+    pub start_offset: u32,
+    pub frames: u32,
+    pub events: Vec<NoteEvent>,
+}
+
+/// Splits a `block_frames`-frame block into consecutive sub-blocks of at
+/// most `max_frames` each, re-basing `events`' `sample_offset` to the start
+/// of whichever sub-block they fall in. `max_frames == 0` or
+/// `max_frames >= block_frames` returns the whole block as a single
+/// unsplit sub-block.
+pub fn split_block(block_frames: u32, max_frames: u32, events: &[NoteEvent]) -> Vec<SubBlock> {
+    if max_frames == 0 || max_frames >= block_frames {
+        return vec![SubBlock { start_offset: 0, frames: block_frames, events: events.to_vec() }];
+    }
+
+    let mut sub_blocks = Vec::new();
+    let mut start = 0u32;
+    while start < block_frames {
+        let frames = (block_frames - start).min(max_frames);
+        sub_blocks.push(SubBlock { start_offset: start, frames, events: Vec::new() });
+        start += frames;
+    }
+
+    let boundaries: Vec<u32> = sub_blocks.iter().skip(1).map(|sub_block| sub_block.start_offset).collect();
+    for &event in events {
+        let segment = boundaries.partition_point(|&boundary| boundary <= event.sample_offset);
+        let segment_start = sub_blocks[segment].start_offset;
+        sub_blocks[segment].events.push(NoteEvent { sample_offset: event.sample_offset - segment_start, ..event });
+    }
+    sub_blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(sample_offset: u32) -> NoteEvent {
+        NoteEvent { sample_offset, note_id: 60, velocity: 1.0 }
+    }
+
+    #[test]
+    fn no_override_runs_as_a_single_sub_block() {
+        let found = split_block(512, 0, &[note(10)]);
+        assert_eq!(found, vec![SubBlock { start_offset: 0, frames: 512, events: vec![note(10)] }]);
+    }
+
+    #[test]
+    fn a_limit_at_or_above_the_block_size_is_a_no_op() {
+        let found = split_block(512, 512, &[]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].frames, 512);
+    }
+
+    #[test]
+    fn splits_an_evenly_divisible_block_into_equal_sub_blocks() {
+        let found = split_block(512, 128, &[]);
+        assert_eq!(found.len(), 4);
+        for (i, sub_block) in found.iter().enumerate() {
+            assert_eq!(sub_block.start_offset, i as u32 * 128);
+            assert_eq!(sub_block.frames, 128);
+        }
+    }
+
+    #[test]
+    fn a_remainder_produces_a_shorter_final_sub_block() {
+        let found = split_block(300, 128, &[]);
+        assert_eq!(found.iter().map(|sub_block| sub_block.frames).collect::<Vec<_>>(), vec![128, 128, 44]);
+    }
+
+    #[test]
+    fn events_are_rebased_to_the_start_of_their_sub_block() {
+        let events = [note(10), note(128), note(200), note(400)];
+        let found = split_block(512, 128, &events);
+        assert_eq!(found[0].events, vec![note(10)]);
+        assert_eq!(found[1].events, vec![note(0), note(72)]);
+        assert_eq!(found[2].events, vec![]);
+        assert_eq!(found[3].events, vec![note(16)]);
+    }
+}
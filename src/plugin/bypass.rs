@@ -0,0 +1,90 @@
+//! Per-plugin bypass state: routes a plugin's input straight through to its
+//! output without processing, without removing it from the graph.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+
+/// Tracks which plugins are currently bypassed, defaulting every plugin to
+/// not bypassed until set otherwise.
+#[derive(Debug, Default)]
+pub struct PluginBypassStates {
+    bypassed: HashMap<PluginInstanceID, bool>,
+}
+
+impl PluginBypassStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_bypassed(&mut self, plugin: PluginInstanceID, bypassed: bool) {
+        if bypassed {
+            self.bypassed.insert(plugin, true);
+        } else {
+            self.bypassed.remove(&plugin);
+        }
+    }
+
+    pub fn is_bypassed(&self, plugin: PluginInstanceID) -> bool {
+        self.bypassed.get(&plugin).copied().unwrap_or(false)
+    }
+
+    /// Drops a plugin's bypass state, e.g. when it is removed from the
+    /// graph.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.bypassed.remove(&plugin);
+    }
+
+    /// Every currently bypassed plugin, sorted for deterministic
+    /// comparison and persistence.
+    pub fn bypassed_plugins(&self) -> Vec<PluginInstanceID> {
+        let mut ids: Vec<PluginInstanceID> = self.bypassed.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugins_default_to_not_bypassed() {
+        let states = PluginBypassStates::new();
+        assert!(!states.is_bypassed(PluginInstanceID::new()));
+    }
+
+    #[test]
+    fn setting_bypass_on_then_off_round_trips() {
+        let mut states = PluginBypassStates::new();
+        let plugin = PluginInstanceID::new();
+        states.set_bypassed(plugin, true);
+        assert!(states.is_bypassed(plugin));
+
+        states.set_bypassed(plugin, false);
+        assert!(!states.is_bypassed(plugin));
+    }
+
+    #[test]
+    fn bypassed_plugins_lists_only_currently_bypassed_plugins_sorted() {
+        let mut states = PluginBypassStates::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        states.set_bypassed(b, true);
+        states.set_bypassed(a, true);
+
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(states.bypassed_plugins(), expected);
+    }
+
+    #[test]
+    fn removing_a_plugin_clears_its_bypass_state() {
+        let mut states = PluginBypassStates::new();
+        let plugin = PluginInstanceID::new();
+        states.set_bypassed(plugin, true);
+        states.remove_plugin(plugin);
+        assert!(!states.is_bypassed(plugin));
+        assert!(states.bypassed_plugins().is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+//! Host-managed dry/wet mix and bypass wrapped around every plugin host
+//! processor.
+//!
+//! Bypass is implemented as a mix change rather than a separate code path:
+//! bypassing a plugin ramps its mix down to fully dry, and un-bypassing
+//! ramps it back to whatever mix the host had dialed in, so toggling bypass
+//! declicks the same way any other mix change does.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+
+/// Linearly ramps toward a target over a fixed number of samples, so a mix
+/// or bypass change doesn't click.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MixRamp {
+    current: f32,
+    target: f32,
+    ramp_samples: u32,
+    step: f32,
+}
+
+impl MixRamp {
+    fn new(initial: f32, ramp_samples: u32) -> Self {
+        Self { current: initial, target: initial, ramp_samples: ramp_samples.max(1), step: 0.0 }
+    }
+
+    fn set_target(&mut self, target: f32) {
+        self.target = target;
+        self.step = (self.target - self.current) / self.ramp_samples as f32;
+    }
+
+    fn next(&mut self) -> f32 {
+        if (self.target - self.current).abs() <= self.step.abs().max(f32::EPSILON) {
+            self.current = self.target;
+        } else {
+            self.current += self.step;
+        }
+        self.current
+    }
+}
+
+/// Host-owned dry/wet mix and bypass for one plugin.
+#[derive(Debug, Clone)]
+pub struct PluginDryWetStage {
+    mix: MixRamp,
+    dry_wet: f32,
+    bypassed: bool,
+}
+
+impl PluginDryWetStage {
+    pub fn new(ramp_samples: u32) -> Self {
+        Self { mix: MixRamp::new(1.0, ramp_samples), dry_wet: 1.0, bypassed: false }
+    }
+
+    /// Sets the dry/wet mix (`0.0` fully dry, `1.0` fully wet), ramped over
+    /// this stage's configured length. Has no audible effect until the
+    /// plugin is un-bypassed.
+    pub fn set_dry_wet(&mut self, mix: f32) {
+        self.dry_wet = mix.clamp(0.0, 1.0);
+        if !self.bypassed {
+            self.mix.set_target(self.dry_wet);
+        }
+    }
+
+    /// Bypasses (or un-bypasses) the plugin, ramping toward fully dry (or
+    /// back to the last dry/wet mix) over this stage's configured length
+    /// instead of cutting over instantly.
+    pub fn set_bypassed(&mut self, bypassed: bool) {
+        self.bypassed = bypassed;
+        self.mix.set_target(if bypassed { 0.0 } else { self.dry_wet });
+    }
+
+    pub fn dry_wet(&self) -> f32 {
+        self.dry_wet
+    }
+
+    pub fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+
+    /// Blends `dry` (the plugin's input) and `wet` (its processed output,
+    /// of the same length) in place into `wet`, crossfading toward `dry`
+    /// while bypassed or mixed down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dry` and `wet` aren't the same length.
+    pub fn process(&mut self, dry: &[f32], wet: &mut [f32]) {
+        assert_eq!(dry.len(), wet.len(), "dry and wet blocks must be the same length");
+        for (dry_sample, wet_sample) in dry.iter().zip(wet.iter_mut()) {
+            let mix = self.mix.next();
+            *wet_sample = dry_sample * (1.0 - mix) + *wet_sample * mix;
+        }
+    }
+}
+
+/// Default crossfade length for dry/wet and bypass changes (~3ms at
+/// 44.1kHz): short enough to feel instant, long enough to avoid a click.
+pub const DEFAULT_DRY_WET_RAMP_SAMPLES: u32 = 128;
+
+/// Per-plugin dry/wet mix and bypass stages, created fully wet and not
+/// bypassed on first touch.
+#[derive(Debug)]
+pub struct PluginDryWetStages {
+    ramp_samples: u32,
+    stages: HashMap<PluginInstanceID, PluginDryWetStage>,
+}
+
+impl Default for PluginDryWetStages {
+    fn default() -> Self {
+        Self::new(DEFAULT_DRY_WET_RAMP_SAMPLES)
+    }
+}
+
+impl PluginDryWetStages {
+    /// Creates a table whose mix/bypass changes ramp over `ramp_samples`
+    /// samples.
+    pub fn new(ramp_samples: u32) -> Self {
+        Self { ramp_samples, stages: HashMap::new() }
+    }
+
+    /// Rebuilds a dry/wet table from a persisted snapshot of `(plugin,
+    /// dry_wet, bypassed)` triples, e.g. when loading project save state.
+    pub fn from_entries(ramp_samples: u32, entries: impl IntoIterator<Item = (PluginInstanceID, f32, bool)>) -> Self {
+        let mut table = Self::new(ramp_samples);
+        for (plugin, dry_wet, bypassed) in entries {
+            let stage = table.stage_mut(plugin);
+            stage.set_dry_wet(dry_wet);
+            stage.set_bypassed(bypassed);
+            // Persisted mix/bypass should apply immediately on load rather
+            // than ramping in from the defaults.
+            stage.mix.current = stage.mix.target;
+        }
+        table
+    }
+
+    fn stage_mut(&mut self, plugin: PluginInstanceID) -> &mut PluginDryWetStage {
+        self.stages.entry(plugin).or_insert_with(|| PluginDryWetStage::new(self.ramp_samples))
+    }
+
+    pub fn set_dry_wet(&mut self, plugin: PluginInstanceID, mix: f32) {
+        self.stage_mut(plugin).set_dry_wet(mix);
+    }
+
+    pub fn set_bypassed(&mut self, plugin: PluginInstanceID, bypassed: bool) {
+        self.stage_mut(plugin).set_bypassed(bypassed);
+    }
+
+    pub fn dry_wet(&self, plugin: PluginInstanceID) -> f32 {
+        self.stages.get(&plugin).map(PluginDryWetStage::dry_wet).unwrap_or(1.0)
+    }
+
+    pub fn is_bypassed(&self, plugin: PluginInstanceID) -> bool {
+        self.stages.get(&plugin).map(PluginDryWetStage::is_bypassed).unwrap_or(false)
+    }
+
+    /// Blends `plugin`'s dry input into its wet output in place, per
+    /// [`PluginDryWetStage::process`].
+    pub fn process(&mut self, plugin: PluginInstanceID, dry: &[f32], wet: &mut [f32]) {
+        self.stage_mut(plugin).process(dry, wet);
+    }
+
+    /// Drops a plugin's dry/wet stage, e.g. when it is removed from the
+    /// graph.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.stages.remove(&plugin);
+    }
+
+    /// A snapshot of every non-default plugin dry/wet mix or bypass state,
+    /// for persisting into project save state.
+    pub fn entries(&self) -> Vec<(PluginInstanceID, f32, bool)> {
+        self.stages
+            .iter()
+            .filter(|(_, stage)| stage.dry_wet() != 1.0 || stage.is_bypassed())
+            .map(|(&plugin, stage)| (plugin, stage.dry_wet(), stage.is_bypassed()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_stages_default_to_fully_wet_and_not_bypassed() {
+        let stages = PluginDryWetStages::new(8);
+        let plugin = PluginInstanceID::new();
+        assert_eq!(stages.dry_wet(plugin), 1.0);
+        assert!(!stages.is_bypassed(plugin));
+    }
+
+    #[test]
+    fn bypassing_ramps_toward_fully_dry_instead_of_jumping() {
+        let mut stages = PluginDryWetStages::new(4);
+        let plugin = PluginInstanceID::new();
+        stages.set_bypassed(plugin, true);
+
+        let dry = vec![1.0_f32; 4];
+        let mut wet = vec![0.0_f32; 4];
+        stages.process(plugin, &dry, &mut wet);
+
+        // Midway through the ramp the output should be strictly between
+        // the processed (wet) and dry signal, not an instant cut to dry.
+        assert!(wet[0] > 0.0 && wet[0] < 1.0);
+        assert!((wet[3] - 1.0).abs() < 1e-4, "should have fully crossed over to dry by the end of the ramp");
+    }
+
+    #[test]
+    fn un_bypassing_restores_the_last_dry_wet_mix() {
+        let mut stages = PluginDryWetStages::new(1);
+        let plugin = PluginInstanceID::new();
+        stages.set_dry_wet(plugin, 0.5);
+        stages.set_bypassed(plugin, true);
+        stages.set_bypassed(plugin, false);
+
+        let dry = vec![0.0_f32; 1];
+        let mut wet = vec![1.0_f32; 1];
+        stages.process(plugin, &dry, &mut wet);
+        assert!((wet[0] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn setting_dry_wet_while_bypassed_only_takes_effect_after_un_bypassing() {
+        let mut stages = PluginDryWetStages::new(1);
+        let plugin = PluginInstanceID::new();
+        stages.set_bypassed(plugin, true);
+        stages.set_dry_wet(plugin, 0.25);
+
+        let dry = vec![0.0_f32; 1];
+        let mut wet = vec![1.0_f32; 1];
+        stages.process(plugin, &dry, &mut wet);
+        assert_eq!(wet[0], 0.0, "still bypassed: dry/wet change shouldn't take effect yet");
+
+        stages.set_bypassed(plugin, false);
+        let mut wet = vec![1.0_f32; 1];
+        stages.process(plugin, &dry, &mut wet);
+        assert!((wet[0] - 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn dry_wet_is_clamped_to_unit_range() {
+        let mut stages = PluginDryWetStages::new(1);
+        let plugin = PluginInstanceID::new();
+        stages.set_dry_wet(plugin, 5.0);
+        assert_eq!(stages.dry_wet(plugin), 1.0);
+        stages.set_dry_wet(plugin, -5.0);
+        assert_eq!(stages.dry_wet(plugin), 0.0);
+    }
+
+    #[test]
+    fn entries_round_trip_persisted_mix_and_bypass_without_reramping() {
+        let mut stages = PluginDryWetStages::new(100);
+        let plugin = PluginInstanceID::new();
+        stages.set_dry_wet(plugin, 0.3);
+        // Let the ramp settle so the persisted value matches the target.
+        let dry = vec![0.0_f32; 200];
+        let mut wet = vec![0.0_f32; 200];
+        stages.process(plugin, &dry, &mut wet);
+
+        let entries = stages.entries();
+        assert_eq!(entries, vec![(plugin, 0.3, false)]);
+
+        let restored = PluginDryWetStages::from_entries(100, entries);
+        let dry = vec![0.0_f32; 1];
+        let mut wet = vec![1.0_f32; 1];
+        let mut restored = restored;
+        restored.process(plugin, &dry, &mut wet);
+        assert!((wet[0] - 0.3).abs() < 1e-4, "restored mix should apply immediately, not ramp from unity");
+    }
+
+    #[test]
+    fn unconfigured_plugins_are_omitted_from_entries() {
+        let stages = PluginDryWetStages::new(8);
+        assert!(stages.entries().is_empty());
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_dry_wet_stage() {
+        let mut stages = PluginDryWetStages::new(8);
+        let plugin = PluginInstanceID::new();
+        stages.set_dry_wet(plugin, 0.5);
+        stages.remove_plugin(plugin);
+        assert_eq!(stages.dry_wet(plugin), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn mismatched_block_lengths_panic() {
+        let mut stage = PluginDryWetStage::new(1);
+        let dry = [0.0_f32; 4];
+        let mut wet = [0.0_f32; 2];
+        stage.process(&dry, &mut wet);
+    }
+}
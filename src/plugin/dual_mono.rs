@@ -0,0 +1,168 @@
+//! Automatically wrapping a mono-only plugin in a stereo path as a ganged
+//! pair of instances.
+//!
+//! Standard DAW convenience: inserting a mono-only plugin into a stereo
+//! signal path spins up two real instances (one per channel) under the
+//! hood, but the host should present them to the rest of the UI and
+//! project file as a single logical plugin — one entry in the plugin
+//! list, one set of parameter controls, one save state. [`DualMonoWrapper`]
+//! tracks which logical IDs are actually ganged pairs, mirrors parameter
+//! changes to both underlying instances, and combines/splits their save
+//! states.
+
+use std::collections::HashMap;
+
+use crate::id::{ParamID, PluginInstanceID};
+use crate::plugin::param_cookie::EventParamValue;
+use crate::plugin::state::DSPluginSaveState;
+
+/// The two real plugin instances standing in for one logical mono-only
+/// plugin in a stereo path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DualMonoPair {
+    pub left: PluginInstanceID,
+    pub right: PluginInstanceID,
+}
+
+/// Tracks ganged left/right instance pairs, keyed by the logical
+/// [`PluginInstanceID`] the rest of the host treats as a single plugin.
+#[derive(Debug, Default)]
+pub struct DualMonoWrapper {
+    pairs: HashMap<PluginInstanceID, DualMonoPair>,
+}
+
+impl DualMonoWrapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `left` and `right` as the underlying instances of a new
+    /// logical dual-mono plugin, returning the logical ID the host should
+    /// show in place of either real instance.
+    pub fn wrap(&mut self, left: PluginInstanceID, right: PluginInstanceID) -> PluginInstanceID {
+        let logical_id = PluginInstanceID::new();
+        self.pairs.insert(logical_id, DualMonoPair { left, right });
+        logical_id
+    }
+
+    /// The underlying instance pair for a logical ID, if it's a dual-mono
+    /// wrapper.
+    pub fn pair(&self, logical_id: PluginInstanceID) -> Option<DualMonoPair> {
+        self.pairs.get(&logical_id).copied()
+    }
+
+    pub fn is_wrapped(&self, logical_id: PluginInstanceID) -> bool {
+        self.pairs.contains_key(&logical_id)
+    }
+
+    /// Unwraps a logical plugin, e.g. when it's removed from the graph.
+    pub fn unwrap(&mut self, logical_id: PluginInstanceID) -> Option<DualMonoPair> {
+        self.pairs.remove(&logical_id)
+    }
+
+    /// Gangs a parameter-value event addressed to `logical_id` into one
+    /// event per underlying instance, so a single host-side control moves
+    /// both channels together. Returns `None` if `logical_id` isn't a
+    /// dual-mono wrapper.
+    pub fn gang_param_event(
+        &self,
+        logical_id: PluginInstanceID,
+        param_id: ParamID,
+        value: f64,
+        cookies: &crate::plugin::param_cookie::ParamCookieCache,
+    ) -> Option<[(PluginInstanceID, EventParamValue); 2]> {
+        let pair = self.pair(logical_id)?;
+        Some([
+            (pair.left, cookies.build_event(pair.left, param_id, value)),
+            (pair.right, cookies.build_event(pair.right, param_id, value)),
+        ])
+    }
+
+    /// Combines the left and right instances' save states into one blob
+    /// for the logical plugin's project-file entry.
+    pub fn combine_save_state(left: &DSPluginSaveState, right: &DSPluginSaveState) -> DSPluginSaveState {
+        let mut data = Vec::with_capacity(4 + left.data.len() + right.data.len());
+        data.extend_from_slice(&(left.data.len() as u32).to_le_bytes());
+        data.extend_from_slice(&left.data);
+        data.extend_from_slice(&right.data);
+        DSPluginSaveState::new(data)
+    }
+
+    /// Splits a combined save state back into its left and right halves,
+    /// e.g. when re-instantiating a dual-mono pair from a loaded project.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `combined` wasn't produced by [`combine_save_state`] (too
+    /// short to contain a length prefix, or the prefix overruns the data).
+    ///
+    /// [`combine_save_state`]: DualMonoWrapper::combine_save_state
+    pub fn split_save_state(combined: &DSPluginSaveState) -> (DSPluginSaveState, DSPluginSaveState) {
+        assert!(combined.data.len() >= 4, "combined dual-mono save state is missing its length prefix");
+        let left_len = u32::from_le_bytes(combined.data[0..4].try_into().unwrap()) as usize;
+        let rest = &combined.data[4..];
+        assert!(left_len <= rest.len(), "combined dual-mono save state's length prefix overruns its data");
+        let (left, right) = rest.split_at(left_len);
+        (DSPluginSaveState::new(left.to_vec()), DSPluginSaveState::new(right.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::param_cookie::ParamCookieCache;
+
+    #[test]
+    fn wrapping_two_instances_returns_a_new_logical_id_distinct_from_either() {
+        let mut wrapper = DualMonoWrapper::new();
+        let left = PluginInstanceID::new();
+        let right = PluginInstanceID::new();
+        let logical = wrapper.wrap(left, right);
+
+        assert_ne!(logical, left);
+        assert_ne!(logical, right);
+        assert_eq!(wrapper.pair(logical), Some(DualMonoPair { left, right }));
+        assert!(wrapper.is_wrapped(logical));
+    }
+
+    #[test]
+    fn an_unwrapped_id_has_no_pair() {
+        let wrapper = DualMonoWrapper::new();
+        assert_eq!(wrapper.pair(PluginInstanceID::new()), None);
+    }
+
+    #[test]
+    fn ganging_a_param_event_mirrors_it_to_both_instances() {
+        let mut wrapper = DualMonoWrapper::new();
+        let left = PluginInstanceID::new();
+        let right = PluginInstanceID::new();
+        let logical = wrapper.wrap(left, right);
+        let cookies = ParamCookieCache::new();
+
+        let events = wrapper.gang_param_event(logical, ParamID(0), 0.75, &cookies).unwrap();
+        assert_eq!(events[0].0, left);
+        assert_eq!(events[1].0, right);
+        assert_eq!(events[0].1.value, 0.75);
+        assert_eq!(events[1].1.value, 0.75);
+    }
+
+    #[test]
+    fn combining_and_splitting_a_save_state_round_trips() {
+        let left = DSPluginSaveState::new(vec![1, 2, 3]);
+        let right = DSPluginSaveState::new(vec![4, 5]);
+
+        let combined = DualMonoWrapper::combine_save_state(&left, &right);
+        let (split_left, split_right) = DualMonoWrapper::split_save_state(&combined);
+
+        assert_eq!(split_left.data, vec![1, 2, 3]);
+        assert_eq!(split_right.data, vec![4, 5]);
+    }
+
+    #[test]
+    fn unwrapping_removes_the_pair() {
+        let mut wrapper = DualMonoWrapper::new();
+        let logical = wrapper.wrap(PluginInstanceID::new(), PluginInstanceID::new());
+        assert!(wrapper.unwrap(logical).is_some());
+        assert!(!wrapper.is_wrapped(logical));
+    }
+}
@@ -0,0 +1,129 @@
+//! Deterministic merging of note events from several sources into the one
+//! stream a plugin's `process` call receives.
+//!
+//! A plugin can receive events from automation, live MIDI input, events the
+//! host scheduled ahead of time, and transport-driven events (loop/seek) in
+//! the same block. Without an explicit merge policy, their relative order
+//! at a shared `sample_offset` depends on whatever order the caller happened
+//! to hand the sources in, which can change across runs if that order is
+//! itself sourced from a `HashMap`. [`merge_events`] always sorts by
+//! `sample_offset` first and a fixed, configurable source precedence second,
+//! so the same inputs produce byte-identical output every time.
+
+use crate::plugin::event_quantizer::NoteEvent;
+
+/// Where a note event being merged came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventSource {
+    Transport,
+    HostScheduled,
+    Automation,
+    LiveMidi,
+}
+
+/// The tie-break order applied when two events from different sources land
+/// on the same `sample_offset`. Earlier entries in [`order`](Self::order)
+/// sort first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventMergePolicy {
+    order: Vec<EventSource>,
+}
+
+impl EventMergePolicy {
+    /// `order` lists every source from highest to lowest tie-break
+    /// priority. A source not listed sorts after every listed one.
+    pub fn new(order: Vec<EventSource>) -> Self {
+        Self { order }
+    }
+
+    /// Transport events (loop/seek) first, since they redefine the block's
+    /// timeline; then host-scheduled events, which were committed ahead of
+    /// time; then automation; then live MIDI last, since it's the least
+    /// predictable input and shouldn't jump ahead of anything already
+    /// queued for the same sample.
+    pub fn default_order() -> Self {
+        Self::new(vec![EventSource::Transport, EventSource::HostScheduled, EventSource::Automation, EventSource::LiveMidi])
+    }
+
+    fn priority(&self, source: EventSource) -> usize {
+        self.order.iter().position(|&s| s == source).unwrap_or(self.order.len())
+    }
+}
+
+/// One event tagged with the source it was merged from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourcedEvent {
+    pub source: EventSource,
+    pub event: NoteEvent,
+}
+
+/// Merges `sources` into one stream ordered by `sample_offset`, breaking
+/// ties by `policy`'s source precedence and, within the same source and
+/// offset, the order events were given in. Deterministic regardless of the
+/// order `sources` itself is passed in.
+pub fn merge_events(policy: &EventMergePolicy, sources: &[(EventSource, Vec<NoteEvent>)]) -> Vec<SourcedEvent> {
+    let mut merged: Vec<SourcedEvent> = sources
+        .iter()
+        .flat_map(|(source, events)| events.iter().map(move |&event| SourcedEvent { source: *source, event }))
+        .collect();
+
+    merged.sort_by_key(|sourced| (sourced.event.sample_offset, policy.priority(sourced.source)));
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(sample_offset: u32, note_id: i32) -> NoteEvent {
+        NoteEvent { sample_offset, note_id, velocity: 1.0 }
+    }
+
+    #[test]
+    fn events_are_ordered_by_sample_offset_first() {
+        let policy = EventMergePolicy::default_order();
+        let sources = vec![
+            (EventSource::LiveMidi, vec![note(100, 1)]),
+            (EventSource::Automation, vec![note(0, 2)]),
+        ];
+        let merged = merge_events(&policy, &sources);
+        assert_eq!(merged.iter().map(|s| s.event.note_id).collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn ties_at_the_same_offset_break_by_source_precedence() {
+        let policy = EventMergePolicy::default_order();
+        let sources = vec![
+            (EventSource::LiveMidi, vec![note(0, 1)]),
+            (EventSource::Transport, vec![note(0, 2)]),
+            (EventSource::Automation, vec![note(0, 3)]),
+        ];
+        let merged = merge_events(&policy, &sources);
+        // Transport sorts before Automation sorts before LiveMidi at the same offset.
+        assert_eq!(merged.iter().map(|s| s.event.note_id).collect::<Vec<_>>(), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn the_result_is_identical_regardless_of_input_source_order() {
+        let policy = EventMergePolicy::default_order();
+        let a = vec![(EventSource::LiveMidi, vec![note(5, 1)]), (EventSource::Transport, vec![note(5, 2)])];
+        let b = vec![(EventSource::Transport, vec![note(5, 2)]), (EventSource::LiveMidi, vec![note(5, 1)])];
+        assert_eq!(merge_events(&policy, &a), merge_events(&policy, &b));
+    }
+
+    #[test]
+    fn a_custom_policy_overrides_the_default_precedence() {
+        let policy = EventMergePolicy::new(vec![EventSource::LiveMidi, EventSource::Transport]);
+        let sources = vec![(EventSource::Transport, vec![note(0, 1)]), (EventSource::LiveMidi, vec![note(0, 2)])];
+        let merged = merge_events(&policy, &sources);
+        assert_eq!(merged.iter().map(|s| s.event.note_id).collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn events_within_the_same_source_and_offset_keep_their_relative_order() {
+        let policy = EventMergePolicy::default_order();
+        let sources = vec![(EventSource::LiveMidi, vec![note(0, 1), note(0, 2), note(0, 3)])];
+        let merged = merge_events(&policy, &sources);
+        assert_eq!(merged.iter().map(|s| s.event.note_id).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Plugin GUI embedding and preview capture.
+
+/// Which windowing API a plugin's GUI is hosted through. This crate doesn't
+/// implement either one itself (that's toolkit/platform-specific, done by
+/// the host's GUI wrapper); it just needs to name the two CLAP/VST3 offer so
+/// [`create_gui_with_fallback`] can report which one actually worked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuiEmbeddingApi {
+    /// The GUI is embedded directly into a host-owned window (CLAP's
+    /// `gui.set_parent`), the common case.
+    Embedded,
+    /// The GUI runs in its own top-level window (CLAP's `gui.set_transient`
+    /// / floating mode), used as a fallback when embedding fails (e.g. an
+    /// X11 plugin under an XWayland quirk).
+    Floating,
+}
+
+impl GuiEmbeddingApi {
+    fn fallback(self) -> Self {
+        match self {
+            GuiEmbeddingApi::Embedded => GuiEmbeddingApi::Floating,
+            GuiEmbeddingApi::Floating => GuiEmbeddingApi::Embedded,
+        }
+    }
+}
+
+/// Tries to create a plugin's GUI via `preferred`, and if that fails, tries
+/// once more via the other API instead of surfacing a hard failure to the
+/// host. `create` is the host's GUI wrapper's actual window-creation call
+/// for the given API; this just sequences the retry and reports which API
+/// ended up succeeding, so the host can remember the working choice for
+/// next time.
+///
+/// Returns the first error if both attempts fail.
+pub fn create_gui_with_fallback<T, E>(
+    preferred: GuiEmbeddingApi,
+    mut create: impl FnMut(GuiEmbeddingApi) -> Result<T, E>,
+) -> Result<(T, GuiEmbeddingApi), E> {
+    match create(preferred) {
+        Ok(gui) => Ok((gui, preferred)),
+        Err(first_err) => {
+            let fallback = preferred.fallback();
+            create(fallback).map(|gui| (gui, fallback)).map_err(|_| first_err)
+        }
+    }
+}
+
+/// An RGBA8 snapshot of a plugin's GUI, rendered off-screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuiPreviewImage {
+    pub width: u32,
+    pub height: u32,
+    /// Tightly-packed RGBA8 pixel data, `width * height * 4` bytes.
+    pub rgba: Vec<u8>,
+}
+
+/// Why a preview capture request could not be fulfilled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewCaptureError {
+    /// This plugin's GUI implementation has no way to render off-screen
+    /// (most CLAP GUIs don't support this).
+    Unsupported,
+    /// The plugin has no GUI open to capture.
+    NoGuiOpen,
+    /// The plugin returned an error while rendering.
+    PluginError(String),
+}
+
+/// A hook a host-side GUI wrapper can implement to let the host request a
+/// rendered preview of a plugin's GUI (e.g. for a mixer strip thumbnail or a
+/// plugin browser listing) without opening the full embedded window.
+pub trait PluginGuiPreview {
+    /// Whether this plugin's GUI can currently produce a preview capture.
+    fn supports_preview_capture(&self) -> bool {
+        false
+    }
+
+    /// Renders the plugin's GUI off-screen, scaled to fit within
+    /// `max_width` x `max_height` while preserving aspect ratio.
+    ///
+    /// The default implementation always reports [`PreviewCaptureError::Unsupported`];
+    /// plugin GUI wrappers that can render off-screen should override this.
+    fn capture_preview(
+        &self,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<GuiPreviewImage, PreviewCaptureError> {
+        let _ = (max_width, max_height);
+        Err(PreviewCaptureError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoPreviewGui;
+    impl PluginGuiPreview for NoPreviewGui {}
+
+    struct SolidColorGui {
+        color: [u8; 4],
+    }
+    impl PluginGuiPreview for SolidColorGui {
+        fn supports_preview_capture(&self) -> bool {
+            true
+        }
+
+        fn capture_preview(
+            &self,
+            max_width: u32,
+            max_height: u32,
+        ) -> Result<GuiPreviewImage, PreviewCaptureError> {
+            let mut rgba = Vec::with_capacity((max_width * max_height * 4) as usize);
+            for _ in 0..(max_width * max_height) {
+                rgba.extend_from_slice(&self.color);
+            }
+            Ok(GuiPreviewImage { width: max_width, height: max_height, rgba })
+        }
+    }
+
+    #[test]
+    fn default_implementation_reports_unsupported() {
+        let gui = NoPreviewGui;
+        assert!(!gui.supports_preview_capture());
+        assert_eq!(gui.capture_preview(64, 64), Err(PreviewCaptureError::Unsupported));
+    }
+
+    #[test]
+    fn succeeds_on_the_preferred_api_without_touching_the_fallback() {
+        let result = create_gui_with_fallback(GuiEmbeddingApi::Embedded, |api| -> Result<&str, &str> {
+            assert_eq!(api, GuiEmbeddingApi::Embedded);
+            Ok("window")
+        });
+        assert_eq!(result, Ok(("window", GuiEmbeddingApi::Embedded)));
+    }
+
+    #[test]
+    fn falls_back_to_the_other_api_when_the_preferred_one_fails() {
+        let result = create_gui_with_fallback(GuiEmbeddingApi::Embedded, |api| match api {
+            GuiEmbeddingApi::Embedded => Err("embedding failed"),
+            GuiEmbeddingApi::Floating => Ok("floating window"),
+        });
+        assert_eq!(result, Ok(("floating window", GuiEmbeddingApi::Floating)));
+    }
+
+    #[test]
+    fn reports_the_first_error_when_both_apis_fail() {
+        let result: Result<(&str, _), _> = create_gui_with_fallback(GuiEmbeddingApi::Floating, |api| match api {
+            GuiEmbeddingApi::Floating => Err("floating failed"),
+            GuiEmbeddingApi::Embedded => Err("embedding also failed"),
+        });
+        assert_eq!(result, Err("floating failed"));
+    }
+
+    #[test]
+    fn overridden_implementation_produces_an_image() {
+        let gui = SolidColorGui { color: [255, 0, 0, 255] };
+        let image = gui.capture_preview(4, 4).unwrap();
+        assert_eq!(image.width, 4);
+        assert_eq!(image.height, 4);
+        assert_eq!(image.rgba.len(), 4 * 4 * 4);
+    }
+}
@@ -0,0 +1,157 @@
+//! Tracks which notes are currently held on each plugin's note input, for
+//! piano-roll ghosting (showing a plugin's live input on its track) and
+//! accurate "panic only this plugin" note-off generation.
+//!
+//! Updated from the audio thread as note-on/note-off events are sent to a
+//! plugin, and mirrored to the main thread for the host UI to read. The set
+//! itself is small (a handful of concurrently-held notes at most), so it's
+//! copied wholesale through a `Mutex` rather than given a lock-free
+//! structure of its own — the same tradeoff [`MessageQueue`](crate::engine::message_queue::MessageQueue)
+//! makes for its "brief lock, no blocking work under it" realtime-safety bar.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::plugin::note_dialect::NotePortKey;
+
+/// One held note: a MIDI key number and channel, the pair CLAP and raw MIDI
+/// both identify a note by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeldNote {
+    pub key: i16,
+    pub channel: i16,
+}
+
+/// The audio thread's handle to one note port's held-note set. Call
+/// [`note_on`](Self::note_on) / [`note_off`](Self::note_off) as events are
+/// sent to the plugin.
+#[derive(Debug, Default)]
+pub struct HeldNotesHandle {
+    held: Mutex<HashSet<HeldNote>>,
+}
+
+impl HeldNotesHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn note_on(&self, note: HeldNote) {
+        self.held.lock().unwrap().insert(note);
+    }
+
+    pub fn note_off(&self, note: HeldNote) {
+        self.held.lock().unwrap().remove(&note);
+    }
+
+    /// Releases every held note, e.g. on a transport stop or plugin panic.
+    pub fn release_all(&self) {
+        self.held.lock().unwrap().clear();
+    }
+
+    /// A snapshot of the notes currently held, for the main thread to
+    /// display.
+    pub fn snapshot(&self) -> Vec<HeldNote> {
+        self.held.lock().unwrap().iter().copied().collect()
+    }
+}
+
+/// Per-note-port [`HeldNotesHandle`]s, created on first touch.
+#[derive(Debug, Default)]
+pub struct HeldNotesTable {
+    handles: HashMap<NotePortKey, Arc<HeldNotesHandle>>,
+}
+
+impl HeldNotesTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the handle for `port`, creating one on first touch. Give a
+    /// clone of this to the audio thread's note event sender for that port.
+    pub fn handle(&mut self, port: NotePortKey) -> Arc<HeldNotesHandle> {
+        self.handles.entry(port).or_insert_with(|| Arc::new(HeldNotesHandle::new())).clone()
+    }
+
+    /// The notes currently held on `port`, or an empty list if it has no
+    /// handle yet.
+    pub fn held_notes(&self, port: NotePortKey) -> Vec<HeldNote> {
+        self.handles.get(&port).map(|handle| handle.snapshot()).unwrap_or_default()
+    }
+
+    /// Releases every held note on every port belonging to `plugin`, the
+    /// "panic only this plugin" case.
+    pub fn release_all_for_plugin(&self, plugin: crate::id::PluginInstanceID) {
+        for (port, handle) in &self.handles {
+            if port.plugin == plugin {
+                handle.release_all();
+            }
+        }
+    }
+
+    pub fn remove_port(&mut self, port: NotePortKey) {
+        self.handles.remove(&port);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::PluginInstanceID;
+
+    fn port() -> NotePortKey {
+        NotePortKey { plugin: PluginInstanceID::new(), port_index: 0 }
+    }
+
+    #[test]
+    fn a_note_on_then_off_leaves_nothing_held() {
+        let handle = HeldNotesHandle::new();
+        let note = HeldNote { key: 60, channel: 0 };
+        handle.note_on(note);
+        assert_eq!(handle.snapshot(), vec![note]);
+        handle.note_off(note);
+        assert!(handle.snapshot().is_empty());
+    }
+
+    #[test]
+    fn distinct_notes_are_tracked_independently() {
+        let handle = HeldNotesHandle::new();
+        handle.note_on(HeldNote { key: 60, channel: 0 });
+        handle.note_on(HeldNote { key: 64, channel: 0 });
+        handle.note_off(HeldNote { key: 60, channel: 0 });
+        assert_eq!(handle.snapshot(), vec![HeldNote { key: 64, channel: 0 }]);
+    }
+
+    #[test]
+    fn release_all_clears_every_held_note() {
+        let handle = HeldNotesHandle::new();
+        handle.note_on(HeldNote { key: 60, channel: 0 });
+        handle.note_on(HeldNote { key: 64, channel: 0 });
+        handle.release_all();
+        assert!(handle.snapshot().is_empty());
+    }
+
+    #[test]
+    fn the_table_creates_one_handle_per_port_on_first_touch() {
+        let mut table = HeldNotesTable::new();
+        let port = port();
+        let handle = table.handle(port);
+        handle.note_on(HeldNote { key: 60, channel: 0 });
+        assert_eq!(table.held_notes(port), vec![HeldNote { key: 60, channel: 0 }]);
+    }
+
+    #[test]
+    fn release_all_for_plugin_only_touches_that_plugins_ports() {
+        let mut table = HeldNotesTable::new();
+        let plugin_a = PluginInstanceID::new();
+        let plugin_b = PluginInstanceID::new();
+        let port_a = NotePortKey { plugin: plugin_a, port_index: 0 };
+        let port_b = NotePortKey { plugin: plugin_b, port_index: 0 };
+
+        table.handle(port_a).note_on(HeldNote { key: 60, channel: 0 });
+        table.handle(port_b).note_on(HeldNote { key: 62, channel: 0 });
+
+        table.release_all_for_plugin(plugin_a);
+        assert!(table.held_notes(port_a).is_empty());
+        assert_eq!(table.held_notes(port_b), vec![HeldNote { key: 62, channel: 0 }]);
+    }
+}
@@ -0,0 +1,148 @@
+//! Per-plugin host-side event injection, invoked once per block just
+//! before a plugin's own processing.
+//!
+//! Lets host code — an arpeggiator, a sequencer, a MIDI effect that isn't
+//! worth building as its own internal plugin — feed note events into a
+//! specific plugin's input queue sample-accurately, without the host
+//! needing to insert a node into the graph for it.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::plugin::event_quantizer::NoteEvent;
+
+/// The block context handed to a registered callback: enough to place new
+/// events at the right sample offset without the callback needing its own
+/// copy of the transport state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcInfo {
+    /// Transport sample position at the start of this block.
+    pub block_start_sample: u64,
+    pub block_frames: u32,
+    pub sample_rate: f64,
+}
+
+type EventCallback = Box<dyn FnMut(&ProcInfo, &mut Vec<NoteEvent>) + Send>;
+
+/// Registers and invokes per-plugin host event callbacks.
+#[derive(Default)]
+pub struct HostEventCallbacks {
+    callbacks: HashMap<PluginInstanceID, EventCallback>,
+}
+
+impl HostEventCallbacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `callback` to run every block, just before `plugin`
+    /// processes, with the chance to push additional events into its
+    /// input queue. Replaces any previously registered callback for the
+    /// same plugin.
+    pub fn register(
+        &mut self,
+        plugin: PluginInstanceID,
+        callback: impl FnMut(&ProcInfo, &mut Vec<NoteEvent>) + Send + 'static,
+    ) {
+        self.callbacks.insert(plugin, Box::new(callback));
+    }
+
+    pub fn unregister(&mut self, plugin: PluginInstanceID) {
+        self.callbacks.remove(&plugin);
+    }
+
+    pub fn is_registered(&self, plugin: PluginInstanceID) -> bool {
+        self.callbacks.contains_key(&plugin)
+    }
+
+    /// Runs `plugin`'s registered callback, if any, letting it append
+    /// events to `events` before the block is delivered to the plugin.
+    /// A no-op for plugins with no registered callback.
+    pub fn invoke(&mut self, plugin: PluginInstanceID, info: &ProcInfo, events: &mut Vec<NoteEvent>) {
+        if let Some(callback) = self.callbacks.get_mut(&plugin) {
+            callback(info, events);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> ProcInfo {
+        ProcInfo { block_start_sample: 0, block_frames: 512, sample_rate: 48_000.0 }
+    }
+
+    #[test]
+    fn invoking_an_unregistered_plugin_is_a_no_op() {
+        let mut callbacks = HostEventCallbacks::new();
+        let mut events = Vec::new();
+        callbacks.invoke(PluginInstanceID::new(), &info(), &mut events);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn a_registered_callback_can_push_events_into_the_queue() {
+        let mut callbacks = HostEventCallbacks::new();
+        let plugin = PluginInstanceID::new();
+        callbacks.register(plugin, |_info, events| {
+            events.push(NoteEvent { sample_offset: 0, note_id: 60, velocity: 1.0 });
+        });
+
+        let mut events = Vec::new();
+        callbacks.invoke(plugin, &info(), &mut events);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].note_id, 60);
+    }
+
+    #[test]
+    fn unregistering_stops_future_invocations() {
+        let mut callbacks = HostEventCallbacks::new();
+        let plugin = PluginInstanceID::new();
+        callbacks.register(plugin, |_info, events| {
+            events.push(NoteEvent { sample_offset: 0, note_id: 60, velocity: 1.0 });
+        });
+        callbacks.unregister(plugin);
+
+        let mut events = Vec::new();
+        callbacks.invoke(plugin, &info(), &mut events);
+        assert!(events.is_empty());
+        assert!(!callbacks.is_registered(plugin));
+    }
+
+    #[test]
+    fn each_plugin_has_an_independent_callback() {
+        let mut callbacks = HostEventCallbacks::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        callbacks.register(a, |_info, events| {
+            events.push(NoteEvent { sample_offset: 0, note_id: 1, velocity: 1.0 });
+        });
+        callbacks.register(b, |_info, events| {
+            events.push(NoteEvent { sample_offset: 0, note_id: 2, velocity: 1.0 });
+        });
+
+        let mut events_a = Vec::new();
+        callbacks.invoke(a, &info(), &mut events_a);
+        let mut events_b = Vec::new();
+        callbacks.invoke(b, &info(), &mut events_b);
+
+        assert_eq!(events_a[0].note_id, 1);
+        assert_eq!(events_b[0].note_id, 2);
+    }
+
+    #[test]
+    fn the_callback_receives_the_blocks_proc_info() {
+        let mut callbacks = HostEventCallbacks::new();
+        let plugin = PluginInstanceID::new();
+        callbacks.register(plugin, |info, events| {
+            if info.block_start_sample == 0 {
+                events.push(NoteEvent { sample_offset: 0, note_id: 60, velocity: 1.0 });
+            }
+        });
+
+        let mut events = Vec::new();
+        callbacks.invoke(plugin, &ProcInfo { block_start_sample: 512, ..info() }, &mut events);
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! A realtime-safe message channel between the host and one internal
+//! plugin instance, for host-defined POD messages that don't belong in the
+//! parameter system (e.g. a sampler's "set clip region" command).
+//!
+//! Bounded and mutex-guarded, the same way the engine's own main-to-audio
+//! message queue is: the audio thread only ever holds the lock for a quick
+//! push or pop, never while doing real work, so contention is negligible
+//! even though it isn't strictly lock-free. Unlike that queue this one is
+//! bidirectional and addressed to a single plugin instance: `M` is the
+//! host's command type flowing to the plugin's processor, `R` is the
+//! plugin's reply type flowing back to the host's main thread.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::id::PluginInstanceID;
+
+/// Returned by a `send_*` method when the channel is already at capacity,
+/// instead of blocking or dropping the message silently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull;
+
+/// A bounded, bidirectional message channel for one internal plugin
+/// instance. Construct one `Arc<PluginMessageChannel<M, R>>` per instance
+/// and give a clone of it to both the plugin's main-thread handle and its
+/// processor.
+pub struct PluginMessageChannel<M, R> {
+    plugin: PluginInstanceID,
+    capacity: usize,
+    to_plugin: Mutex<VecDeque<M>>,
+    to_host: Mutex<VecDeque<R>>,
+}
+
+impl<M, R> PluginMessageChannel<M, R> {
+    pub fn new(plugin: PluginInstanceID, capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            plugin,
+            capacity,
+            to_plugin: Mutex::new(VecDeque::new()),
+            to_host: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    pub fn plugin(&self) -> PluginInstanceID {
+        self.plugin
+    }
+
+    /// Enqueues a command for the plugin's processor to pick up on its next
+    /// call to [`receive_from_host`](Self::receive_from_host).
+    pub fn send_to_plugin(&self, message: M) -> Result<(), QueueFull> {
+        let mut queue = self.to_plugin.lock().unwrap();
+        if queue.len() >= self.capacity {
+            return Err(QueueFull);
+        }
+        queue.push_back(message);
+        Ok(())
+    }
+
+    /// Called from the plugin's processor to pop the oldest command the
+    /// host has sent since the last call, if any.
+    pub fn receive_from_host(&self) -> Option<M> {
+        self.to_plugin.lock().unwrap().pop_front()
+    }
+
+    /// Called from the plugin's processor to report something back to the
+    /// host's main thread (e.g. an acknowledgement or a status update).
+    pub fn send_to_host(&self, message: R) -> Result<(), QueueFull> {
+        let mut queue = self.to_host.lock().unwrap();
+        if queue.len() >= self.capacity {
+            return Err(QueueFull);
+        }
+        queue.push_back(message);
+        Ok(())
+    }
+
+    /// Called from the host's main thread to pop the oldest reply the
+    /// plugin has sent since the last call, if any.
+    pub fn receive_from_plugin(&self) -> Option<R> {
+        self.to_host.lock().unwrap().pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum SamplerCommand {
+        SetClipRegion { start_frame: u64, end_frame: u64 },
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum SamplerReply {
+        ClipRegionApplied,
+    }
+
+    #[test]
+    fn a_command_sent_to_the_plugin_is_received_in_fifo_order() {
+        let channel = PluginMessageChannel::<SamplerCommand, SamplerReply>::new(PluginInstanceID::new(), 4);
+        channel.send_to_plugin(SamplerCommand::SetClipRegion { start_frame: 0, end_frame: 100 }).unwrap();
+        channel.send_to_plugin(SamplerCommand::SetClipRegion { start_frame: 100, end_frame: 200 }).unwrap();
+
+        assert_eq!(channel.receive_from_host(), Some(SamplerCommand::SetClipRegion { start_frame: 0, end_frame: 100 }));
+        assert_eq!(channel.receive_from_host(), Some(SamplerCommand::SetClipRegion { start_frame: 100, end_frame: 200 }));
+        assert_eq!(channel.receive_from_host(), None);
+    }
+
+    #[test]
+    fn a_reply_sent_to_the_host_is_received_independently_of_the_command_direction() {
+        let channel = PluginMessageChannel::<SamplerCommand, SamplerReply>::new(PluginInstanceID::new(), 4);
+        channel.send_to_plugin(SamplerCommand::SetClipRegion { start_frame: 0, end_frame: 100 }).unwrap();
+        channel.send_to_host(SamplerReply::ClipRegionApplied).unwrap();
+
+        assert_eq!(channel.receive_from_plugin(), Some(SamplerReply::ClipRegionApplied));
+        assert_eq!(channel.receive_from_host(), Some(SamplerCommand::SetClipRegion { start_frame: 0, end_frame: 100 }));
+    }
+
+    #[test]
+    fn pushing_past_capacity_reports_backpressure_instead_of_panicking() {
+        let channel = PluginMessageChannel::<u32, u32>::new(PluginInstanceID::new(), 2);
+        channel.send_to_plugin(1).unwrap();
+        channel.send_to_plugin(2).unwrap();
+        assert_eq!(channel.send_to_plugin(3), Err(QueueFull));
+    }
+
+    #[test]
+    fn each_direction_has_its_own_independent_capacity() {
+        let channel = PluginMessageChannel::<u32, u32>::new(PluginInstanceID::new(), 1);
+        channel.send_to_plugin(1).unwrap();
+        assert_eq!(channel.send_to_plugin(2), Err(QueueFull));
+        // The host->plugin direction being full doesn't affect the reverse.
+        assert!(channel.send_to_host(1).is_ok());
+    }
+
+    #[test]
+    fn the_channel_remembers_which_plugin_instance_it_belongs_to() {
+        let plugin = PluginInstanceID::new();
+        let channel = PluginMessageChannel::<u32, u32>::new(plugin, 4);
+        assert_eq!(channel.plugin(), plugin);
+    }
+}
@@ -0,0 +1,203 @@
+//! Humanization of incoming live note events: timing jitter, velocity
+//! variance, and probability-based note dropping.
+//!
+//! Unlike [`EventQuantizer`](crate::plugin::EventQuantizer), which snaps
+//! events *onto* a grid, this nudges them *off* of wherever they already
+//! are, by a deterministic amount derived from a per-plugin seed rather
+//! than a stateful RNG. Deriving jitter from a hash of the event's own
+//! identity (rather than drawing from a sequential generator) means the
+//! same note always humanizes the same way regardless of how the host
+//! happens to split events across process blocks.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::plugin::event_quantizer::NoteEvent;
+
+/// Per-plugin humanization settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HumanizeSettings {
+    /// Maximum absolute timing jitter, in samples, applied symmetrically
+    /// around a note's original `sample_offset`.
+    pub timing_jitter_samples: u32,
+    /// Maximum absolute velocity jitter, applied symmetrically around a
+    /// note's original velocity and clamped back into `[0.0, 1.0]`.
+    pub velocity_jitter: f64,
+    /// Probability, in `[0.0, 1.0]`, that a given note survives. `1.0`
+    /// never drops a note; `0.0` drops every note.
+    pub note_probability: f64,
+    /// Seed for the deterministic per-note jitter; the same seed always
+    /// humanizes the same input the same way.
+    pub seed: u64,
+}
+
+impl Default for HumanizeSettings {
+    fn default() -> Self {
+        Self { timing_jitter_samples: 0, velocity_jitter: 0.0, note_probability: 1.0, seed: 0 }
+    }
+}
+
+/// A fast, dependency-free, deterministic hash from a `u64` to a `u64`,
+/// used to derive per-note jitter without carrying RNG state across calls.
+/// This is the splitmix64 finalizer.
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^ (x >> 31)
+}
+
+/// Maps a `u64` hash into a `f64` in `[0.0, 1.0)`.
+fn unit_float(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Per-plugin note humanization: timing jitter, velocity variance, and
+/// note dropping, applied deterministically so the same seed and input
+/// always produce the same output.
+#[derive(Debug, Default)]
+pub struct Humanizer {
+    settings: HashMap<PluginInstanceID, HumanizeSettings>,
+}
+
+impl Humanizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables humanization of `plugin`'s incoming note events with
+    /// `settings`.
+    pub fn set_settings(&mut self, plugin: PluginInstanceID, settings: HumanizeSettings) {
+        self.settings.insert(plugin, settings);
+    }
+
+    /// Disables humanization for a plugin; its events pass through
+    /// unmodified.
+    pub fn clear_settings(&mut self, plugin: PluginInstanceID) {
+        self.settings.remove(&plugin);
+    }
+
+    /// Humanizes `events` arriving at `plugin` during a block of
+    /// `block_frames`. Returns `events` unchanged if `plugin` has no
+    /// settings configured. Jitter is clamped so a note's `sample_offset`
+    /// never leaves `[0, block_frames)`.
+    pub fn humanize(&self, plugin: PluginInstanceID, block_frames: u32, events: &[NoteEvent]) -> Vec<NoteEvent> {
+        let Some(settings) = self.settings.get(&plugin) else {
+            return events.to_vec();
+        };
+
+        let mut out = Vec::new();
+        for (index, event) in events.iter().enumerate() {
+            let lane = splitmix64(settings.seed ^ (index as u64).wrapping_add(event.note_id as u64));
+
+            if settings.note_probability < 1.0 {
+                let roll = unit_float(splitmix64(lane ^ 0x1));
+                if roll >= settings.note_probability {
+                    continue;
+                }
+            }
+
+            let mut sample_offset = event.sample_offset;
+            if settings.timing_jitter_samples > 0 {
+                let roll = unit_float(splitmix64(lane ^ 0x2));
+                let jitter = (roll * 2.0 - 1.0) * settings.timing_jitter_samples as f64;
+                let jittered = event.sample_offset as i64 + jitter.round() as i64;
+                sample_offset = jittered.clamp(0, block_frames.saturating_sub(1) as i64) as u32;
+            }
+
+            let mut velocity = event.velocity;
+            if settings.velocity_jitter > 0.0 {
+                let roll = unit_float(splitmix64(lane ^ 0x3));
+                let jitter = (roll * 2.0 - 1.0) * settings.velocity_jitter;
+                velocity = (event.velocity + jitter).clamp(0.0, 1.0);
+            }
+
+            out.push(NoteEvent { sample_offset, velocity, ..*event });
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(sample_offset: u32, velocity: f64) -> NoteEvent {
+        NoteEvent { sample_offset, note_id: 60, velocity }
+    }
+
+    #[test]
+    fn an_unconfigured_plugin_passes_events_through_unmodified() {
+        let humanizer = Humanizer::new();
+        let events = [note(10, 0.8)];
+        assert_eq!(humanizer.humanize(PluginInstanceID::new(), 128, &events), events);
+    }
+
+    #[test]
+    fn the_same_seed_humanizes_the_same_input_identically() {
+        let mut humanizer = Humanizer::new();
+        let plugin = PluginInstanceID::new();
+        humanizer.set_settings(
+            plugin,
+            HumanizeSettings { timing_jitter_samples: 20, velocity_jitter: 0.1, note_probability: 0.7, seed: 42 },
+        );
+        let events: Vec<NoteEvent> = (0..16).map(|i| note(i * 8, 0.5)).collect();
+
+        let first = humanizer.humanize(plugin, 256, &events);
+        let second = humanizer.humanize(plugin, 256, &events);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn different_seeds_humanize_differently() {
+        let mut a = Humanizer::new();
+        let mut b = Humanizer::new();
+        let plugin = PluginInstanceID::new();
+        let settings =
+            HumanizeSettings { timing_jitter_samples: 30, velocity_jitter: 0.2, note_probability: 1.0, seed: 1 };
+        a.set_settings(plugin, settings);
+        b.set_settings(plugin, HumanizeSettings { seed: 2, ..settings });
+        let events: Vec<NoteEvent> = (0..8).map(|i| note(i * 10, 0.5)).collect();
+
+        assert_ne!(a.humanize(plugin, 256, &events), b.humanize(plugin, 256, &events));
+    }
+
+    #[test]
+    fn zero_probability_drops_every_note() {
+        let mut humanizer = Humanizer::new();
+        let plugin = PluginInstanceID::new();
+        humanizer.set_settings(plugin, HumanizeSettings { note_probability: 0.0, ..Default::default() });
+        let events: Vec<NoteEvent> = (0..8).map(|i| note(i * 10, 0.5)).collect();
+        assert!(humanizer.humanize(plugin, 256, &events).is_empty());
+    }
+
+    #[test]
+    fn timing_jitter_never_leaves_the_block() {
+        let mut humanizer = Humanizer::new();
+        let plugin = PluginInstanceID::new();
+        humanizer.set_settings(
+            plugin,
+            HumanizeSettings { timing_jitter_samples: 1000, note_probability: 1.0, ..Default::default() },
+        );
+        let events: Vec<NoteEvent> = (0..32).map(|i| note(i, 0.5)).collect();
+
+        for event in humanizer.humanize(plugin, 64, &events) {
+            assert!(event.sample_offset < 64);
+        }
+    }
+
+    #[test]
+    fn velocity_jitter_stays_within_bounds() {
+        let mut humanizer = Humanizer::new();
+        let plugin = PluginInstanceID::new();
+        humanizer.set_settings(
+            plugin,
+            HumanizeSettings { velocity_jitter: 0.9, note_probability: 1.0, ..Default::default() },
+        );
+        let events: Vec<NoteEvent> = (0..32).map(|i| note(i, 0.5)).collect();
+
+        for event in humanizer.humanize(plugin, 256, &events) {
+            assert!((0.0..=1.0).contains(&event.velocity));
+        }
+    }
+}
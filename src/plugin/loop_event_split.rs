@@ -0,0 +1,79 @@
+//! Splitting a block's sample-accurate events across transport loop
+//! boundaries.
+//!
+//! When the transport loops mid-block, the block is rendered as several
+//! sub-ranges: up to the loop point, then from the loop destination onward
+//! (and, for a loop shorter than one block, through the loop point again).
+//! Each sub-range is processed as if it were its own block starting at
+//! sample `0`, so any event scheduled after a loop crossing needs its
+//! `sample_offset` re-based to the start of the sub-range it actually
+//! falls in, or it would fire late (or not at all) once delivered against
+//! the wrong sub-range.
+
+use crate::plugin::event_quantizer::NoteEvent;
+
+/// Splits `events` into per-sub-range batches at `loop_crossings` (sample
+/// offsets within the original block where the transport wraps, in
+/// ascending order expected but not required — they're sorted internally).
+///
+/// Returns `loop_crossings.len() + 1` batches: the first covers everything
+/// before the first crossing, the last covers everything from the final
+/// crossing to the end of the block, and each event's `sample_offset` is
+/// re-based to the start of its own batch.
+pub fn split_events_across_loop_boundaries(events: &[NoteEvent], loop_crossings: &[u32]) -> Vec<Vec<NoteEvent>> {
+    let mut boundaries = loop_crossings.to_vec();
+    boundaries.sort_unstable();
+
+    let mut sub_ranges = vec![Vec::new(); boundaries.len() + 1];
+    for &event in events {
+        let segment = boundaries.partition_point(|&boundary| boundary <= event.sample_offset);
+        let segment_start = if segment == 0 { 0 } else { boundaries[segment - 1] };
+        sub_ranges[segment].push(NoteEvent { sample_offset: event.sample_offset - segment_start, ..event });
+    }
+    sub_ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(sample_offset: u32) -> NoteEvent {
+        NoteEvent { sample_offset, note_id: 60, velocity: 1.0 }
+    }
+
+    #[test]
+    fn no_loop_crossing_returns_a_single_unmodified_batch() {
+        let events = [note(0), note(500)];
+        let result = split_events_across_loop_boundaries(&events, &[]);
+        assert_eq!(result, vec![vec![note(0), note(500)]]);
+    }
+
+    #[test]
+    fn events_are_split_and_rebased_at_a_single_loop_crossing() {
+        let events = [note(10), note(100), note(150)];
+        let result = split_events_across_loop_boundaries(&events, &[100]);
+        assert_eq!(result, vec![vec![note(10)], vec![note(0), note(50)]]);
+    }
+
+    #[test]
+    fn an_event_exactly_on_a_loop_crossing_belongs_to_the_sub_range_after_it() {
+        let events = [note(100)];
+        let result = split_events_across_loop_boundaries(&events, &[100]);
+        assert_eq!(result, vec![vec![], vec![note(0)]]);
+    }
+
+    #[test]
+    fn a_loop_shorter_than_the_block_produces_more_than_two_sub_ranges() {
+        let events = [note(10), note(60), note(160)];
+        let result = split_events_across_loop_boundaries(&events, &[50, 150]);
+        assert_eq!(result, vec![vec![note(10)], vec![note(10)], vec![note(10)]]);
+    }
+
+    #[test]
+    fn unsorted_loop_crossings_are_sorted_before_splitting() {
+        let events = [note(10), note(120)];
+        let sorted = split_events_across_loop_boundaries(&events, &[100, 50]);
+        let unsorted = split_events_across_loop_boundaries(&events, &[50, 100]);
+        assert_eq!(sorted, unsorted);
+    }
+}
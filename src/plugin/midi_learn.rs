@@ -0,0 +1,202 @@
+//! MIDI learn: binding incoming MIDI CC messages to plugin parameters.
+//!
+//! The host arms learn mode for one parameter, the next CC message received
+//! on any port becomes that parameter's binding, and from then on every CC
+//! matching the binding is converted into a [`EventParamValue`] for the
+//! mapped plugin before the audio thread ever hands the raw CC to it —
+//! plugins never see CC messages that have been learned this way.
+
+use std::collections::HashMap;
+
+use crate::id::{ParamID, PluginInstanceID};
+use crate::plugin::param_cookie::{EventParamValue, ParamCookieCache};
+
+/// Identifies one MIDI CC controller on one channel of one input port, the
+/// unit a learned binding maps from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MidiCcKey {
+    pub port_index: u16,
+    pub channel: u8,
+    pub cc: u8,
+}
+
+/// How a CC's normalized `0.0..=1.0` value is shaped before being scaled
+/// into a mapping's `[min, max]` range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMappingCurve {
+    Linear,
+    /// Biases resolution toward the low end of the range, useful for
+    /// parameters like frequency where small low-end changes matter more.
+    Exponential,
+    /// Increasing the CC value decreases the parameter value.
+    Inverted,
+}
+
+impl MidiMappingCurve {
+    fn apply(&self, normalized: f64) -> f64 {
+        match self {
+            MidiMappingCurve::Linear => normalized,
+            MidiMappingCurve::Exponential => normalized * normalized,
+            MidiMappingCurve::Inverted => 1.0 - normalized,
+        }
+    }
+}
+
+/// A learned binding from a MIDI CC to one plugin parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiMapping {
+    pub plugin: PluginInstanceID,
+    pub param_id: ParamID,
+    pub min: f64,
+    pub max: f64,
+    pub curve: MidiMappingCurve,
+}
+
+/// Host-owned table of MIDI CC -> parameter bindings, plus the main-thread
+/// "learn mode" state used to create new bindings from the next incoming
+/// CC message.
+#[derive(Debug, Default)]
+pub struct MidiLearnTable {
+    mappings: HashMap<MidiCcKey, MidiMapping>,
+    armed: Option<MidiMapping>,
+}
+
+impl MidiLearnTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Arms learn mode: the next CC passed to [`learn_next_cc`](Self::learn_next_cc)
+    /// is bound to `plugin`'s `param_id`, scaled into `[min, max]` via
+    /// `curve`.
+    pub fn arm_learn(&mut self, plugin: PluginInstanceID, param_id: ParamID, min: f64, max: f64, curve: MidiMappingCurve) {
+        self.armed = Some(MidiMapping { plugin, param_id, min, max, curve });
+    }
+
+    pub fn cancel_learn(&mut self) {
+        self.armed = None;
+    }
+
+    pub fn is_learning(&self) -> bool {
+        self.armed.is_some()
+    }
+
+    /// Main-thread hook: if learn mode is armed, binds `key` to the armed
+    /// target and returns the new mapping for the host to persist. A no-op
+    /// returning `None` if learn mode isn't armed.
+    pub fn learn_next_cc(&mut self, key: MidiCcKey) -> Option<MidiMapping> {
+        let mapping = self.armed.take()?;
+        self.mappings.insert(key, mapping);
+        Some(mapping)
+    }
+
+    /// Directly sets a binding, e.g. when restoring mappings the host
+    /// previously persisted.
+    pub fn set_mapping(&mut self, key: MidiCcKey, mapping: MidiMapping) {
+        self.mappings.insert(key, mapping);
+    }
+
+    pub fn remove_mapping(&mut self, key: MidiCcKey) {
+        self.mappings.remove(&key);
+    }
+
+    /// Every binding currently held, for the host to persist.
+    pub fn mappings(&self) -> impl Iterator<Item = (&MidiCcKey, &MidiMapping)> {
+        self.mappings.iter()
+    }
+
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.mappings.retain(|_, mapping| mapping.plugin != plugin);
+    }
+
+    /// Audio-thread hook: converts an incoming 7-bit CC value at `key` into
+    /// the param-value event its mapped plugin should receive instead,
+    /// using `cookies` for the same cookie fast path every other
+    /// host-emitted param event takes. Returns `None` if no binding is
+    /// mapped to `key`, meaning the CC should pass through unmapped.
+    pub fn convert_cc(&self, key: MidiCcKey, value_7bit: u8, cookies: &ParamCookieCache) -> Option<EventParamValue> {
+        let mapping = self.mappings.get(&key)?;
+        let normalized = value_7bit as f64 / 127.0;
+        let curved = mapping.curve.apply(normalized);
+        let value = mapping.min + curved * (mapping.max - mapping.min);
+        Some(cookies.build_event(mapping.plugin, mapping.param_id, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(cc: u8) -> MidiCcKey {
+        MidiCcKey { port_index: 0, channel: 0, cc }
+    }
+
+    #[test]
+    fn learning_is_a_no_op_when_not_armed() {
+        let mut table = MidiLearnTable::new();
+        assert_eq!(table.learn_next_cc(key(1)), None);
+    }
+
+    #[test]
+    fn arming_and_receiving_a_cc_creates_a_binding() {
+        let mut table = MidiLearnTable::new();
+        let plugin = PluginInstanceID::new();
+        table.arm_learn(plugin, ParamID(3), 0.0, 1.0, MidiMappingCurve::Linear);
+        assert!(table.is_learning());
+
+        let mapping = table.learn_next_cc(key(74)).unwrap();
+        assert_eq!(mapping.plugin, plugin);
+        assert_eq!(mapping.param_id, ParamID(3));
+        assert!(!table.is_learning());
+    }
+
+    #[test]
+    fn cancelling_learn_mode_binds_nothing() {
+        let mut table = MidiLearnTable::new();
+        table.arm_learn(PluginInstanceID::new(), ParamID(0), 0.0, 1.0, MidiMappingCurve::Linear);
+        table.cancel_learn();
+        assert_eq!(table.learn_next_cc(key(1)), None);
+    }
+
+    #[test]
+    fn a_linear_binding_scales_the_cc_into_its_range() {
+        let mut table = MidiLearnTable::new();
+        let plugin = PluginInstanceID::new();
+        table.set_mapping(key(7), MidiMapping { plugin, param_id: ParamID(0), min: 0.0, max: 2.0, curve: MidiMappingCurve::Linear });
+
+        let cookies = ParamCookieCache::new();
+        let event = table.convert_cc(key(7), 127, &cookies).unwrap();
+        assert!((event.value - 2.0).abs() < 1e-9);
+        assert_eq!(event.param_id, ParamID(0));
+    }
+
+    #[test]
+    fn an_inverted_binding_scales_the_cc_backwards() {
+        let mut table = MidiLearnTable::new();
+        table.set_mapping(key(7), MidiMapping { plugin: PluginInstanceID::new(), param_id: ParamID(0), min: 0.0, max: 1.0, curve: MidiMappingCurve::Inverted });
+
+        let cookies = ParamCookieCache::new();
+        let event = table.convert_cc(key(7), 0, &cookies).unwrap();
+        assert!((event.value - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn an_unmapped_cc_converts_to_nothing() {
+        let table = MidiLearnTable::new();
+        let cookies = ParamCookieCache::new();
+        assert_eq!(table.convert_cc(key(1), 64, &cookies), None);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_only_its_bindings() {
+        let mut table = MidiLearnTable::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        table.set_mapping(key(1), MidiMapping { plugin: a, param_id: ParamID(0), min: 0.0, max: 1.0, curve: MidiMappingCurve::Linear });
+        table.set_mapping(key(2), MidiMapping { plugin: b, param_id: ParamID(0), min: 0.0, max: 1.0, curve: MidiMappingCurve::Linear });
+
+        table.remove_plugin(a);
+        assert_eq!(table.mappings().count(), 1);
+        assert_eq!(table.mappings().next().unwrap().1.plugin, b);
+    }
+}
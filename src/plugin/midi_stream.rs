@@ -0,0 +1,160 @@
+//! Decodes a live MIDI 1.0 byte stream into the engine's [`NoteEvent`]
+//! representation.
+//!
+//! A host wiring a `midir`/JACK MIDI callback straight into the engine
+//! gets raw bytes, not pre-parsed messages, framed by running status like
+//! any other MIDI 1.0 stream: a status byte can be omitted from a message
+//! if it repeats the previous one, and a single byte slice handed to
+//! [`MidiStreamDecoder::push`] might contain a partial message, several
+//! complete ones, or both. [`MidiStreamDecoder`] keeps the in-progress
+//! status/data byte state across calls so none of that matters to the
+//! caller; only note-on/note-off channel voice messages produce a
+//! [`NoteEvent`], everything else (CC, program change, pitch bend, sysex,
+//! realtime bytes) is consumed and dropped.
+
+use super::NoteEvent;
+
+/// How many data bytes follow a channel voice status byte.
+fn data_len(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    }
+}
+
+/// Decodes a complete channel voice message into a [`NoteEvent`], treating
+/// a note-on with velocity `0` as a note-off, matching
+/// [`midi_import`](crate::util::midi_import)'s convention. Returns `None`
+/// for anything that isn't a note-on/note-off.
+fn decode_note_event(status: u8, data: &[u8], sample_offset: u32) -> Option<NoteEvent> {
+    let note_id = *data.first()? as i32;
+    match status & 0xF0 {
+        0x90 if data.get(1).copied().unwrap_or(0) > 0 => {
+            Some(NoteEvent { sample_offset, note_id, velocity: data[1] as f64 / 127.0 })
+        }
+        0x90 | 0x80 => Some(NoteEvent { sample_offset, note_id, velocity: 0.0 }),
+        _ => None,
+    }
+}
+
+/// A stateful MIDI 1.0 byte-stream decoder for one input port, tracking
+/// running status and a partially received message across calls.
+#[derive(Debug, Clone, Default)]
+pub struct MidiStreamDecoder {
+    running_status: Option<u8>,
+    pending: Vec<u8>,
+}
+
+impl MidiStreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds `bytes` into the decoder, stamping any note events completed
+    /// by this call with `sample_offset` (the frame offset within the
+    /// current process block the bytes arrived at).
+    pub fn push(&mut self, bytes: &[u8], sample_offset: u32) -> Vec<NoteEvent> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            if byte >= 0xF8 {
+                // Realtime messages (clock, start/stop/...) carry no data
+                // bytes and don't interrupt an in-progress message.
+                continue;
+            }
+            if byte & 0x80 != 0 {
+                self.pending.clear();
+                // System common/exclusive status bytes (0xF0-0xF7) cancel
+                // running status per the MIDI spec; we don't decode their
+                // contents, so just stop tracking them as a status.
+                self.running_status = if byte < 0xF0 { Some(byte) } else { None };
+                continue;
+            }
+            let Some(status) = self.running_status else { continue };
+            self.pending.push(byte);
+            if self.pending.len() == data_len(status) {
+                if let Some(event) = decode_note_event(status, &self.pending, sample_offset) {
+                    events.push(event);
+                }
+                self.pending.clear();
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_complete_note_on_produces_a_note_event() {
+        let mut decoder = MidiStreamDecoder::new();
+        let events = decoder.push(&[0x90, 60, 100], 5);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 5, note_id: 60, velocity: 100.0 / 127.0 }]);
+    }
+
+    #[test]
+    fn a_zero_velocity_note_on_is_treated_as_a_note_off() {
+        let mut decoder = MidiStreamDecoder::new();
+        let events = decoder.push(&[0x90, 60, 0], 0);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 0, note_id: 60, velocity: 0.0 }]);
+    }
+
+    #[test]
+    fn a_note_off_status_produces_a_zero_velocity_event() {
+        let mut decoder = MidiStreamDecoder::new();
+        let events = decoder.push(&[0x80, 60, 64], 0);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 0, note_id: 60, velocity: 0.0 }]);
+    }
+
+    #[test]
+    fn running_status_reuses_the_previous_status_byte() {
+        let mut decoder = MidiStreamDecoder::new();
+        let events = decoder.push(&[0x90, 60, 100, 64, 80], 0);
+        assert_eq!(
+            events,
+            vec![
+                NoteEvent { sample_offset: 0, note_id: 60, velocity: 100.0 / 127.0 },
+                NoteEvent { sample_offset: 0, note_id: 64, velocity: 80.0 / 127.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_message_split_across_two_push_calls_still_decodes() {
+        let mut decoder = MidiStreamDecoder::new();
+        assert!(decoder.push(&[0x90, 60], 0).is_empty());
+        let events = decoder.push(&[100], 3);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 3, note_id: 60, velocity: 100.0 / 127.0 }]);
+    }
+
+    #[test]
+    fn a_non_note_channel_message_is_consumed_without_producing_an_event() {
+        let mut decoder = MidiStreamDecoder::new();
+        // Control change (3 bytes via running status rules) followed by a
+        // note-on that reuses the control-change status would be wrong, so
+        // use a fresh status byte for the note-on to isolate the CC.
+        let events = decoder.push(&[0xB0, 7, 100, 0x90, 60, 100], 0);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 0, note_id: 60, velocity: 100.0 / 127.0 }]);
+    }
+
+    #[test]
+    fn a_realtime_byte_mid_message_does_not_disturb_the_partial_message() {
+        let mut decoder = MidiStreamDecoder::new();
+        assert!(decoder.push(&[0x90, 60], 0).is_empty());
+        // A clock byte (0xF8) can legally appear between any two bytes of
+        // another message.
+        let events = decoder.push(&[0xF8, 100], 0);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 0, note_id: 60, velocity: 100.0 / 127.0 }]);
+    }
+
+    #[test]
+    fn a_program_change_consumes_only_one_data_byte() {
+        let mut decoder = MidiStreamDecoder::new();
+        // Program change (1 data byte) then, via running status... program
+        // change never repeats as running status for a note, so give the
+        // note-on its own status byte.
+        let events = decoder.push(&[0xC0, 5, 0x90, 60, 100], 0);
+        assert_eq!(events, vec![NoteEvent { sample_offset: 0, note_id: 60, velocity: 100.0 / 127.0 }]);
+    }
+}
@@ -0,0 +1,71 @@
+//! Plugin hosting: GUI embedding, parameters, and state.
+
+pub mod arpeggiator;
+pub mod block_splitter;
+pub mod bypass;
+pub mod dry_wet;
+pub mod dual_mono;
+pub mod event_merge;
+pub mod event_quantizer;
+pub mod gain_stage;
+pub mod gui;
+pub mod held_notes;
+pub mod host_callback;
+pub mod host_message_channel;
+pub mod humanize;
+pub mod loop_event_split;
+pub mod midi_learn;
+pub mod midi_stream;
+pub mod note_dialect;
+pub mod note_ports_rescan;
+pub mod param_cookie;
+pub mod param_diff;
+pub mod param_display;
+pub mod param_readout;
+pub mod plugin_order;
+pub mod preset_morph;
+pub mod process_stats;
+pub mod remote_controls;
+pub mod scale_filter;
+pub mod scan_watch;
+pub mod scanner;
+pub mod soft_bypass;
+pub mod state;
+pub mod track_info;
+pub mod voice_info;
+
+pub use arpeggiator::{ArpPattern, ArpSettings, Arpeggiator};
+pub use block_splitter::{split_block, PluginBlockLimits, SubBlock};
+pub use bypass::PluginBypassStates;
+pub use dry_wet::{PluginDryWetStage, PluginDryWetStages, DEFAULT_DRY_WET_RAMP_SAMPLES};
+pub use dual_mono::{DualMonoPair, DualMonoWrapper};
+pub use event_merge::{merge_events, EventMergePolicy, EventSource, SourcedEvent};
+pub use event_quantizer::{EventQuantizer, NoteEvent, QuantizeGrid, QuantizedEvents};
+pub use gui::{create_gui_with_fallback, GuiEmbeddingApi, GuiPreviewImage, PluginGuiPreview, PreviewCaptureError};
+pub use held_notes::{HeldNote, HeldNotesHandle, HeldNotesTable};
+pub use host_callback::{HostEventCallbacks, ProcInfo};
+pub use host_message_channel::{PluginMessageChannel, QueueFull as HostMessageQueueFull};
+pub use humanize::{HumanizeSettings, Humanizer};
+pub use gain_stage::{PluginGainStage, PluginGainStages, DEFAULT_GAIN_RAMP_SAMPLES};
+pub use loop_event_split::split_events_across_loop_boundaries;
+pub use midi_learn::{MidiCcKey, MidiLearnTable, MidiMapping, MidiMappingCurve};
+pub use midi_stream::MidiStreamDecoder;
+pub use note_dialect::{NoteDialect, NoteDialectTable, NotePortKey};
+pub use note_ports_rescan::{apply_note_ports_rescan, diff_note_port_lists, NotePortChange, NotePortInfo, NotePortListDiff};
+pub use param_cookie::{EventParamMod, EventParamValue, NoteTarget, ParamCookie, ParamCookieCache};
+pub use param_diff::{diff_param_lists, ParamChange, ParamInfo, ParamListDiff};
+pub use param_display::{ParamDisplayCache, ParamValueFormatter};
+pub use param_readout::{ParamReadout, ParamReadoutTable};
+pub use plugin_order::PluginOrder;
+pub use preset_morph::{ParamSnapshot, PresetMorph};
+pub use process_stats::{bucket_range, PluginProcessStats, ProcessStatsHandle, NUM_BUCKETS};
+pub use remote_controls::{RemoteControlPage, RemoteControlPagesTable, REMOTE_CONTROL_PAGE_SIZE};
+pub use scale_filter::{Scale, ScaleFilter, ScaleFilterSettings};
+#[cfg(feature = "watch-folders")]
+pub use scan_watch::FolderWatcher;
+pub use scan_watch::{PluginScannerEvent, ScanEventQueue};
+pub use scanner::{default_lv2_search_paths, PluginDescriptor, PluginFormat, PluginKey, PluginScanDiff, PluginScanner};
+pub use soft_bypass::{bypass_method, toggle_plugin_bypass, BypassMethod};
+pub use state::DSPluginSaveState;
+pub use track_info::{TrackInfo, TrackInfoFlags, TrackInfoTable};
+pub use voice_info::{VoiceInfo, VoiceInfoHandle, VoiceInfoTable};
@@ -0,0 +1,151 @@
+//! Per-note-port dialect negotiation results and host overrides.
+//!
+//! A note port's wire format (CLAP note events vs. raw MIDI vs. MIDI2) is
+//! negotiated with the plugin at activation time, but until now the choice
+//! was implicit: nothing recorded which dialect actually won, and a host
+//! had no way to diagnose a misbehaving plugin or force a specific dialect
+//! around a buggy negotiation.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+
+/// A note event wire format a plugin's note port can negotiate to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteDialect {
+    Clap,
+    Midi,
+    Midi2,
+}
+
+/// Identifies one note port on one plugin instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NotePortKey {
+    pub plugin: PluginInstanceID,
+    pub port_index: u32,
+}
+
+/// Records which dialect each note port actually negotiated to, and lets
+/// the host force a specific dialect for a port regardless of what it
+/// would otherwise negotiate.
+///
+/// Negotiated results are runtime-only (re-derived every activation);
+/// forced overrides are the part that persists in project save state, via
+/// [`forced_entries`](Self::forced_entries) /
+/// [`from_forced_entries`](Self::from_forced_entries).
+#[derive(Debug, Default)]
+pub struct NoteDialectTable {
+    negotiated: HashMap<NotePortKey, NoteDialect>,
+    forced: HashMap<NotePortKey, NoteDialect>,
+}
+
+impl NoteDialectTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds the forced-override half of the table from a persisted
+    /// snapshot, e.g. when loading project save state. Negotiated results
+    /// start empty; they're filled in again the next time each plugin
+    /// activates.
+    pub fn from_forced_entries(entries: impl IntoIterator<Item = (NotePortKey, NoteDialect)>) -> Self {
+        Self { negotiated: HashMap::new(), forced: entries.into_iter().collect() }
+    }
+
+    /// Records the dialect `port` actually negotiated to after activation.
+    pub fn record_negotiated(&mut self, port: NotePortKey, dialect: NoteDialect) {
+        self.negotiated.insert(port, dialect);
+    }
+
+    /// The dialect `port` negotiated to, if it has activated since the
+    /// table was last reset.
+    pub fn negotiated(&self, port: NotePortKey) -> Option<NoteDialect> {
+        self.negotiated.get(&port).copied()
+    }
+
+    /// Forces `port` to use `dialect` regardless of what it would
+    /// otherwise negotiate to.
+    pub fn force(&mut self, port: NotePortKey, dialect: NoteDialect) {
+        self.forced.insert(port, dialect);
+    }
+
+    /// Clears a forced override, letting `port` negotiate normally again.
+    pub fn clear_forced(&mut self, port: NotePortKey) {
+        self.forced.remove(&port);
+    }
+
+    pub fn forced(&self, port: NotePortKey) -> Option<NoteDialect> {
+        self.forced.get(&port).copied()
+    }
+
+    /// The dialect that should actually be used for `port`: a forced
+    /// override if one is set, otherwise the negotiated result.
+    pub fn effective(&self, port: NotePortKey) -> Option<NoteDialect> {
+        self.forced.get(&port).or_else(|| self.negotiated.get(&port)).copied()
+    }
+
+    /// A snapshot of every forced override, for persisting into project
+    /// save state. Negotiated results aren't included; they're runtime
+    /// diagnostics, re-derived on the next activation.
+    pub fn forced_entries(&self) -> Vec<(NotePortKey, NoteDialect)> {
+        self.forced.iter().map(|(port, dialect)| (*port, *dialect)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(index: u32) -> NotePortKey {
+        NotePortKey { plugin: PluginInstanceID::new(), port_index: index }
+    }
+
+    #[test]
+    fn effective_falls_back_to_negotiated_without_a_forced_override() {
+        let mut table = NoteDialectTable::new();
+        let port = port(0);
+        table.record_negotiated(port, NoteDialect::Clap);
+        assert_eq!(table.effective(port), Some(NoteDialect::Clap));
+    }
+
+    #[test]
+    fn a_forced_override_takes_priority_over_the_negotiated_result() {
+        let mut table = NoteDialectTable::new();
+        let port = port(0);
+        table.record_negotiated(port, NoteDialect::Clap);
+        table.force(port, NoteDialect::Midi2);
+        assert_eq!(table.effective(port), Some(NoteDialect::Midi2));
+        assert_eq!(table.negotiated(port), Some(NoteDialect::Clap));
+    }
+
+    #[test]
+    fn clearing_a_forced_override_reverts_to_the_negotiated_result() {
+        let mut table = NoteDialectTable::new();
+        let port = port(0);
+        table.record_negotiated(port, NoteDialect::Midi);
+        table.force(port, NoteDialect::Midi2);
+        table.clear_forced(port);
+        assert_eq!(table.effective(port), Some(NoteDialect::Midi));
+    }
+
+    #[test]
+    fn a_port_with_no_negotiation_or_override_has_no_effective_dialect() {
+        let table = NoteDialectTable::new();
+        assert_eq!(table.effective(port(0)), None);
+    }
+
+    #[test]
+    fn forced_overrides_round_trip_through_forced_entries() {
+        let mut table = NoteDialectTable::new();
+        let a = port(0);
+        let b = port(1);
+        table.force(a, NoteDialect::Midi);
+        table.force(b, NoteDialect::Midi2);
+        table.record_negotiated(a, NoteDialect::Clap);
+
+        let restored = NoteDialectTable::from_forced_entries(table.forced_entries());
+        assert_eq!(restored.forced(a), Some(NoteDialect::Midi));
+        assert_eq!(restored.forced(b), Some(NoteDialect::Midi2));
+        assert_eq!(restored.negotiated(a), None);
+    }
+}
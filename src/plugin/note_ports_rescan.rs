@@ -0,0 +1,168 @@
+//! Handling the CLAP `note-ports` rescan host callback: a plugin telling
+//! the host its note port list or preferred dialect changed at runtime,
+//! rather than only ever being asked once at activation.
+//!
+//! This mirrors [`diff_param_lists`](super::param_diff::diff_param_lists)'s
+//! before/after diffing shape, applied to note ports instead of parameters,
+//! and feeds the result into [`NoteDialectTable`] so a forced host override
+//! still wins over whatever a plugin now prefers.
+
+use super::note_dialect::{NoteDialect, NoteDialectTable, NotePortKey};
+use crate::id::PluginInstanceID;
+
+/// A snapshot of one note port's declared info, as of a particular scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotePortInfo {
+    pub port_index: u32,
+    pub name: String,
+    /// The dialect the plugin would prefer to negotiate to if the host
+    /// doesn't force one, per CLAP's `preferred_dialect`.
+    pub preferred_dialect: NoteDialect,
+}
+
+/// A note port present both before and after a rescan, whose preferred
+/// dialect or name changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotePortChange {
+    pub port_index: u32,
+    pub before: NotePortInfo,
+    pub after: NotePortInfo,
+}
+
+/// The result of comparing a plugin's note port list before and after a
+/// `CLAP_NOTE_PORTS_RESCAN_*` callback.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NotePortListDiff {
+    pub added: Vec<NotePortInfo>,
+    pub removed: Vec<NotePortInfo>,
+    pub changed: Vec<NotePortChange>,
+}
+
+impl NotePortListDiff {
+    /// Whether anything actually changed; an empty diff means the rescan
+    /// callback fired but nothing a host cares about moved.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares `before` and `after` note port list snapshots, matching ports by
+/// `port_index`. Anything only in `before` is `removed`, anything only in
+/// `after` is `added`, and anything present in both with a different
+/// preferred dialect or name is `changed`.
+pub fn diff_note_port_lists(before: &[NotePortInfo], after: &[NotePortInfo]) -> NotePortListDiff {
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for port in after {
+        match before.iter().find(|p| p.port_index == port.port_index) {
+            None => added.push(port.clone()),
+            Some(old) if old != port => {
+                changed.push(NotePortChange { port_index: port.port_index, before: old.clone(), after: port.clone() })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let removed: Vec<NotePortInfo> =
+        before.iter().filter(|port| !after.iter().any(|p| p.port_index == port.port_index)).cloned().collect();
+
+    NotePortListDiff { added, removed, changed }
+}
+
+/// Applies a note-ports rescan diff to `table`: for every added or changed
+/// port, records the plugin's new preferred dialect as its negotiated
+/// result (a [`force`](NoteDialectTable::force)d host override, if any,
+/// still takes priority). A removed port's stale negotiated/forced entries
+/// are left alone; re-syncing the graph to drop the port is the host's job.
+///
+/// Returns the ports whose *effective* dialect actually changed as a
+/// result, so the host knows which graph ports to re-sync and which GUIs to
+/// notify — a port whose preferred dialect changed but is pinned by a
+/// forced override doesn't need either.
+pub fn apply_note_ports_rescan(
+    plugin: PluginInstanceID,
+    diff: &NotePortListDiff,
+    table: &mut NoteDialectTable,
+) -> Vec<NotePortKey> {
+    let mut changed_effective = Vec::new();
+    for port_info in diff.added.iter().chain(diff.changed.iter().map(|change| &change.after)) {
+        let key = NotePortKey { plugin, port_index: port_info.port_index };
+        let before_effective = table.effective(key);
+        table.record_negotiated(key, port_info.preferred_dialect);
+        if table.effective(key) != before_effective {
+            changed_effective.push(key);
+        }
+    }
+    changed_effective
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(index: u32, name: &str, dialect: NoteDialect) -> NotePortInfo {
+        NotePortInfo { port_index: index, name: name.to_string(), preferred_dialect: dialect }
+    }
+
+    #[test]
+    fn identical_lists_produce_an_empty_diff() {
+        let list = vec![port(0, "In 1", NoteDialect::Clap)];
+        let diff = diff_note_port_lists(&list, &list);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn a_new_port_index_is_reported_as_added() {
+        let before = vec![];
+        let after = vec![port(0, "In 1", NoteDialect::Clap)];
+        let diff = diff_note_port_lists(&before, &after);
+        assert_eq!(diff.added, vec![port(0, "In 1", NoteDialect::Clap)]);
+    }
+
+    #[test]
+    fn a_missing_port_index_is_reported_as_removed() {
+        let before = vec![port(0, "In 1", NoteDialect::Clap)];
+        let after = vec![];
+        let diff = diff_note_port_lists(&before, &after);
+        assert_eq!(diff.removed, vec![port(0, "In 1", NoteDialect::Clap)]);
+    }
+
+    #[test]
+    fn a_changed_preferred_dialect_is_reported_as_changed() {
+        let before = vec![port(0, "In 1", NoteDialect::Clap)];
+        let after = vec![port(0, "In 1", NoteDialect::Midi2)];
+        let diff = diff_note_port_lists(&before, &after);
+        assert_eq!(diff.changed, vec![NotePortChange { port_index: 0, before: before[0].clone(), after: after[0].clone() }]);
+    }
+
+    #[test]
+    fn applying_a_rescan_updates_the_negotiated_dialect_and_reports_the_change() {
+        let plugin = PluginInstanceID::new();
+        let mut table = NoteDialectTable::new();
+        let before = vec![port(0, "In 1", NoteDialect::Clap)];
+        let after = vec![port(0, "In 1", NoteDialect::Midi2)];
+        let diff = diff_note_port_lists(&before, &after);
+
+        let changed = apply_note_ports_rescan(plugin, &diff, &mut table);
+        let key = NotePortKey { plugin, port_index: 0 };
+        assert_eq!(table.negotiated(key), Some(NoteDialect::Midi2));
+        assert_eq!(changed, vec![key]);
+    }
+
+    #[test]
+    fn a_forced_override_absorbs_a_preferred_dialect_change_without_reporting_it() {
+        let plugin = PluginInstanceID::new();
+        let mut table = NoteDialectTable::new();
+        let key = NotePortKey { plugin, port_index: 0 };
+        table.force(key, NoteDialect::Midi);
+        table.record_negotiated(key, NoteDialect::Clap);
+
+        let before = vec![port(0, "In 1", NoteDialect::Clap)];
+        let after = vec![port(0, "In 1", NoteDialect::Midi2)];
+        let diff = diff_note_port_lists(&before, &after);
+
+        let changed = apply_note_ports_rescan(plugin, &diff, &mut table);
+        assert!(changed.is_empty(), "forced override still wins, so the effective dialect never moved");
+        assert_eq!(table.effective(key), Some(NoteDialect::Midi));
+    }
+}
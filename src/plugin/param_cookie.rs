@@ -0,0 +1,178 @@
+//! Caches CLAP parameter cookies so host-emitted param-value events can
+//! skip the plugin-side hash lookup from a parameter's `clap_id` on every
+//! event.
+//!
+//! CLAP lets a plugin hand back an opaque "cookie" the first time it's
+//! asked about a parameter (via `params.get_info`), which the host can then
+//! stash on every subsequent `clap_event_param_value` for that parameter so
+//! the plugin can skip its own lookup. Before this cache existed, every
+//! emitted event always carried [`ParamCookie::NONE`], throwing that fast
+//! path away on every single automation event.
+
+use std::collections::HashMap;
+
+use crate::id::{ParamID, PluginInstanceID};
+
+/// An opaque, plugin-provided fast-path token for one parameter. Dropseed
+/// never interprets its value — it is only ever echoed back to the same
+/// plugin instance it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParamCookie(pub usize);
+
+impl ParamCookie {
+    /// The cookie CLAP uses to mean "no cookie available"; an event built
+    /// with this falls back to the plugin's own lookup.
+    pub const NONE: ParamCookie = ParamCookie(0);
+}
+
+impl Default for ParamCookie {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// A host-to-plugin parameter value change event, built with the fast-path
+/// cookie from a [`ParamCookieCache`] instead of always passing
+/// [`ParamCookie::NONE`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventParamValue {
+    pub param_id: ParamID,
+    pub value: f64,
+    pub cookie: ParamCookie,
+}
+
+/// Which voice a polyphonic modulation event targets, mirroring CLAP's
+/// `note_id`/`port_index`/`channel`/`key` quadruple on `clap_event_param_mod`.
+/// Each field is `-1` to mean "every voice matches on this field", so a
+/// modulator can target anything from a single held note up to every voice
+/// on the plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoteTarget {
+    pub note_id: i32,
+    pub port_index: i16,
+    pub channel: i16,
+    pub key: i16,
+}
+
+impl NoteTarget {
+    /// Targets every voice on the plugin, the non-polyphonic case.
+    pub const ALL: NoteTarget = NoteTarget { note_id: -1, port_index: -1, channel: -1, key: -1 };
+}
+
+/// A host-to-plugin polyphonic modulation event: a relative offset applied
+/// to `param_id` for the voice(s) matching `target`, distinct from
+/// [`EventParamValue`]'s absolute, non-targeted value the same way CLAP's
+/// `param_mod` event is distinct from `param_value`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventParamMod {
+    pub param_id: ParamID,
+    pub amount: f64,
+    pub target: NoteTarget,
+    pub cookie: ParamCookie,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    plugin: PluginInstanceID,
+    param: ParamID,
+}
+
+/// Caches each plugin parameter's cookie so host-emitted param-value events
+/// can use the fast path instead of always carrying [`ParamCookie::NONE`].
+#[derive(Debug, Default)]
+pub struct ParamCookieCache {
+    cookies: HashMap<Key, ParamCookie>,
+}
+
+impl ParamCookieCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the cookie a plugin returned for one of its parameters.
+    pub fn set(&mut self, plugin: PluginInstanceID, param: ParamID, cookie: ParamCookie) {
+        self.cookies.insert(Key { plugin, param }, cookie);
+    }
+
+    /// Returns the cached cookie for a parameter, or [`ParamCookie::NONE`]
+    /// if it hasn't been cached yet.
+    pub fn get(&self, plugin: PluginInstanceID, param: ParamID) -> ParamCookie {
+        self.cookies.get(&Key { plugin, param }).copied().unwrap_or_default()
+    }
+
+    /// Builds an [`EventParamValue`] for `param_id` on `plugin`, attaching
+    /// its cached cookie so the plugin can take the fast path instead of
+    /// hashing `param_id` itself.
+    pub fn build_event(&self, plugin: PluginInstanceID, param_id: ParamID, value: f64) -> EventParamValue {
+        EventParamValue { param_id, value, cookie: self.get(plugin, param_id) }
+    }
+
+    /// Builds an [`EventParamMod`] for `param_id` on `plugin`, targeting
+    /// `target` and attaching its cached cookie the same way
+    /// [`build_event`](Self::build_event) does for absolute values.
+    pub fn build_mod_event(&self, plugin: PluginInstanceID, param_id: ParamID, amount: f64, target: NoteTarget) -> EventParamMod {
+        EventParamMod { param_id, amount, target, cookie: self.get(plugin, param_id) }
+    }
+
+    /// Drops every cached cookie for a plugin, e.g. after its parameter
+    /// list is rescanned and previously handed-out cookies may no longer
+    /// be valid.
+    pub fn invalidate_plugin(&mut self, plugin: PluginInstanceID) {
+        self.cookies.retain(|key, _| key.plugin != plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_uncached_param_builds_an_event_with_the_none_cookie() {
+        let cache = ParamCookieCache::new();
+        let event = cache.build_event(PluginInstanceID::new(), ParamID(0), 0.5);
+        assert_eq!(event.cookie, ParamCookie::NONE);
+    }
+
+    #[test]
+    fn a_cached_cookie_is_used_when_building_the_event() {
+        let mut cache = ParamCookieCache::new();
+        let plugin = PluginInstanceID::new();
+        cache.set(plugin, ParamID(3), ParamCookie(0xABCD));
+
+        let event = cache.build_event(plugin, ParamID(3), 0.25);
+        assert_eq!(event.cookie, ParamCookie(0xABCD));
+        assert_eq!(event.param_id, ParamID(3));
+        assert_eq!(event.value, 0.25);
+    }
+
+    #[test]
+    fn a_mod_event_carries_its_note_target_and_cached_cookie() {
+        let mut cache = ParamCookieCache::new();
+        let plugin = PluginInstanceID::new();
+        cache.set(plugin, ParamID(5), ParamCookie(0x1234));
+
+        let target = NoteTarget { note_id: 7, port_index: 0, channel: 0, key: 60 };
+        let event = cache.build_mod_event(plugin, ParamID(5), 0.1, target);
+        assert_eq!(event.target, target);
+        assert_eq!(event.cookie, ParamCookie(0x1234));
+        assert_eq!(event.amount, 0.1);
+    }
+
+    #[test]
+    fn note_target_all_matches_every_field_as_wildcard() {
+        assert_eq!(NoteTarget::ALL, NoteTarget { note_id: -1, port_index: -1, channel: -1, key: -1 });
+    }
+
+    #[test]
+    fn invalidating_a_plugin_drops_only_its_cookies() {
+        let mut cache = ParamCookieCache::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        cache.set(a, ParamID(0), ParamCookie(1));
+        cache.set(b, ParamID(0), ParamCookie(2));
+
+        cache.invalidate_plugin(a);
+        assert_eq!(cache.get(a, ParamID(0)), ParamCookie::NONE);
+        assert_eq!(cache.get(b, ParamID(0)), ParamCookie(2));
+    }
+}
@@ -0,0 +1,145 @@
+//! Diffing two snapshots of a plugin's parameter list.
+//!
+//! When a plugin fires `PluginUpdatedParameterList` (its CLAP parameter
+//! count or info changed, e.g. after loading a different internal preset
+//! bank), the host needs to figure out what actually changed to keep
+//! automation lanes and UI mappings in sync, rather than just refetching
+//! everything and losing track of what used to be what.
+
+use std::collections::HashMap;
+
+use crate::id::ParamID;
+
+/// A snapshot of one parameter's declared info, as of a particular scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamInfo {
+    pub id: ParamID,
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub default: f64,
+    /// Whether the plugin declared this parameter with CLAP's
+    /// `CLAP_PARAM_IS_BYPASS` flag, meaning the plugin implements its own
+    /// latency-correct soft bypass through it; see
+    /// [`soft_bypass`](super::soft_bypass).
+    pub is_bypass: bool,
+}
+
+/// A parameter present both before and after, whose declared info changed
+/// (a new range, default, or display name), independent of its current
+/// value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamChange {
+    pub id: ParamID,
+    pub before: ParamInfo,
+    pub after: ParamInfo,
+}
+
+/// The result of comparing a plugin's parameter list before and after a
+/// rescan.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParamListDiff {
+    pub added: Vec<ParamInfo>,
+    pub removed: Vec<ParamInfo>,
+    pub changed: Vec<ParamChange>,
+    /// Best-effort mapping from a removed parameter's old ID to an added
+    /// parameter's new ID, for parameters that look like the same logical
+    /// control under a new stable ID (matched by name, since the ID itself
+    /// is what changed). Automation lanes and mappings can use this to
+    /// migrate themselves instead of silently losing their binding.
+    pub remap: HashMap<ParamID, ParamID>,
+}
+
+/// Compares `before` and `after` parameter list snapshots, matching
+/// parameters by [`ParamID`] first. Anything only in `before` is
+/// `removed`, anything only in `after` is `added`, and anything present in
+/// both with different info is `changed`. Removed/added pairs that share a
+/// name are recorded in [`ParamListDiff::remap`] as a likely rename.
+pub fn diff_param_lists(before: &[ParamInfo], after: &[ParamInfo]) -> ParamListDiff {
+    let before_by_id: HashMap<ParamID, &ParamInfo> = before.iter().map(|p| (p.id, p)).collect();
+    let after_by_id: HashMap<ParamID, &ParamInfo> = after.iter().map(|p| (p.id, p)).collect();
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for param in after {
+        match before_by_id.get(&param.id) {
+            None => added.push(param.clone()),
+            Some(&old) if old != param => {
+                changed.push(ParamChange { id: param.id, before: old.clone(), after: param.clone() })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let removed: Vec<ParamInfo> =
+        before.iter().filter(|param| !after_by_id.contains_key(&param.id)).cloned().collect();
+
+    let mut remap = HashMap::new();
+    for old in &removed {
+        if let Some(new) = added.iter().find(|param| param.name == old.name) {
+            remap.insert(old.id, new.id);
+        }
+    }
+
+    ParamListDiff { added, removed, changed, remap }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(id: u32, name: &str, min: f64, max: f64, default: f64) -> ParamInfo {
+        ParamInfo { id: ParamID(id), name: name.to_string(), min, max, default, is_bypass: false }
+    }
+
+    #[test]
+    fn identical_lists_produce_an_empty_diff() {
+        let list = vec![param(0, "Gain", 0.0, 1.0, 0.5)];
+        let diff = diff_param_lists(&list, &list);
+        assert_eq!(diff, ParamListDiff::default());
+    }
+
+    #[test]
+    fn a_new_id_with_no_match_is_reported_as_added() {
+        let before = vec![];
+        let after = vec![param(0, "Gain", 0.0, 1.0, 0.5)];
+        let diff = diff_param_lists(&before, &after);
+        assert_eq!(diff.added, vec![param(0, "Gain", 0.0, 1.0, 0.5)]);
+        assert!(diff.removed.is_empty());
+    }
+
+    #[test]
+    fn a_missing_id_with_no_match_is_reported_as_removed() {
+        let before = vec![param(0, "Gain", 0.0, 1.0, 0.5)];
+        let after = vec![];
+        let diff = diff_param_lists(&before, &after);
+        assert_eq!(diff.removed, vec![param(0, "Gain", 0.0, 1.0, 0.5)]);
+        assert!(diff.added.is_empty());
+    }
+
+    #[test]
+    fn the_same_id_with_a_different_range_is_reported_as_changed() {
+        let before = vec![param(0, "Gain", 0.0, 1.0, 0.5)];
+        let after = vec![param(0, "Gain", 0.0, 2.0, 0.5)];
+        let diff = diff_param_lists(&before, &after);
+        assert_eq!(diff.changed, vec![ParamChange { id: ParamID(0), before: before[0].clone(), after: after[0].clone() }]);
+    }
+
+    #[test]
+    fn a_removed_and_added_pair_sharing_a_name_is_remapped() {
+        let before = vec![param(0, "Cutoff", 20.0, 20_000.0, 1000.0)];
+        let after = vec![param(1, "Cutoff", 20.0, 20_000.0, 1000.0)];
+        let diff = diff_param_lists(&before, &after);
+        assert_eq!(diff.removed, vec![before[0].clone()]);
+        assert_eq!(diff.added, vec![after[0].clone()]);
+        assert_eq!(diff.remap.get(&ParamID(0)), Some(&ParamID(1)));
+    }
+
+    #[test]
+    fn a_removed_param_with_no_name_match_has_no_remap_entry() {
+        let before = vec![param(0, "Cutoff", 20.0, 20_000.0, 1000.0)];
+        let after = vec![param(1, "Resonance", 0.0, 1.0, 0.0)];
+        let diff = diff_param_lists(&before, &after);
+        assert!(diff.remap.is_empty());
+    }
+}
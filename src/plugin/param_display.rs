@@ -0,0 +1,139 @@
+//! Caches the host-facing display text for plugin parameter values.
+//!
+//! A visible knob asks its plugin to format its current value into text
+//! (CLAP's `param_value_to_text`, or the equivalent for an internal plugin)
+//! every frame it's on screen. That round trip is cheap for an internal
+//! plugin but can mean an out-of-process call for an external one, so
+//! formatted strings are cached here keyed by a quantized value bucket and
+//! only invalidated when the plugin's parameter list is rescanned.
+
+use std::collections::HashMap;
+
+use crate::id::{ParamID, PluginInstanceID};
+
+/// Something that can turn a parameter's raw value into display text, the
+/// same role a plugin's `param_value_to_text` callback plays.
+pub trait ParamValueFormatter {
+    fn format_param_value(&self, param_id: ParamID, value: f64) -> String;
+}
+
+/// Quantizes a value into a cache bucket. Display text rarely needs more
+/// precision than this, and without quantization every tiny automation
+/// wiggle would be a guaranteed cache miss.
+fn bucket(value: f64) -> i64 {
+    (value * 1_000_000.0).round() as i64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    plugin: PluginInstanceID,
+    param: ParamID,
+    bucket: i64,
+}
+
+/// A main-thread cache of formatted parameter display strings.
+#[derive(Debug, Default)]
+pub struct ParamDisplayCache {
+    entries: HashMap<CacheKey, String>,
+}
+
+impl ParamDisplayCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the display text for `param_id` at `value` on `plugin`,
+    /// formatting and caching it via `formatter` on a cache miss.
+    pub fn format(
+        &mut self,
+        formatter: &dyn ParamValueFormatter,
+        plugin: PluginInstanceID,
+        param_id: ParamID,
+        value: f64,
+    ) -> &str {
+        let key = CacheKey { plugin, param: param_id, bucket: bucket(value) };
+        self.entries.entry(key).or_insert_with(|| formatter.format_param_value(param_id, value))
+    }
+
+    /// Formats many `(param_id, value)` pairs for the same plugin in one
+    /// call, so a UI redrawing a whole panel of knobs doesn't pay per-call
+    /// overhead for each one individually.
+    pub fn format_many(
+        &mut self,
+        formatter: &dyn ParamValueFormatter,
+        plugin: PluginInstanceID,
+        values: &[(ParamID, f64)],
+    ) -> Vec<String> {
+        values
+            .iter()
+            .map(|&(param_id, value)| self.format(formatter, plugin, param_id, value).to_string())
+            .collect()
+    }
+
+    /// Drops every cached entry for `plugin`, e.g. after its parameter list
+    /// is rescanned and existing display text may no longer be valid.
+    pub fn invalidate_plugin(&mut self, plugin: PluginInstanceID) {
+        self.entries.retain(|key, _| key.plugin != plugin);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct CountingFormatter {
+        calls: Cell<u32>,
+    }
+
+    impl ParamValueFormatter for CountingFormatter {
+        fn format_param_value(&self, param_id: ParamID, value: f64) -> String {
+            self.calls.set(self.calls.get() + 1);
+            format!("p{}={value:.2}", param_id.0)
+        }
+    }
+
+    #[test]
+    fn repeated_identical_values_hit_the_cache() {
+        let formatter = CountingFormatter { calls: Cell::new(0) };
+        let mut cache = ParamDisplayCache::new();
+        let plugin = PluginInstanceID::new();
+
+        let a = cache.format(&formatter, plugin, ParamID(0), 0.5).to_string();
+        let b = cache.format(&formatter, plugin, ParamID(0), 0.5).to_string();
+        assert_eq!(a, b);
+        assert_eq!(formatter.calls.get(), 1);
+    }
+
+    #[test]
+    fn invalidating_a_plugin_forces_a_reformat() {
+        let formatter = CountingFormatter { calls: Cell::new(0) };
+        let mut cache = ParamDisplayCache::new();
+        let plugin = PluginInstanceID::new();
+
+        cache.format(&formatter, plugin, ParamID(0), 0.5);
+        cache.invalidate_plugin(plugin);
+        assert!(cache.is_empty());
+        cache.format(&formatter, plugin, ParamID(0), 0.5);
+        assert_eq!(formatter.calls.get(), 2);
+    }
+
+    #[test]
+    fn format_many_formats_every_pair_for_the_plugin() {
+        let formatter = CountingFormatter { calls: Cell::new(0) };
+        let mut cache = ParamDisplayCache::new();
+        let plugin = PluginInstanceID::new();
+
+        let texts = cache.format_many(&formatter, plugin, &[(ParamID(0), 0.1), (ParamID(1), 0.2)]);
+        assert_eq!(texts, vec!["p0=0.10", "p1=0.20"]);
+        assert_eq!(formatter.calls.get(), 2);
+    }
+}
@@ -0,0 +1,137 @@
+//! Realtime-safe parameter value readouts for host-side DSP.
+//!
+//! Host audio-thread code sometimes needs to key its own processing off a
+//! plugin's current effective parameter value (e.g. metering that reacts to
+//! a threshold parameter) without round-tripping through the main thread.
+//! A [`ParamReadout`] is a lock-free single-value mailbox for exactly that:
+//! the audio thread writes the plugin's effective value once per process
+//! block, and any number of readers (other audio-thread DSP, or a host
+//! callback) can poll the latest value without blocking.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::id::{ParamID, PluginInstanceID};
+
+/// A single lock-free parameter value, written by the audio thread and
+/// readable from any thread without blocking.
+#[derive(Debug)]
+pub struct ParamReadout {
+    bits: AtomicU64,
+}
+
+impl ParamReadout {
+    pub fn new(initial: f64) -> Self {
+        Self { bits: AtomicU64::new(initial.to_bits()) }
+    }
+
+    /// Publishes a new effective value. Intended to be called at most once
+    /// per process block from the audio thread.
+    pub fn write(&self, value: f64) {
+        self.bits.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Reads the most recently published value.
+    pub fn read(&self) -> f64 {
+        f64::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for ParamReadout {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    plugin: PluginInstanceID,
+    param: ParamID,
+}
+
+/// A registry of realtime-safe parameter readouts, one per plugin
+/// parameter the host has asked to track.
+#[derive(Debug, Default)]
+pub struct ParamReadoutTable {
+    entries: HashMap<Key, Arc<ParamReadout>>,
+}
+
+impl ParamReadoutTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a parameter for readout, returning a shared handle that
+    /// the audio thread keeps to publish new values to it. Calling this
+    /// again for an already-registered parameter returns the existing
+    /// handle rather than resetting it.
+    pub fn register(&mut self, plugin: PluginInstanceID, param: ParamID, initial: f64) -> Arc<ParamReadout> {
+        self.entries.entry(Key { plugin, param }).or_insert_with(|| Arc::new(ParamReadout::new(initial))).clone()
+    }
+
+    /// Reads the latest published value for a parameter without touching
+    /// the main thread. Returns `None` if the parameter was never
+    /// registered.
+    pub fn read(&self, plugin: PluginInstanceID, param: ParamID) -> Option<f64> {
+        self.entries.get(&Key { plugin, param }).map(|readout| readout.read())
+    }
+
+    /// Drops every registered readout for a plugin, e.g. when it is removed
+    /// from the graph.
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.entries.retain(|key, _| key.plugin != plugin);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_latest_written_value() {
+        let readout = ParamReadout::new(0.0);
+        assert_eq!(readout.read(), 0.0);
+        readout.write(0.75);
+        assert_eq!(readout.read(), 0.75);
+    }
+
+    #[test]
+    fn registering_twice_returns_the_same_handle() {
+        let mut table = ParamReadoutTable::new();
+        let plugin = PluginInstanceID::new();
+
+        let a = table.register(plugin, ParamID(0), 0.0);
+        a.write(0.5);
+        let b = table.register(plugin, ParamID(0), 0.0);
+        assert_eq!(b.read(), 0.5);
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn reading_an_unregistered_parameter_returns_none() {
+        let table = ParamReadoutTable::new();
+        assert_eq!(table.read(PluginInstanceID::new(), ParamID(0)), None);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_only_its_readouts() {
+        let mut table = ParamReadoutTable::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        table.register(a, ParamID(0), 1.0);
+        table.register(b, ParamID(0), 2.0);
+
+        table.remove_plugin(a);
+        assert_eq!(table.read(a, ParamID(0)), None);
+        assert_eq!(table.read(b, ParamID(0)), Some(2.0));
+    }
+}
@@ -0,0 +1,169 @@
+//! Stable display order for plugin host UIs.
+//!
+//! Plugin hosts are stored keyed by [`PluginInstanceID`] in `HashMap`s
+//! throughout this crate (gain stages, dry/wet, bypass, ...), which makes
+//! no iteration-order guarantee. A host UI that lists plugins by iterating
+//! one of those tables directly would reshuffle every time an unrelated
+//! internal change touched the map. `PluginOrder` is the explicit list a
+//! host keeps alongside them instead: insertion order by default, with
+//! [`move_to`](PluginOrder::move_to) for user-driven reordering (e.g.
+//! dragging a track in a mixer), and [`from_saved_order`] to reproduce an
+//! exact ordering recorded in project save state.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+
+/// An ordered list of plugin instances with an `O(1)` index lookup.
+#[derive(Debug, Default)]
+pub struct PluginOrder {
+    order: Vec<PluginInstanceID>,
+    index_of: HashMap<PluginInstanceID, usize>,
+}
+
+impl PluginOrder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds an order from a previously-persisted list, e.g. the plugin
+    /// list stored in project save state, so a restored project's UI lists
+    /// match the order the user left them in.
+    pub fn from_saved_order(ids: impl IntoIterator<Item = PluginInstanceID>) -> Self {
+        let mut order = Self::new();
+        for id in ids {
+            order.insert(id);
+        }
+        order
+    }
+
+    /// Appends `plugin` to the end of the order, if it isn't already
+    /// present, and returns its index.
+    pub fn insert(&mut self, plugin: PluginInstanceID) -> usize {
+        if let Some(&index) = self.index_of.get(&plugin) {
+            return index;
+        }
+        let index = self.order.len();
+        self.order.push(plugin);
+        self.index_of.insert(plugin, index);
+        index
+    }
+
+    /// Removes `plugin` from the order, shifting every later entry's index
+    /// down by one to stay contiguous.
+    pub fn remove(&mut self, plugin: PluginInstanceID) {
+        let Some(index) = self.index_of.remove(&plugin) else { return };
+        self.order.remove(index);
+        for (later_plugin, later_index) in self.index_of.iter_mut() {
+            if *later_index > index {
+                *later_index -= 1;
+            }
+            debug_assert_ne!(*later_plugin, plugin);
+        }
+    }
+
+    /// The stable index of `plugin` in the current order, if it's present.
+    pub fn index_of(&self, plugin: PluginInstanceID) -> Option<usize> {
+        self.index_of.get(&plugin).copied()
+    }
+
+    /// Moves `plugin` to `new_index`, shifting everything between its old
+    /// and new position over by one. `new_index` is clamped to the valid
+    /// range. Does nothing if `plugin` isn't present.
+    pub fn move_to(&mut self, plugin: PluginInstanceID, new_index: usize) {
+        let Some(old_index) = self.index_of(plugin) else { return };
+        let new_index = new_index.min(self.order.len() - 1);
+        if old_index == new_index {
+            return;
+        }
+        self.order.remove(old_index);
+        self.order.insert(new_index, plugin);
+        for (index, &id) in self.order.iter().enumerate() {
+            self.index_of.insert(id, index);
+        }
+    }
+
+    /// The full order, for iterating in a host UI list or persisting into
+    /// project save state.
+    pub fn order(&self) -> &[PluginInstanceID] {
+        &self.order
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugins_iterate_in_insertion_order() {
+        let mut order = PluginOrder::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        order.insert(a);
+        order.insert(b);
+        order.insert(c);
+        assert_eq!(order.order(), &[a, b, c]);
+        assert_eq!(order.index_of(b), Some(1));
+    }
+
+    #[test]
+    fn inserting_the_same_plugin_twice_keeps_its_original_index() {
+        let mut order = PluginOrder::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        order.insert(a);
+        order.insert(b);
+        assert_eq!(order.insert(a), 0);
+        assert_eq!(order.len(), 2);
+    }
+
+    #[test]
+    fn removing_a_plugin_shifts_later_indices_down() {
+        let mut order = PluginOrder::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        order.insert(a);
+        order.insert(b);
+        order.insert(c);
+
+        order.remove(a);
+        assert_eq!(order.order(), &[b, c]);
+        assert_eq!(order.index_of(b), Some(0));
+        assert_eq!(order.index_of(c), Some(1));
+        assert_eq!(order.index_of(a), None);
+    }
+
+    #[test]
+    fn move_to_reorders_without_losing_any_entries() {
+        let mut order = PluginOrder::new();
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let c = PluginInstanceID::new();
+        order.insert(a);
+        order.insert(b);
+        order.insert(c);
+
+        order.move_to(c, 0);
+        assert_eq!(order.order(), &[c, a, b]);
+        assert_eq!(order.index_of(c), Some(0));
+        assert_eq!(order.index_of(a), Some(1));
+    }
+
+    #[test]
+    fn from_saved_order_reproduces_the_exact_persisted_sequence() {
+        let a = PluginInstanceID::new();
+        let b = PluginInstanceID::new();
+        let restored = PluginOrder::from_saved_order(vec![b, a]);
+        assert_eq!(restored.order(), &[b, a]);
+    }
+}
@@ -0,0 +1,104 @@
+//! CLAP remote-controls (formerly quick-controls) extension support.
+//!
+//! A plugin groups its parameters into named pages of up to 8 knobs each,
+//! for a hardware controller with a fixed number of physical knobs to page
+//! through. Queried once at activation and again whenever the plugin
+//! reports its pages changed (e.g. after a preset load), never derived from
+//! the audio thread.
+
+use std::collections::HashMap;
+
+use crate::id::{ParamID, PluginInstanceID};
+
+/// The fixed number of parameter slots per remote-controls page, matching
+/// CLAP's `CLAP_REMOTE_CONTROLS_COUNT`.
+pub const REMOTE_CONTROL_PAGE_SIZE: usize = 8;
+
+/// One page of mapped parameters. A slot is `None` when the plugin leaves
+/// it unmapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteControlPage {
+    pub name: String,
+    pub param_ids: [Option<ParamID>; REMOTE_CONTROL_PAGE_SIZE],
+}
+
+/// Per-plugin remote-controls pages, replaced wholesale whenever the plugin
+/// reports a change.
+#[derive(Debug, Default)]
+pub struct RemoteControlPagesTable {
+    pages: HashMap<PluginInstanceID, Vec<RemoteControlPage>>,
+}
+
+impl RemoteControlPagesTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces `plugin`'s pages, returning whether they actually differ
+    /// from what was cached before, so the host's idle loop knows whether
+    /// to emit its own pages-changed notification event.
+    pub fn set_pages(&mut self, plugin: PluginInstanceID, pages: Vec<RemoteControlPage>) -> bool {
+        let changed = self.pages.get(&plugin) != Some(&pages);
+        self.pages.insert(plugin, pages);
+        changed
+    }
+
+    /// The pages currently cached for `plugin`, in the plugin-provided
+    /// order. Empty if the plugin hasn't reported any (or doesn't support
+    /// the extension).
+    pub fn remote_control_pages(&self, plugin: PluginInstanceID) -> &[RemoteControlPage] {
+        self.pages.get(&plugin).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.pages.remove(&plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(name: &str, first_param: u32) -> RemoteControlPage {
+        let mut param_ids = [None; REMOTE_CONTROL_PAGE_SIZE];
+        param_ids[0] = Some(ParamID(first_param));
+        RemoteControlPage { name: name.to_string(), param_ids }
+    }
+
+    #[test]
+    fn a_plugin_with_no_reported_pages_has_an_empty_list() {
+        let table = RemoteControlPagesTable::new();
+        assert!(table.remote_control_pages(PluginInstanceID::new()).is_empty());
+    }
+
+    #[test]
+    fn setting_pages_makes_them_queryable() {
+        let mut table = RemoteControlPagesTable::new();
+        let plugin = PluginInstanceID::new();
+        table.set_pages(plugin, vec![page("Filter", 0), page("Envelope", 4)]);
+
+        let pages = table.remote_control_pages(plugin);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].name, "Filter");
+        assert_eq!(pages[1].param_ids[0], Some(ParamID(4)));
+    }
+
+    #[test]
+    fn set_pages_reports_whether_anything_actually_changed() {
+        let mut table = RemoteControlPagesTable::new();
+        let plugin = PluginInstanceID::new();
+
+        assert!(table.set_pages(plugin, vec![page("Filter", 0)]), "first report is always a change");
+        assert!(!table.set_pages(plugin, vec![page("Filter", 0)]), "identical pages shouldn't be flagged as changed");
+        assert!(table.set_pages(plugin, vec![page("Envelope", 4)]), "different pages should be flagged as changed");
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_pages() {
+        let mut table = RemoteControlPagesTable::new();
+        let plugin = PluginInstanceID::new();
+        table.set_pages(plugin, vec![page("Filter", 0)]);
+        table.remove_plugin(plugin);
+        assert!(table.remote_control_pages(plugin).is_empty());
+    }
+}
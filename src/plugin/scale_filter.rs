@@ -0,0 +1,165 @@
+//! Chord/scale-constrained note filtering.
+//!
+//! An internal note-effect that remaps incoming note events to the nearest
+//! note in a configured key/scale, for live hosts that want "can't play a
+//! wrong note" assistance without a full MIDI effect plugin.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+use crate::plugin::event_quantizer::NoteEvent;
+
+/// A set of scale degrees, as semitone offsets from the root, used to
+/// constrain incoming notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    Chromatic,
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MajorPentatonic,
+    MinorPentatonic,
+}
+
+impl Scale {
+    /// Semitone offsets from the root that belong to this scale.
+    fn intervals(self) -> &'static [i32] {
+        match self {
+            Scale::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Scale::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => &[0, 3, 5, 7, 10],
+        }
+    }
+}
+
+/// Per-plugin key/scale constraint settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaleFilterSettings {
+    /// The root note's pitch class, `0` (C) through `11` (B).
+    pub root: i32,
+    pub scale: Scale,
+}
+
+impl ScaleFilterSettings {
+    pub fn new(root: i32, scale: Scale) -> Self {
+        assert!((0..12).contains(&root));
+        Self { root, scale }
+    }
+}
+
+/// Snaps `note_id` to the nearest note allowed by `settings`, preferring the
+/// lower of two equidistant candidates. Every MIDI note ID has a match
+/// within a search radius of 6 semitones, since an octave always contains
+/// at least one degree of any non-empty scale.
+fn nearest_in_scale(note_id: i32, settings: &ScaleFilterSettings) -> i32 {
+    let intervals = settings.scale.intervals();
+    let is_allowed = |note: i32| intervals.contains(&(note - settings.root).rem_euclid(12));
+    if is_allowed(note_id) {
+        return note_id;
+    }
+    for distance in 1..=6 {
+        if is_allowed(note_id - distance) {
+            return note_id - distance;
+        }
+        if is_allowed(note_id + distance) {
+            return note_id + distance;
+        }
+    }
+    note_id
+}
+
+/// Per-plugin chord/scale note filter.
+#[derive(Debug, Default)]
+pub struct ScaleFilter {
+    settings: HashMap<PluginInstanceID, ScaleFilterSettings>,
+}
+
+impl ScaleFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_settings(&mut self, plugin: PluginInstanceID, settings: ScaleFilterSettings) {
+        self.settings.insert(plugin, settings);
+    }
+
+    pub fn clear_settings(&mut self, plugin: PluginInstanceID) {
+        self.settings.remove(&plugin);
+    }
+
+    /// Remaps `events` arriving at `plugin` to the nearest note in its
+    /// configured scale. Returns `events` unchanged if `plugin` has no
+    /// settings configured.
+    pub fn filter(&self, plugin: PluginInstanceID, events: &[NoteEvent]) -> Vec<NoteEvent> {
+        let Some(settings) = self.settings.get(&plugin) else {
+            return events.to_vec();
+        };
+        events.iter().map(|event| NoteEvent { note_id: nearest_in_scale(event.note_id, settings), ..*event }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(note_id: i32) -> NoteEvent {
+        NoteEvent { sample_offset: 0, note_id, velocity: 1.0 }
+    }
+
+    #[test]
+    fn an_unconfigured_plugin_passes_events_through_unmodified() {
+        let filter = ScaleFilter::new();
+        let events = [note(61)];
+        assert_eq!(filter.filter(PluginInstanceID::new(), &events), events);
+    }
+
+    #[test]
+    fn a_note_already_in_the_scale_is_unchanged() {
+        let mut filter = ScaleFilter::new();
+        let plugin = PluginInstanceID::new();
+        filter.set_settings(plugin, ScaleFilterSettings::new(0, Scale::Major));
+        // 64 = E, a degree of C major.
+        assert_eq!(filter.filter(plugin, &[note(64)]), vec![note(64)]);
+    }
+
+    #[test]
+    fn an_out_of_scale_note_snaps_to_the_nearest_degree() {
+        let mut filter = ScaleFilter::new();
+        let plugin = PluginInstanceID::new();
+        filter.set_settings(plugin, ScaleFilterSettings::new(0, Scale::Major));
+        // 61 = C#, equidistant... actually nearest is 60 (C) at distance 1
+        // vs 62 (D) at distance 1; the lower candidate wins ties.
+        assert_eq!(filter.filter(plugin, &[note(61)]), vec![note(60)]);
+    }
+
+    #[test]
+    fn transposing_the_root_shifts_the_allowed_set() {
+        let mut filter = ScaleFilter::new();
+        let plugin = PluginInstanceID::new();
+        filter.set_settings(plugin, ScaleFilterSettings::new(2, Scale::Major)); // D major
+        // 61 = C#, a degree of D major (the major third).
+        assert_eq!(filter.filter(plugin, &[note(61)]), vec![note(61)]);
+    }
+
+    #[test]
+    fn chromatic_scale_allows_every_note() {
+        let mut filter = ScaleFilter::new();
+        let plugin = PluginInstanceID::new();
+        filter.set_settings(plugin, ScaleFilterSettings::new(0, Scale::Chromatic));
+        for id in 48..72 {
+            assert_eq!(filter.filter(plugin, &[note(id)]), vec![note(id)]);
+        }
+    }
+
+    #[test]
+    fn clearing_settings_restores_pass_through() {
+        let mut filter = ScaleFilter::new();
+        let plugin = PluginInstanceID::new();
+        filter.set_settings(plugin, ScaleFilterSettings::new(0, Scale::MinorPentatonic));
+        filter.clear_settings(plugin);
+        assert_eq!(filter.filter(plugin, &[note(61)]), vec![note(61)]);
+    }
+}
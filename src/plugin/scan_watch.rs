@@ -0,0 +1,144 @@
+//! Turning filesystem changes under scanned plugin directories into an
+//! idle-pollable event, instead of the host polling [`PluginScanner::rescan_dir`]
+//! on a timer.
+//!
+//! The queue ([`ScanEventQueue`]) has no dependency on how directories are
+//! actually watched, so it can be exercised and used on its own. The
+//! platform watcher that feeds it ([`FolderWatcher`]) is behind the
+//! `watch-folders` feature, which pulls in the `notify` crate for the
+//! platform's native filesystem notification API.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::plugin::scanner::PluginScanDiff;
+
+/// One batch of plugins added to/removed from a watched directory, queued
+/// for the host's idle loop to pick up and react to (e.g. by refreshing a
+/// plugin browser).
+pub type PluginScannerEvent = PluginScanDiff;
+
+/// A bounded queue of pending scan events, filled by whatever is watching
+/// the scan directories and drained by the host's main-thread idle loop.
+/// Oldest-first; past capacity the oldest pending event is dropped in favor
+/// of the new one, since idle events are meant to be drained promptly and a
+/// host that falls behind only needs to know *that* something changed.
+#[derive(Debug)]
+pub struct ScanEventQueue {
+    capacity: usize,
+    events: Mutex<VecDeque<PluginScannerEvent>>,
+}
+
+impl ScanEventQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, events: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Queues `event`, unless it's empty (nothing actually changed).
+    pub fn push(&self, event: PluginScannerEvent) {
+        if event.is_empty() {
+            return;
+        }
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Called from the host's idle loop to pop the oldest pending event, if
+    /// any.
+    pub fn poll(&self) -> Option<PluginScannerEvent> {
+        self.events.lock().unwrap().pop_front()
+    }
+}
+
+#[cfg(feature = "watch-folders")]
+mod watcher {
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use notify::{RecursiveMode, Watcher};
+
+    use super::ScanEventQueue;
+    use crate::plugin::scanner::PluginScanner;
+
+    /// Watches a fixed set of directories non-recursively, rescanning
+    /// whichever one a filesystem event fires under and pushing the
+    /// resulting diff onto `queue`.
+    ///
+    /// Holds the platform watcher alive for as long as this is kept
+    /// around; dropping it stops the watch.
+    pub struct FolderWatcher {
+        _watcher: notify::RecommendedWatcher,
+    }
+
+    impl FolderWatcher {
+        pub fn new(
+            dirs: Vec<PathBuf>,
+            scanner: Arc<Mutex<PluginScanner>>,
+            queue: Arc<ScanEventQueue>,
+        ) -> notify::Result<Self> {
+            let watched = dirs.clone();
+            let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else { return };
+                let mut scanner = scanner.lock().unwrap();
+                for dir in watched.iter().filter(|dir| event.paths.iter().any(|path| path.parent() == Some(dir))) {
+                    if let Ok(diff) = scanner.rescan_dir(dir) {
+                        queue.push(diff);
+                    }
+                }
+            })?;
+            for dir in &dirs {
+                watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            }
+            Ok(Self { _watcher: watcher })
+        }
+    }
+}
+
+#[cfg(feature = "watch-folders")]
+pub use watcher::FolderWatcher;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::scanner::{PluginDescriptor, PluginFormat, PluginKey};
+    use std::path::PathBuf;
+
+    fn descriptor(name: &str) -> PluginDescriptor {
+        PluginDescriptor {
+            key: PluginKey { format: PluginFormat::Clap, id: format!("com.example.{name}") },
+            name: name.to_string(),
+            path: PathBuf::from(format!("/plugins/{name}.clap")),
+        }
+    }
+
+    #[test]
+    fn an_empty_diff_is_not_queued() {
+        let queue = ScanEventQueue::new(4);
+        queue.push(PluginScanDiff::default());
+        assert!(queue.poll().is_none());
+    }
+
+    #[test]
+    fn a_nonempty_diff_is_queued_and_drained_in_order() {
+        let queue = ScanEventQueue::new(4);
+        queue.push(PluginScanDiff { added: vec![descriptor("synth")], removed: vec![] });
+        queue.push(PluginScanDiff { added: vec![descriptor("delay")], removed: vec![] });
+
+        assert_eq!(queue.poll().unwrap().added[0].name, "synth");
+        assert_eq!(queue.poll().unwrap().added[0].name, "delay");
+        assert!(queue.poll().is_none());
+    }
+
+    #[test]
+    fn pushing_past_capacity_drops_the_oldest_pending_event() {
+        let queue = ScanEventQueue::new(1);
+        queue.push(PluginScanDiff { added: vec![descriptor("synth")], removed: vec![] });
+        queue.push(PluginScanDiff { added: vec![descriptor("delay")], removed: vec![] });
+
+        assert_eq!(queue.poll().unwrap().added[0].name, "delay");
+        assert!(queue.poll().is_none());
+    }
+}
@@ -0,0 +1,325 @@
+//! Discovering installed plugins on disk.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Which plugin format a scanned bundle is hosted through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginFormat {
+    Clap,
+    /// Only ever produced by [`PluginScanner::scan_dir`] when the `vst3`
+    /// feature is enabled.
+    Vst3,
+    /// Only ever produced by [`PluginScanner::scan_dir`] when the `lv2`
+    /// feature is enabled.
+    Lv2,
+}
+
+/// Identifies a scanned plugin by its declared ID within a specific
+/// format, since the same reverse-DNS-style ID can be reused independently
+/// by a CLAP build and a VST3 build of the same plugin.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PluginKey {
+    pub format: PluginFormat,
+    pub id: String,
+}
+
+/// Basic identifying information about a plugin found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginDescriptor {
+    pub key: PluginKey,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Scans directories for plugin bundles and caches the results by
+/// [`PluginKey`], so a rescan only needs to touch paths that changed.
+///
+/// This is deliberately shareable (behind an `Arc<Mutex<_>>` at the call
+/// site) so that multiple engines/projects open in the same process don't
+/// each pay the cost of scanning the same plugin directories.
+#[derive(Debug, Default)]
+pub struct PluginScanner {
+    by_key: HashMap<PluginKey, PluginDescriptor>,
+    /// The keys found in each directory as of its last [`rescan_dir`](Self::rescan_dir)
+    /// call, so the next call can tell what's been added or removed.
+    dir_keys: HashMap<PathBuf, HashSet<PluginKey>>,
+    /// When a plugin is requested by ID and isn't available in the
+    /// requested format, fall back to any other scanned format that has
+    /// it instead of reporting it missing.
+    fallback_to_other_formats: bool,
+}
+
+/// What changed in a [`PluginScanner`]'s cache as a result of a
+/// [`PluginScanner::rescan_dir`] call.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PluginScanDiff {
+    pub added: Vec<PluginDescriptor>,
+    pub removed: Vec<PluginKey>,
+}
+
+impl PluginScanDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl PluginScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_fallback_to_other_formats(&mut self, fallback: bool) {
+        self.fallback_to_other_formats = fallback;
+    }
+
+    /// Scans `dir` (non-recursively) for `.clap` bundles (and `.vst3`
+    /// bundles, when the `vst3` feature is enabled, and `.lv2` bundle
+    /// directories, when the `lv2` feature is enabled), adding/replacing
+    /// entries in the cache for each one found. Returns the descriptors
+    /// discovered in this call.
+    pub fn scan_dir(&mut self, dir: &Path) -> std::io::Result<Vec<PluginDescriptor>> {
+        let mut found = Vec::new();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(found),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let Some(format) = format_for_entry(&path) else {
+                continue;
+            };
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown").to_string();
+            // The real plugin entry point would be queried here for its
+            // declared ID; a reverse-DNS-style placeholder keyed by
+            // format keeps the cache keyed consistently until real bundle
+            // introspection lands.
+            let id = format!("com.dropseed.scanned.{name}");
+            let key = PluginKey { format, id };
+            let descriptor = PluginDescriptor { key: key.clone(), name, path };
+            self.by_key.insert(key, descriptor.clone());
+            found.push(descriptor);
+        }
+        Ok(found)
+    }
+
+    /// Like [`scan_dir`](Self::scan_dir), but also removes cache entries
+    /// for plugins that were found in `dir` on a previous call and have
+    /// since disappeared from it, and reports both sides of the change
+    /// instead of only the additions. This is what an automatic rescan
+    /// (e.g. triggered by watching `dir` for filesystem events) should
+    /// call instead of `scan_dir`, since `scan_dir` alone never shrinks
+    /// the cache.
+    pub fn rescan_dir(&mut self, dir: &Path) -> std::io::Result<PluginScanDiff> {
+        let found = self.scan_dir(dir)?;
+        let current_keys: HashSet<PluginKey> = found.iter().map(|d| d.key.clone()).collect();
+        let previous_keys = self.dir_keys.insert(dir.to_path_buf(), current_keys.clone()).unwrap_or_default();
+
+        let removed: Vec<PluginKey> = previous_keys.difference(&current_keys).cloned().collect();
+        for key in &removed {
+            self.by_key.remove(key);
+        }
+        let added = found.into_iter().filter(|d| !previous_keys.contains(&d.key)).collect();
+
+        Ok(PluginScanDiff { added, removed })
+    }
+
+    pub fn get(&self, key: &PluginKey) -> Option<&PluginDescriptor> {
+        self.by_key.get(key)
+    }
+
+    /// Looks up a plugin by ID, preferring `preferred_format`. If it isn't
+    /// available in that format and [`set_fallback_to_other_formats`] has
+    /// been enabled, returns any other scanned format with the same ID
+    /// instead of reporting it missing.
+    ///
+    /// [`set_fallback_to_other_formats`]: PluginScanner::set_fallback_to_other_formats
+    pub fn get_preferring(&self, id: &str, preferred_format: PluginFormat) -> Option<&PluginDescriptor> {
+        let preferred = PluginKey { format: preferred_format, id: id.to_string() };
+        if let Some(descriptor) = self.by_key.get(&preferred) {
+            return Some(descriptor);
+        }
+        if !self.fallback_to_other_formats {
+            return None;
+        }
+        self.by_key.values().find(|descriptor| descriptor.key.id == id)
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &PluginDescriptor> {
+        self.by_key.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_key.is_empty()
+    }
+
+    /// Scans every directory returned by [`default_lv2_search_paths`] and
+    /// merges the results into the cache, ignoring individual directories
+    /// that don't exist. Only finds anything when the `lv2` feature is
+    /// enabled.
+    pub fn scan_default_lv2_paths(&mut self) -> std::io::Result<Vec<PluginDescriptor>> {
+        let mut found = Vec::new();
+        for dir in default_lv2_search_paths() {
+            found.extend(self.scan_dir(&dir)?);
+        }
+        Ok(found)
+    }
+}
+
+fn format_for_entry(path: &Path) -> Option<PluginFormat> {
+    let extension = path.extension().and_then(|e| e.to_str());
+    match extension {
+        Some("clap") => Some(PluginFormat::Clap),
+        #[cfg(feature = "vst3")]
+        Some("vst3") => Some(PluginFormat::Vst3),
+        #[cfg(feature = "lv2")]
+        Some("lv2") if path.is_dir() && path.join("manifest.ttl").is_file() => Some(PluginFormat::Lv2),
+        _ => None,
+    }
+}
+
+/// The conventional LV2 bundle search directories on Linux: `LV2_PATH` (if
+/// set, colon-separated, per the LV2 spec) followed by the standard system
+/// and per-user locations. Only meaningful when the `lv2` feature is
+/// enabled; [`PluginScanner::scan_dir`] otherwise won't recognize `.lv2`
+/// bundles found in them.
+#[cfg(target_os = "linux")]
+pub fn default_lv2_search_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(lv2_path) = std::env::var("LV2_PATH") {
+        paths.extend(std::env::split_paths(&lv2_path));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        paths.push(PathBuf::from(home).join(".lv2"));
+    }
+    paths.push(PathBuf::from("/usr/lib/lv2"));
+    paths.push(PathBuf::from("/usr/local/lib/lv2"));
+    paths
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn default_lv2_search_paths() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_clap_bundles_and_ignores_other_files() {
+        let dir = std::env::temp_dir().join("dropseed_scanner_test_clap");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("synth.clap"), b"").unwrap();
+        std::fs::write(dir.join("readme.txt"), b"").unwrap();
+
+        let mut scanner = PluginScanner::new();
+        let found = scanner.scan_dir(&dir).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "synth");
+        assert_eq!(found[0].key.format, PluginFormat::Clap);
+        assert_eq!(scanner.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn rescan_reports_plugins_added_and_removed_since_the_last_rescan() {
+        let dir = std::env::temp_dir().join("dropseed_scanner_test_rescan");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("synth.clap"), b"").unwrap();
+
+        let mut scanner = PluginScanner::new();
+        let first = scanner.rescan_dir(&dir).unwrap();
+        assert_eq!(first.added.len(), 1);
+        assert!(first.removed.is_empty());
+
+        std::fs::remove_file(dir.join("synth.clap")).unwrap();
+        std::fs::write(dir.join("delay.clap"), b"").unwrap();
+        let second = scanner.rescan_dir(&dir).unwrap();
+        assert_eq!(second.added.len(), 1);
+        assert_eq!(second.added[0].name, "delay");
+        assert_eq!(second.removed.len(), 1);
+        assert_eq!(second.removed[0].id, first.added[0].key.id);
+        assert_eq!(scanner.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn missing_directory_scans_to_empty_without_erroring() {
+        let mut scanner = PluginScanner::new();
+        let found = scanner.scan_dir(Path::new("/nonexistent/dropseed/path")).unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn without_fallback_a_missing_format_is_not_found() {
+        let mut scanner = PluginScanner::new();
+        scanner.by_key.insert(
+            PluginKey { format: PluginFormat::Clap, id: "com.example.synth".to_string() },
+            PluginDescriptor {
+                key: PluginKey { format: PluginFormat::Clap, id: "com.example.synth".to_string() },
+                name: "synth".to_string(),
+                path: PathBuf::from("/plugins/synth.clap"),
+            },
+        );
+
+        assert!(scanner.get_preferring("com.example.synth", PluginFormat::Vst3).is_none());
+    }
+
+    #[test]
+    fn with_fallback_enabled_a_missing_format_falls_back_to_another() {
+        let mut scanner = PluginScanner::new();
+        scanner.set_fallback_to_other_formats(true);
+        scanner.by_key.insert(
+            PluginKey { format: PluginFormat::Clap, id: "com.example.synth".to_string() },
+            PluginDescriptor {
+                key: PluginKey { format: PluginFormat::Clap, id: "com.example.synth".to_string() },
+                name: "synth".to_string(),
+                path: PathBuf::from("/plugins/synth.clap"),
+            },
+        );
+
+        let found = scanner.get_preferring("com.example.synth", PluginFormat::Vst3).unwrap();
+        assert_eq!(found.key.format, PluginFormat::Clap);
+    }
+
+    #[cfg(feature = "lv2")]
+    #[test]
+    fn scans_lv2_bundle_directories_with_a_manifest() {
+        let dir = std::env::temp_dir().join("dropseed_scanner_test_lv2");
+        std::fs::create_dir_all(&dir).unwrap();
+        let bundle = dir.join("reverb.lv2");
+        std::fs::create_dir_all(&bundle).unwrap();
+        std::fs::write(bundle.join("manifest.ttl"), b"").unwrap();
+        // A bare `.lv2`-suffixed directory with no manifest isn't a valid
+        // bundle and should be skipped.
+        std::fs::create_dir_all(dir.join("not_a_bundle.lv2")).unwrap();
+
+        let mut scanner = PluginScanner::new();
+        let found = scanner.scan_dir(&dir).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "reverb");
+        assert_eq!(found[0].key.format, PluginFormat::Lv2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(feature = "lv2")]
+    #[test]
+    fn default_lv2_search_paths_includes_lv2_path_entries() {
+        std::env::set_var("LV2_PATH", "/tmp/a/lv2:/tmp/b/lv2");
+        let paths = default_lv2_search_paths();
+        std::env::remove_var("LV2_PATH");
+
+        assert!(paths.contains(&PathBuf::from("/tmp/a/lv2")));
+        assert!(paths.contains(&PathBuf::from("/tmp/b/lv2")));
+    }
+}
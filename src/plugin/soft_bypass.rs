@@ -0,0 +1,109 @@
+//! Soft bypass: preferring a plugin's own CLAP bypass parameter over the
+//! host-side dry/wet crossfade.
+//!
+//! [`PluginDryWetStages`] gives every plugin a host-run bypass regardless of
+//! what it implements itself, but a plugin that declares a parameter with
+//! CLAP's `CLAP_PARAM_IS_BYPASS` flag is saying it can bypass itself
+//! latency-correctly (e.g. routing around internal look-ahead delay rather
+//! than just crossfading around it). [`toggle_plugin_bypass`] is the single
+//! entry point a host calls either way: it prefers the plugin's own
+//! parameter when one is declared, and falls back to the host crossfade
+//! otherwise.
+
+use crate::id::{ParamID, PluginInstanceID};
+
+use super::param_cookie::{EventParamValue, ParamCookieCache};
+use super::param_diff::ParamInfo;
+use super::dry_wet::PluginDryWetStages;
+
+/// How a plugin's bypass is implemented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BypassMethod {
+    /// The plugin declared no bypass parameter; [`PluginDryWetStages`]
+    /// crossfades around it instead.
+    HostCrossfade,
+    /// The plugin declared this parameter with `CLAP_PARAM_IS_BYPASS`; the
+    /// host toggles it directly rather than crossfading.
+    PluginParameter(ParamID),
+}
+
+/// Picks the bypass method for a plugin from its declared parameter list:
+/// the first parameter flagged `is_bypass`, if any, otherwise the host
+/// crossfade.
+pub fn bypass_method(params: &[ParamInfo]) -> BypassMethod {
+    match params.iter().find(|param| param.is_bypass) {
+        Some(param) => BypassMethod::PluginParameter(param.id),
+        None => BypassMethod::HostCrossfade,
+    }
+}
+
+/// Toggles `plugin`'s bypass state, preferring its own bypass parameter
+/// (per [`bypass_method`]) over the host crossfade in `dry_wet`.
+///
+/// Returns the param-value event the host must send down to the plugin when
+/// the plugin owns its own bypass; returns `None` when the host crossfade in
+/// `dry_wet` was engaged directly and there is nothing further to send.
+pub fn toggle_plugin_bypass(
+    plugin: PluginInstanceID,
+    bypassed: bool,
+    params: &[ParamInfo],
+    dry_wet: &mut PluginDryWetStages,
+    cookies: &ParamCookieCache,
+) -> Option<EventParamValue> {
+    match bypass_method(params) {
+        BypassMethod::PluginParameter(param_id) => {
+            // CLAP's bypass parameter convention: a value of `1.0` means
+            // bypassed, `0.0` means active.
+            Some(cookies.build_event(plugin, param_id, if bypassed { 1.0 } else { 0.0 }))
+        }
+        BypassMethod::HostCrossfade => {
+            dry_wet.set_bypassed(plugin, bypassed);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(id: u32, is_bypass: bool) -> ParamInfo {
+        ParamInfo { id: ParamID(id), name: "Bypass".to_string(), min: 0.0, max: 1.0, default: 0.0, is_bypass }
+    }
+
+    #[test]
+    fn a_plugin_with_no_bypass_param_uses_the_host_crossfade() {
+        let params = vec![param(0, false)];
+        assert_eq!(bypass_method(&params), BypassMethod::HostCrossfade);
+    }
+
+    #[test]
+    fn a_plugin_with_a_bypass_param_is_preferred_over_the_host_crossfade() {
+        let params = vec![param(0, false), param(1, true)];
+        assert_eq!(bypass_method(&params), BypassMethod::PluginParameter(ParamID(1)));
+    }
+
+    #[test]
+    fn toggling_bypass_without_a_plugin_param_engages_the_host_crossfade() {
+        let plugin = PluginInstanceID::new();
+        let mut dry_wet = PluginDryWetStages::new(8);
+        let cookies = ParamCookieCache::new();
+
+        let event = toggle_plugin_bypass(plugin, true, &[], &mut dry_wet, &cookies);
+        assert!(event.is_none());
+        assert!(dry_wet.is_bypassed(plugin));
+    }
+
+    #[test]
+    fn toggling_bypass_with_a_plugin_param_sends_an_event_instead_of_touching_the_host_crossfade() {
+        let plugin = PluginInstanceID::new();
+        let mut dry_wet = PluginDryWetStages::new(8);
+        let cookies = ParamCookieCache::new();
+        let params = vec![param(3, true)];
+
+        let event = toggle_plugin_bypass(plugin, true, &params, &mut dry_wet, &cookies).unwrap();
+        assert_eq!(event.param_id, ParamID(3));
+        assert_eq!(event.value, 1.0);
+        assert!(!dry_wet.is_bypassed(plugin), "plugin owns its own bypass; host crossfade should stay untouched");
+    }
+}
@@ -0,0 +1,65 @@
+//! Versioned plugin save-state container.
+//!
+//! A plugin's raw save data (the CLAP `clap_plugin_state` blob, or an
+//! internal plugin's own serialization) is opaque to the host, but the
+//! *wrapper* around it is ours, so it carries an explicit schema version.
+//! That lets a future dropseed release recognize and migrate state saved by
+//! an older one instead of failing to load a downstream host's existing
+//! projects.
+
+use crate::util::versioned_migrations::{MigrationGap, MigrationRegistry};
+
+/// The current version written by this build of dropseed for
+/// [`DSPluginSaveState`].
+pub const CURRENT_PLUGIN_STATE_VERSION: u32 = 1;
+
+/// A plugin's saved state, tagged with the schema version it was written
+/// under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DSPluginSaveState {
+    pub version: u32,
+    /// The plugin's own opaque state blob; dropseed does not interpret its
+    /// contents.
+    pub data: Vec<u8>,
+}
+
+impl DSPluginSaveState {
+    /// Wraps `data` at the current schema version.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { version: CURRENT_PLUGIN_STATE_VERSION, data }
+    }
+
+    /// Migrates this state's wrapper up to `CURRENT_PLUGIN_STATE_VERSION`
+    /// using `registry`, returning the migration gap if one is missing.
+    /// A state already at the current version is returned unchanged.
+    pub fn migrate(self, registry: &MigrationRegistry<Vec<u8>>) -> Result<Self, MigrationGap> {
+        let version = self.version;
+        let data = registry.migrate(self.data, version, CURRENT_PLUGIN_STATE_VERSION)?;
+        Ok(Self { version: CURRENT_PLUGIN_STATE_VERSION, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_state_is_tagged_with_the_current_version() {
+        let state = DSPluginSaveState::new(vec![1, 2, 3]);
+        assert_eq!(state.version, CURRENT_PLUGIN_STATE_VERSION);
+    }
+
+    #[test]
+    fn migrating_an_older_state_updates_its_version_and_data() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(0, |mut data: Vec<u8>| {
+            data.push(0xFF);
+            data
+        });
+
+        let old = DSPluginSaveState { version: 0, data: vec![1, 2, 3] };
+        let migrated = old.migrate(&registry).unwrap();
+        assert_eq!(migrated.version, CURRENT_PLUGIN_STATE_VERSION);
+        assert_eq!(migrated.data, vec![1, 2, 3, 0xFF]);
+    }
+}
@@ -0,0 +1,111 @@
+//! CLAP `track-info` host extension: lets the host label a plugin instance
+//! with the track it belongs to, so plugins like channel strips can
+//! auto-display the track name instead of asking the user to type it twice.
+
+use std::collections::HashMap;
+
+use crate::id::PluginInstanceID;
+
+/// What kind of track a plugin instance belongs to, matching CLAP's
+/// `CLAP_TRACK_INFO_*` flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrackInfoFlags {
+    /// This is a return track (an effects-send destination).
+    pub is_for_return_track: bool,
+    /// This is a bus (a submix of other tracks), not a single source track.
+    pub is_for_bus: bool,
+    /// This is the master/main output track.
+    pub is_for_master: bool,
+}
+
+/// The track metadata a host reports for one plugin instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackInfo {
+    pub name: String,
+    /// `[r, g, b, a]`, or `None` if the host assigns no color to this track.
+    pub color: Option<[u8; 4]>,
+    pub channel_count: u32,
+    pub flags: TrackInfoFlags,
+}
+
+/// Per-plugin track labels, set by the host and answered back to plugins
+/// that query the `track-info` extension.
+#[derive(Debug, Default)]
+pub struct TrackInfoTable {
+    info: HashMap<PluginInstanceID, TrackInfo>,
+}
+
+impl TrackInfoTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Labels `plugin` with `info`, overwriting any previous label. Returns
+    /// whether this changed anything the plugin might have already cached,
+    /// so the host knows whether to notify it via `track_info.changed()`.
+    pub fn set_track_info(&mut self, plugin: PluginInstanceID, info: TrackInfo) -> bool {
+        let changed = self.info.get(&plugin) != Some(&info);
+        self.info.insert(plugin, info);
+        changed
+    }
+
+    /// The track info for `plugin`, if the host has labeled it. This is
+    /// what answers the plugin's `track_info.get` query.
+    pub fn track_info(&self, plugin: PluginInstanceID) -> Option<&TrackInfo> {
+        self.info.get(&plugin)
+    }
+
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.info.remove(&plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(name: &str) -> TrackInfo {
+        TrackInfo { name: name.to_string(), color: Some([255, 0, 0, 255]), channel_count: 2, flags: TrackInfoFlags::default() }
+    }
+
+    #[test]
+    fn an_unlabeled_plugin_has_no_track_info() {
+        let table = TrackInfoTable::new();
+        assert_eq!(table.track_info(PluginInstanceID::new()), None);
+    }
+
+    #[test]
+    fn setting_track_info_makes_it_queryable() {
+        let mut table = TrackInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        table.set_track_info(plugin, track("Lead Synth"));
+        assert_eq!(table.track_info(plugin).unwrap().name, "Lead Synth");
+    }
+
+    #[test]
+    fn set_track_info_reports_whether_anything_changed() {
+        let mut table = TrackInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        assert!(table.set_track_info(plugin, track("Lead Synth")));
+        assert!(!table.set_track_info(plugin, track("Lead Synth")));
+        assert!(table.set_track_info(plugin, track("Renamed")));
+    }
+
+    #[test]
+    fn master_bus_flags_round_trip() {
+        let mut table = TrackInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        let flags = TrackInfoFlags { is_for_bus: true, is_for_master: true, is_for_return_track: false };
+        table.set_track_info(plugin, TrackInfo { name: "Master".into(), color: None, channel_count: 2, flags });
+        assert_eq!(table.track_info(plugin).unwrap().flags, flags);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_track_info() {
+        let mut table = TrackInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        table.set_track_info(plugin, track("Lead Synth"));
+        table.remove_plugin(plugin);
+        assert_eq!(table.track_info(plugin), None);
+    }
+}
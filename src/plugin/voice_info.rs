@@ -0,0 +1,163 @@
+//! Polyphony reporting via CLAP's `voice-info` extension.
+//!
+//! A plugin that implements `voice-info` can report how many voices it
+//! currently has active and its hard ceiling on concurrent voices, which a
+//! host uses for polyphony displays and to decide whether polyphonic
+//! modulation has somewhere to go. The count changes far more often than
+//! the capacity (every note-on/off vs. only on a preset/settings change), so
+//! this is the same lock-free single-value mailbox shape as
+//! [`MeterHandle`](crate::metering::MeterHandle): whichever thread learns
+//! the new numbers (typically the audio thread, processing the plugin's
+//! voice-info query) stores them without blocking, and the host's main
+//! thread polls for a change to decide whether to emit its own
+//! idle-notification event.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::id::PluginInstanceID;
+
+/// A plugin's reported voice count and capacity at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceInfo {
+    /// Number of voices currently active.
+    pub voice_count: u32,
+    /// Maximum number of voices this plugin can run concurrently. `u32::MAX`
+    /// if the plugin reports no fixed limit.
+    pub voice_capacity: u32,
+}
+
+impl VoiceInfo {
+    fn to_bits(self) -> u64 {
+        (self.voice_count as u64) << 32 | self.voice_capacity as u64
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        Self { voice_count: (bits >> 32) as u32, voice_capacity: bits as u32 }
+    }
+}
+
+/// A lock-free mailbox for one plugin's latest [`VoiceInfo`], shared between
+/// whatever reports it and the host's main thread.
+#[derive(Debug)]
+pub struct VoiceInfoHandle {
+    bits: AtomicU64,
+}
+
+impl Default for VoiceInfoHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VoiceInfoHandle {
+    pub fn new() -> Self {
+        Self { bits: AtomicU64::new(VoiceInfo { voice_count: 0, voice_capacity: u32::MAX }.to_bits()) }
+    }
+
+    pub fn set(&self, info: VoiceInfo) {
+        self.bits.store(info.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> VoiceInfo {
+        VoiceInfo::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+}
+
+/// Per-plugin [`VoiceInfoHandle`]s, with change detection for deciding when
+/// to emit an `OnIdleEvent`-style polyphony-changed notification.
+#[derive(Debug, Default)]
+pub struct VoiceInfoTable {
+    handles: HashMap<PluginInstanceID, Arc<VoiceInfoHandle>>,
+    last_seen: HashMap<PluginInstanceID, VoiceInfo>,
+}
+
+impl VoiceInfoTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the handle for `plugin`, creating one on first touch. Give a
+    /// clone of this to whatever reports the plugin's voice-info query
+    /// results.
+    pub fn handle(&mut self, plugin: PluginInstanceID) -> Arc<VoiceInfoHandle> {
+        self.handles.entry(plugin).or_insert_with(|| Arc::new(VoiceInfoHandle::new())).clone()
+    }
+
+    /// The most recently reported voice info for `plugin`, if it has a
+    /// handle.
+    pub fn voice_info(&self, plugin: PluginInstanceID) -> Option<VoiceInfo> {
+        self.handles.get(&plugin).map(|handle| handle.get())
+    }
+
+    /// Polls every plugin for a voice-info change since the last call,
+    /// returning the plugins whose count or capacity moved, for the host's
+    /// main thread to turn into `OnIdleEvent::PluginVoiceInfoChanged` events.
+    pub fn poll_changes(&mut self) -> Vec<(PluginInstanceID, VoiceInfo)> {
+        let mut changed = Vec::new();
+        for (&plugin, handle) in &self.handles {
+            let current = handle.get();
+            if self.last_seen.get(&plugin) != Some(&current) {
+                self.last_seen.insert(plugin, current);
+                changed.push((plugin, current));
+            }
+        }
+        changed
+    }
+
+    pub fn remove_plugin(&mut self, plugin: PluginInstanceID) {
+        self.handles.remove(&plugin);
+        self.last_seen.remove(&plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_new_handle_reports_zero_voices_and_no_fixed_capacity() {
+        let handle = VoiceInfoHandle::new();
+        assert_eq!(handle.get(), VoiceInfo { voice_count: 0, voice_capacity: u32::MAX });
+    }
+
+    #[test]
+    fn set_and_get_round_trip_through_the_bit_packed_mailbox() {
+        let handle = VoiceInfoHandle::new();
+        handle.set(VoiceInfo { voice_count: 7, voice_capacity: 16 });
+        assert_eq!(handle.get(), VoiceInfo { voice_count: 7, voice_capacity: 16 });
+    }
+
+    #[test]
+    fn the_table_creates_one_handle_per_plugin_on_first_touch() {
+        let mut table = VoiceInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        let handle = table.handle(plugin);
+        handle.set(VoiceInfo { voice_count: 3, voice_capacity: 8 });
+        assert_eq!(table.voice_info(plugin), Some(VoiceInfo { voice_count: 3, voice_capacity: 8 }));
+    }
+
+    #[test]
+    fn poll_changes_reports_a_plugin_only_once_per_distinct_value() {
+        let mut table = VoiceInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        let handle = table.handle(plugin);
+
+        handle.set(VoiceInfo { voice_count: 1, voice_capacity: 8 });
+        assert_eq!(table.poll_changes(), vec![(plugin, VoiceInfo { voice_count: 1, voice_capacity: 8 })]);
+        assert!(table.poll_changes().is_empty(), "unchanged value shouldn't be reported again");
+
+        handle.set(VoiceInfo { voice_count: 2, voice_capacity: 8 });
+        assert_eq!(table.poll_changes(), vec![(plugin, VoiceInfo { voice_count: 2, voice_capacity: 8 })]);
+    }
+
+    #[test]
+    fn removing_a_plugin_drops_its_handle_and_history() {
+        let mut table = VoiceInfoTable::new();
+        let plugin = PluginInstanceID::new();
+        table.handle(plugin);
+        table.remove_plugin(plugin);
+        assert_eq!(table.voice_info(plugin), None);
+    }
+}
@@ -0,0 +1,13 @@
+//! A single, canonical import for the handle types most host integrations
+//! reach for first, so downstream code doesn't have to go hunting through
+//! `dropseed::graph`, `dropseed::plugin`, etc. to find them.
+//!
+//! ```
+//! use dropseed::prelude::*;
+//! ```
+
+pub use crate::engine::DSEngineMainThread;
+pub use crate::graph::{GraphEditRequest, PortType, Schedule};
+pub use crate::id::{ParamID, PluginInstanceID};
+pub use crate::plugin::{DSPluginSaveState, EventQuantizer, PluginGainStages};
+pub use crate::transport::{TempoMap, TimeSignatureMap};
@@ -0,0 +1,97 @@
+//! Peak scanning and auto-normalization for loaded PCM resources.
+
+use super::pcm::PcmRAM;
+
+/// The result of scanning a [`PcmRAM`] for its peak sample amplitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeakScan {
+    /// Linear peak amplitude across all channels (`0.0` for silence).
+    pub peak_linear: f32,
+}
+
+impl PeakScan {
+    /// The peak amplitude in dBFS. `-f32::INFINITY` for digital silence.
+    pub fn peak_dbfs(&self) -> f32 {
+        if self.peak_linear <= 0.0 {
+            f32::NEG_INFINITY
+        } else {
+            20.0 * self.peak_linear.log10()
+        }
+    }
+}
+
+/// Scans every channel of `pcm` for its peak absolute sample value.
+pub fn scan_peaks(pcm: &PcmRAM) -> PeakScan {
+    let mut peak_linear = 0.0f32;
+    for channel in pcm.channels() {
+        for &sample in channel {
+            peak_linear = peak_linear.max(sample.abs());
+        }
+    }
+    PeakScan { peak_linear }
+}
+
+/// The gain (linear, not dB) that would normalize `scan`'s peak to
+/// `target_dbfs`. Returns `1.0` (no change) for digital silence.
+pub fn normalization_gain(scan: PeakScan, target_dbfs: f32) -> f32 {
+    if scan.peak_linear <= 0.0 {
+        return 1.0;
+    }
+    let target_linear = 10f32.powf(target_dbfs / 20.0);
+    target_linear / scan.peak_linear
+}
+
+impl PcmRAM {
+    /// Scans this resource's peak amplitude.
+    pub fn scan_peaks(&self) -> PeakScan {
+        scan_peaks(self)
+    }
+
+    /// Applies a linear gain to every sample in place.
+    pub fn apply_gain(&mut self, gain: f32) {
+        for ch_idx in 0..self.num_channels() {
+            for sample in self.channel_mut(ch_idx) {
+                *sample *= gain;
+            }
+        }
+    }
+
+    /// Scans this resource's peak and applies the gain needed to normalize
+    /// it to `target_dbfs`, in place.
+    pub fn normalize_to_peak(&mut self, target_dbfs: f32) {
+        let gain = normalization_gain(self.scan_peaks(), target_dbfs);
+        self.apply_gain(gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::metadata::PcmMetadata;
+
+    fn test_pcm(channels: Vec<Vec<f32>>) -> PcmRAM {
+        PcmRAM::new(channels, 44_100, PcmMetadata::default())
+    }
+
+    #[test]
+    fn scans_peak_across_channels() {
+        let pcm = test_pcm(vec![vec![0.1, -0.2, 0.3], vec![0.0, 0.9, -0.1]]);
+        let scan = pcm.scan_peaks();
+        assert!((scan.peak_linear - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normalizes_to_target_peak() {
+        let mut pcm = test_pcm(vec![vec![0.1, -0.5, 0.25]]);
+        pcm.normalize_to_peak(0.0); // 0 dBFS = full scale
+        let scan = pcm.scan_peaks();
+        assert!((scan.peak_linear - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn silence_normalizes_to_silence() {
+        let mut pcm = test_pcm(vec![vec![0.0, 0.0]]);
+        pcm.normalize_to_peak(0.0);
+        assert_eq!(pcm.scan_peaks().peak_linear, 0.0);
+    }
+}
@@ -0,0 +1,50 @@
+//! Broadcast Wave Format (`bext`) and iXML metadata, as embedded in WAV
+//! files by field recorders and preserved across recording/export so that
+//! provenance (scene/take, timecode, description) survives a round trip.
+
+/// The fields of a WAV `bext` chunk that are useful to preserve, plus the
+/// raw iXML payload if present.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BwfMetadata {
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    pub origination_date: String,
+    pub origination_time: String,
+    /// Number of samples since midnight that this file's first sample
+    /// corresponds to, per the `bext` spec.
+    pub time_reference: u64,
+    /// The raw iXML chunk contents (an XML document), if present.
+    pub ixml: Option<String>,
+}
+
+pub(crate) fn fixed_str(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+pub(crate) fn pad_fixed(s: &str, len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    let bytes = s.as_bytes();
+    let n = bytes.len().min(len);
+    buf[..n].copy_from_slice(&bytes[..n]);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_str_trims_at_first_nul() {
+        let mut bytes = b"hello".to_vec();
+        bytes.extend([0u8; 5]);
+        assert_eq!(fixed_str(&bytes), "hello");
+    }
+
+    #[test]
+    fn pad_fixed_truncates_and_zero_pads() {
+        assert_eq!(pad_fixed("hi", 5), vec![b'h', b'i', 0, 0, 0]);
+        assert_eq!(pad_fixed("toolong", 3), vec![b't', b'o', b'o']);
+    }
+}
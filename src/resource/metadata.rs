@@ -0,0 +1,348 @@
+//! Extraction of embedded loop points, root note, and cue/slice markers from
+//! WAV (`smpl`/`cue `) and AIFF (`MARK`/`INST`) chunks.
+//!
+//! Symphonia's format readers don't expose these instrument chunks, so this
+//! module does a second, much more limited pass over the container purely
+//! to pull out the metadata that sampler nodes care about.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::bwf::{fixed_str, BwfMetadata};
+
+/// A sustain/release loop point, in sample frames from the start of the
+/// resource.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopPoint {
+    pub start_frame: u64,
+    pub end_frame: u64,
+}
+
+/// A named cue point / slice marker, in sample frames from the start of the
+/// resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuePoint {
+    pub name: String,
+    pub frame: u64,
+}
+
+/// Instrument metadata extracted from a WAV `smpl`/`cue ` chunk or an AIFF
+/// `INST`/`MARK` chunk.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PcmMetadata {
+    pub root_note: Option<u8>,
+    pub loop_points: Vec<LoopPoint>,
+    pub cue_points: Vec<CuePoint>,
+    /// Broadcast Wave Format / iXML metadata, if the file had a `bext` or
+    /// `iXML` chunk.
+    pub bwf: Option<BwfMetadata>,
+}
+
+/// Scans the file at `path` for embedded loop/cue metadata. Returns an empty
+/// [`PcmMetadata`] (not an error) if the container isn't recognized or has
+/// no such chunks, since most files simply won't have any.
+pub fn extract_metadata(path: &Path) -> std::io::Result<PcmMetadata> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    if file.read_exact(&mut magic).is_err() {
+        return Ok(PcmMetadata::default());
+    }
+    match &magic {
+        b"RIFF" => read_riff_metadata(&mut file),
+        b"FORM" => read_aiff_metadata(&mut file),
+        _ => Ok(PcmMetadata::default()),
+    }
+}
+
+fn read_u32le(file: &mut File) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    file.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u32be(file: &mut File) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    file.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn read_riff_metadata(file: &mut File) -> std::io::Result<PcmMetadata> {
+    let mut meta = PcmMetadata::default();
+    let riff_size = read_u32le(file)?;
+    let mut form_type = [0u8; 4];
+    file.read_exact(&mut form_type)?;
+    if &form_type != b"WAVE" {
+        return Ok(meta);
+    }
+
+    let mut cue_names: Vec<(u32, String)> = Vec::new();
+    let mut cue_frames: Vec<(u32, u64)> = Vec::new();
+
+    let end = 8 + riff_size as u64;
+    loop {
+        let pos = file.stream_position()?;
+        if pos + 8 > end {
+            break;
+        }
+        let mut id = [0u8; 4];
+        if file.read_exact(&mut id).is_err() {
+            break;
+        }
+        let size = read_u32le(file)?;
+        let chunk_start = file.stream_position()?;
+
+        match &id {
+            b"smpl" => {
+                // MIDI unity note is at byte offset 12 within the chunk body.
+                file.seek(SeekFrom::Start(chunk_start + 12))?;
+                let unity_note = read_u32le(file)?;
+                meta.root_note = Some(unity_note as u8);
+
+                file.seek(SeekFrom::Start(chunk_start + 28))?;
+                let num_loops = read_u32le(file)?;
+                file.seek(SeekFrom::Start(chunk_start + 36))?; // skip sampler data size
+                for _ in 0..num_loops {
+                    let _cue_point_id = read_u32le(file)?;
+                    let _loop_type = read_u32le(file)?;
+                    let start = read_u32le(file)?;
+                    let end = read_u32le(file)?;
+                    let _fraction = read_u32le(file)?;
+                    let _play_count = read_u32le(file)?;
+                    meta.loop_points
+                        .push(LoopPoint { start_frame: start as u64, end_frame: end as u64 });
+                }
+            }
+            b"cue " => {
+                file.seek(SeekFrom::Start(chunk_start))?;
+                let num_points = read_u32le(file)?;
+                for _ in 0..num_points {
+                    let id = read_u32le(file)?;
+                    let _position = read_u32le(file)?;
+                    let mut chunk_id = [0u8; 4];
+                    file.read_exact(&mut chunk_id)?;
+                    let _chunk_start_field = read_u32le(file)?;
+                    let _block_start = read_u32le(file)?;
+                    let sample_offset = read_u32le(file)?;
+                    cue_frames.push((id, sample_offset as u64));
+                }
+            }
+            b"LIST" => {
+                // A "labl" sub-chunk associates a cue point ID with a name.
+                let list_end = chunk_start + size as u64;
+                file.seek(SeekFrom::Start(chunk_start + 4))?; // skip list type
+                while file.stream_position()? + 8 <= list_end {
+                    let mut sub_id = [0u8; 4];
+                    file.read_exact(&mut sub_id)?;
+                    let sub_size = read_u32le(file)?;
+                    let sub_start = file.stream_position()?;
+                    if &sub_id == b"labl" {
+                        let id = read_u32le(file)?;
+                        let text_len = (sub_size as usize).saturating_sub(4);
+                        let mut buf = vec![0u8; text_len];
+                        file.read_exact(&mut buf)?;
+                        let name =
+                            String::from_utf8_lossy(&buf).trim_end_matches('\0').to_string();
+                        cue_names.push((id, name));
+                    }
+                    let padded = sub_size as u64 + (sub_size & 1) as u64;
+                    file.seek(SeekFrom::Start(sub_start + padded))?;
+                }
+            }
+            b"bext" => {
+                file.seek(SeekFrom::Start(chunk_start))?;
+                let mut description = [0u8; 256];
+                file.read_exact(&mut description)?;
+                let mut originator = [0u8; 32];
+                file.read_exact(&mut originator)?;
+                let mut originator_reference = [0u8; 32];
+                file.read_exact(&mut originator_reference)?;
+                let mut origination_date = [0u8; 10];
+                file.read_exact(&mut origination_date)?;
+                let mut origination_time = [0u8; 8];
+                file.read_exact(&mut origination_time)?;
+                let mut time_reference = [0u8; 8];
+                file.read_exact(&mut time_reference)?;
+                let time_reference = u64::from_le_bytes(time_reference);
+
+                let bwf = meta.bwf.get_or_insert_with(BwfMetadata::default);
+                bwf.description = fixed_str(&description);
+                bwf.originator = fixed_str(&originator);
+                bwf.originator_reference = fixed_str(&originator_reference);
+                bwf.origination_date = fixed_str(&origination_date);
+                bwf.origination_time = fixed_str(&origination_time);
+                bwf.time_reference = time_reference;
+            }
+            b"iXML" => {
+                file.seek(SeekFrom::Start(chunk_start))?;
+                let mut buf = vec![0u8; size as usize];
+                file.read_exact(&mut buf)?;
+                meta.bwf.get_or_insert_with(BwfMetadata::default).ixml =
+                    Some(String::from_utf8_lossy(&buf).to_string());
+            }
+            _ => {}
+        }
+
+        let padded_size = size as u64 + (size & 1) as u64;
+        file.seek(SeekFrom::Start(chunk_start + padded_size))?;
+    }
+
+    for (id, frame) in cue_frames {
+        let name = cue_names
+            .iter()
+            .find(|(n_id, _)| *n_id == id)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| format!("Cue {id}"));
+        meta.cue_points.push(CuePoint { name, frame });
+    }
+
+    Ok(meta)
+}
+
+fn read_aiff_metadata(file: &mut File) -> std::io::Result<PcmMetadata> {
+    let mut meta = PcmMetadata::default();
+    let form_size = read_u32be(file)?;
+    let mut form_type = [0u8; 4];
+    file.read_exact(&mut form_type)?;
+    if &form_type != b"AIFF" && &form_type != b"AIFC" {
+        return Ok(meta);
+    }
+
+    let end = 8 + form_size as u64;
+    loop {
+        let pos = file.stream_position()?;
+        if pos + 8 > end {
+            break;
+        }
+        let mut id = [0u8; 4];
+        if file.read_exact(&mut id).is_err() {
+            break;
+        }
+        let size = read_u32be(file)?;
+        let chunk_start = file.stream_position()?;
+
+        match &id {
+            b"MARK" => {
+                let mut count_buf = [0u8; 2];
+                file.read_exact(&mut count_buf)?;
+                let num_markers = u16::from_be_bytes(count_buf);
+                for _ in 0..num_markers {
+                    let mut marker_id_buf = [0u8; 2];
+                    file.read_exact(&mut marker_id_buf)?;
+                    let frame = read_u32be(file)?;
+                    let mut len_buf = [0u8; 1];
+                    file.read_exact(&mut len_buf)?;
+                    let len = len_buf[0] as usize;
+                    let mut name_buf = vec![0u8; len];
+                    file.read_exact(&mut name_buf)?;
+                    // Marker names are Pascal strings padded to an even size.
+                    if len.is_multiple_of(2) {
+                        file.seek(SeekFrom::Current(1))?;
+                    }
+                    let name = String::from_utf8_lossy(&name_buf).to_string();
+                    meta.cue_points.push(CuePoint { name, frame: frame as u64 });
+                }
+            }
+            b"INST" => {
+                file.seek(SeekFrom::Start(chunk_start))?;
+                let mut base_note = [0u8; 1];
+                file.read_exact(&mut base_note)?;
+                meta.root_note = Some(base_note[0]);
+            }
+            _ => {}
+        }
+
+        let padded_size = size as u64 + (size & 1) as u64;
+        file.seek(SeekFrom::Start(chunk_start + padded_size))?;
+    }
+
+    Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_wav_with_smpl_and_cue(path: &Path) {
+        let data: Vec<u8> = vec![0u8; 8]; // 4 sample frames @ 16-bit mono
+        let mut fmt = Vec::new();
+        fmt.extend(1u16.to_le_bytes());
+        fmt.extend(1u16.to_le_bytes());
+        fmt.extend(44_100u32.to_le_bytes());
+        fmt.extend(88_200u32.to_le_bytes());
+        fmt.extend(2u16.to_le_bytes());
+        fmt.extend(16u16.to_le_bytes());
+
+        let mut smpl = Vec::new();
+        smpl.extend(0u32.to_le_bytes()); // manufacturer
+        smpl.extend(0u32.to_le_bytes()); // product
+        smpl.extend(0u32.to_le_bytes()); // sample period
+        smpl.extend(60u32.to_le_bytes()); // unity note
+        smpl.extend(0u32.to_le_bytes()); // pitch fraction
+        smpl.extend(0u32.to_le_bytes()); // smpte format
+        smpl.extend(0u32.to_le_bytes()); // smpte offset
+        smpl.extend(1u32.to_le_bytes()); // num sample loops
+        smpl.extend(0u32.to_le_bytes()); // sampler data
+        smpl.extend(0u32.to_le_bytes()); // loop id
+        smpl.extend(0u32.to_le_bytes()); // loop type
+        smpl.extend(1u32.to_le_bytes()); // loop start
+        smpl.extend(3u32.to_le_bytes()); // loop end
+        smpl.extend(0u32.to_le_bytes()); // fraction
+        smpl.extend(0u32.to_le_bytes()); // play count
+
+        let mut cue = Vec::new();
+        cue.extend(1u32.to_le_bytes()); // num cue points
+        cue.extend(1u32.to_le_bytes()); // cue id
+        cue.extend(0u32.to_le_bytes()); // position
+        cue.extend(*b"data");
+        cue.extend(0u32.to_le_bytes());
+        cue.extend(0u32.to_le_bytes());
+        cue.extend(2u32.to_le_bytes()); // sample offset
+
+        fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut out = Vec::new();
+            out.extend(id);
+            out.extend((body.len() as u32).to_le_bytes());
+            out.extend(body);
+            if body.len() % 2 == 1 {
+                out.push(0);
+            }
+            out
+        }
+
+        let mut riff_body = Vec::new();
+        riff_body.extend(*b"WAVE");
+        riff_body.extend(chunk(b"fmt ", &fmt));
+        riff_body.extend(chunk(b"data", &data));
+        riff_body.extend(chunk(b"smpl", &smpl));
+        riff_body.extend(chunk(b"cue ", &cue));
+
+        let mut file = File::create(path).unwrap();
+        file.write_all(b"RIFF").unwrap();
+        file.write_all(&(riff_body.len() as u32).to_le_bytes()).unwrap();
+        file.write_all(&riff_body).unwrap();
+    }
+
+    #[test]
+    fn extracts_root_note_and_loop_points_from_wav() {
+        let path = std::env::temp_dir().join("dropseed_metadata_test.wav");
+        write_wav_with_smpl_and_cue(&path);
+
+        let meta = extract_metadata(&path).unwrap();
+        assert_eq!(meta.root_note, Some(60));
+        assert_eq!(meta.loop_points, vec![LoopPoint { start_frame: 1, end_frame: 3 }]);
+        assert_eq!(meta.cue_points, vec![CuePoint { name: "Cue 1".to_string(), frame: 2 }]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn unrecognized_container_returns_empty_metadata() {
+        let path = std::env::temp_dir().join("dropseed_metadata_unknown.bin");
+        std::fs::write(&path, b"not audio").unwrap();
+        let meta = extract_metadata(&path).unwrap();
+        assert_eq!(meta, PcmMetadata::default());
+        std::fs::remove_file(&path).ok();
+    }
+}
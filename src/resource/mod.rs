@@ -0,0 +1,17 @@
+//! Loading audio files into memory for use by sampler-style internal nodes.
+
+pub mod analysis;
+pub mod bwf;
+pub mod loader;
+pub mod metadata;
+pub mod pcm;
+pub mod wav_writer;
+pub mod waveform;
+
+pub use analysis::{normalization_gain, scan_peaks, PeakScan};
+pub use bwf::BwfMetadata;
+pub use loader::{LoadError, PcmLoader};
+pub use metadata::{CuePoint, LoopPoint, PcmMetadata};
+pub use pcm::PcmRAM;
+pub use wav_writer::write_wav_with_bwf;
+pub use waveform::{build_waveform_pyramid, MinMax, WaveformPyramid};
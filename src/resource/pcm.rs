@@ -0,0 +1,55 @@
+//! The in-memory, decoded PCM representation produced by the [`super::PcmLoader`].
+
+use super::metadata::PcmMetadata;
+
+/// A fully-decoded audio resource held in RAM as planar `f32` samples.
+#[derive(Debug, Clone)]
+pub struct PcmRAM {
+    /// One `Vec<f32>` per channel, each of the same length.
+    channels: Vec<Vec<f32>>,
+    sample_rate: u32,
+    /// Embedded loop points, root note, and cue markers, if any were found
+    /// in the source file.
+    metadata: PcmMetadata,
+}
+
+impl PcmRAM {
+    pub(crate) fn new(channels: Vec<Vec<f32>>, sample_rate: u32, metadata: PcmMetadata) -> Self {
+        debug_assert!(!channels.is_empty());
+        debug_assert!(channels.windows(2).all(|w| w[0].len() == w[1].len()));
+        Self { channels, sample_rate, metadata }
+    }
+
+    pub fn metadata(&self) -> &PcmMetadata {
+        &self.metadata
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// The exact number of frames in this resource.
+    ///
+    /// This reflects the number of frames actually decoded rather than any
+    /// (potentially inaccurate) duration estimate from the container, which
+    /// matters most for variable-bitrate formats.
+    pub fn num_frames(&self) -> usize {
+        self.channels.first().map(|c| c.len()).unwrap_or(0)
+    }
+
+    pub fn channel(&self, index: usize) -> &[f32] {
+        &self.channels[index]
+    }
+
+    pub(crate) fn channel_mut(&mut self, index: usize) -> &mut [f32] {
+        &mut self.channels[index]
+    }
+
+    pub(crate) fn channels(&self) -> &[Vec<f32>] {
+        &self.channels
+    }
+}
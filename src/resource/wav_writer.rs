@@ -0,0 +1,131 @@
+//! A minimal WAV writer that preserves BWF/iXML metadata across a
+//! recording/export round trip.
+//!
+//! This exists alongside the Symphonia-backed decode path because
+//! preserving arbitrary `bext`/`iXML` chunks on write isn't something
+//! general-purpose decode libraries need to support.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::bwf::{pad_fixed, BwfMetadata};
+
+fn write_chunk(file: &mut File, id: &[u8; 4], body: &[u8]) -> io::Result<()> {
+    file.write_all(id)?;
+    file.write_all(&(body.len() as u32).to_le_bytes())?;
+    file.write_all(body)?;
+    if body.len() % 2 == 1 {
+        file.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+fn bext_chunk_body(bwf: &BwfMetadata) -> Vec<u8> {
+    let mut body = Vec::with_capacity(602);
+    body.extend(pad_fixed(&bwf.description, 256));
+    body.extend(pad_fixed(&bwf.originator, 32));
+    body.extend(pad_fixed(&bwf.originator_reference, 32));
+    body.extend(pad_fixed(&bwf.origination_date, 10));
+    body.extend(pad_fixed(&bwf.origination_time, 8));
+    body.extend(bwf.time_reference.to_le_bytes());
+    body.extend(1u16.to_le_bytes()); // version
+    body.extend([0u8; 64]); // UMID
+    body.extend([0u8; 190]); // loudness + reserved fields
+    body
+}
+
+/// Writes planar `f32` samples to a 16-bit PCM WAV file at `path`, embedding
+/// `bwf` as a `bext` chunk (and an `iXML` chunk, if present) when given.
+pub fn write_wav_with_bwf(
+    path: &Path,
+    channels: &[Vec<f32>],
+    sample_rate: u32,
+    bwf: Option<&BwfMetadata>,
+) -> io::Result<()> {
+    let num_channels = channels.len() as u16;
+    let num_frames = channels.first().map(|c| c.len()).unwrap_or(0);
+
+    let mut data = Vec::with_capacity(num_frames * channels.len() * 2);
+    for frame in 0..num_frames {
+        for ch in channels {
+            let sample = (ch[frame].clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    let byte_rate = sample_rate * num_channels as u32 * 2;
+    let block_align = num_channels * 2;
+
+    let mut fmt = Vec::new();
+    fmt.extend(1u16.to_le_bytes()); // PCM
+    fmt.extend(num_channels.to_le_bytes());
+    fmt.extend(sample_rate.to_le_bytes());
+    fmt.extend(byte_rate.to_le_bytes());
+    fmt.extend(block_align.to_le_bytes());
+    fmt.extend(16u16.to_le_bytes());
+
+    let mut body = Vec::new();
+    body.extend(*b"WAVE");
+    if let Some(bwf) = bwf {
+        body.push(b'b');
+        body.push(b'e');
+        body.push(b'x');
+        body.push(b't');
+        let bext_body = bext_chunk_body(bwf);
+        body.extend((bext_body.len() as u32).to_le_bytes());
+        body.extend(&bext_body);
+        if let Some(ixml) = &bwf.ixml {
+            body.extend(*b"iXML");
+            body.extend((ixml.len() as u32).to_le_bytes());
+            body.extend(ixml.as_bytes());
+            if ixml.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(b"RIFF")?;
+    file.write_all(&0u32.to_le_bytes())?; // placeholder, fixed up below
+    file.write_all(&body)?;
+    write_chunk(&mut file, b"fmt ", &fmt)?;
+    write_chunk(&mut file, b"data", &data)?;
+
+    use std::io::Seek;
+    let total_len = file.stream_position()?;
+    file.seek(std::io::SeekFrom::Start(4))?;
+    file.write_all(&((total_len - 8) as u32).to_le_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::metadata::extract_metadata;
+
+    #[test]
+    fn round_trips_bwf_metadata_through_wav_export() {
+        let path = std::env::temp_dir().join("dropseed_wav_writer_test.wav");
+        let bwf = BwfMetadata {
+            description: "take 3".to_string(),
+            originator: "dropseed".to_string(),
+            originator_reference: "DS0001".to_string(),
+            origination_date: "2026-08-09".to_string(),
+            origination_time: "12:00:00".to_string(),
+            time_reference: 12345,
+            ixml: Some("<BWFXML><SCENE>1</SCENE></BWFXML>".to_string()),
+        };
+        write_wav_with_bwf(&path, &[vec![0.0, 0.25, -0.25, 0.5]], 44_100, Some(&bwf)).unwrap();
+
+        let meta = extract_metadata(&path).unwrap();
+        let round_tripped = meta.bwf.expect("bwf metadata preserved");
+        assert_eq!(round_tripped.description, bwf.description);
+        assert_eq!(round_tripped.originator_reference, bwf.originator_reference);
+        assert_eq!(round_tripped.time_reference, bwf.time_reference);
+        assert_eq!(round_tripped.ixml, bwf.ixml);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,125 @@
+//! Min/max waveform pyramid generation, for drawing zoomable waveforms
+//! without re-scanning the full resource at every zoom level.
+
+use super::pcm::PcmRAM;
+
+/// The minimum and maximum sample value within one pixel-sized chunk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinMax {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// A mip-map style pyramid of min/max pairs for a single channel.
+///
+/// `levels[0]` is the finest resolution (`base_chunk_frames` frames per
+/// point); each subsequent level halves the resolution of the previous one
+/// by merging adjacent pairs, so a UI can pick the coarsest level that still
+/// has at least one point per pixel at the current zoom.
+#[derive(Debug, Clone)]
+pub struct WaveformPyramid {
+    base_chunk_frames: usize,
+    levels: Vec<Vec<MinMax>>,
+}
+
+impl WaveformPyramid {
+    /// The number of frames per point at the finest level (level 0).
+    pub fn base_chunk_frames(&self) -> usize {
+        self.base_chunk_frames
+    }
+
+    pub fn num_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn level(&self, index: usize) -> &[MinMax] {
+        &self.levels[index]
+    }
+
+    /// The coarsest available level whose points still cover no more than
+    /// `max_frames_per_point` frames each.
+    pub fn best_level_for_zoom(&self, max_frames_per_point: usize) -> usize {
+        let mut best = 0;
+        for (i, _) in self.levels.iter().enumerate() {
+            let frames_per_point = self.base_chunk_frames << i;
+            if frames_per_point <= max_frames_per_point {
+                best = i;
+            } else {
+                break;
+            }
+        }
+        best
+    }
+}
+
+/// Builds a min/max waveform pyramid for one channel of `pcm`, with the
+/// finest level grouping `base_chunk_frames` frames per point.
+pub fn build_waveform_pyramid(
+    pcm: &PcmRAM,
+    channel: usize,
+    base_chunk_frames: usize,
+) -> WaveformPyramid {
+    assert!(base_chunk_frames > 0);
+    let samples = pcm.channel(channel);
+
+    let mut base_level = Vec::with_capacity(samples.len().div_ceil(base_chunk_frames));
+    for chunk in samples.chunks(base_chunk_frames) {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for &s in chunk {
+            min = min.min(s);
+            max = max.max(s);
+        }
+        base_level.push(MinMax { min, max });
+    }
+
+    let mut levels = vec![base_level];
+    while levels.last().unwrap().len() > 1 {
+        let prev = levels.last().unwrap();
+        let mut next = Vec::with_capacity(prev.len().div_ceil(2));
+        for pair in prev.chunks(2) {
+            let min = pair.iter().map(|p| p.min).fold(f32::INFINITY, f32::min);
+            let max = pair.iter().map(|p| p.max).fold(f32::NEG_INFINITY, f32::max);
+            next.push(MinMax { min, max });
+        }
+        levels.push(next);
+    }
+
+    WaveformPyramid { base_chunk_frames, levels }
+}
+
+impl PcmRAM {
+    /// Builds a min/max waveform pyramid for `channel` of this resource.
+    pub fn waveform_pyramid(&self, channel: usize, base_chunk_frames: usize) -> WaveformPyramid {
+        build_waveform_pyramid(self, channel, base_chunk_frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::metadata::PcmMetadata;
+
+    #[test]
+    fn base_level_captures_exact_min_max_per_chunk() {
+        let pcm = PcmRAM::new(vec![vec![0.1, -0.5, 0.3, 0.2, -0.1, 0.9]], 44_100, PcmMetadata::default());
+        let pyramid = build_waveform_pyramid(&pcm, 0, 3);
+        assert_eq!(pyramid.level(0), &[MinMax { min: -0.5, max: 0.3 }, MinMax { min: -0.1, max: 0.9 }]);
+    }
+
+    #[test]
+    fn coarser_levels_halve_and_still_bound_the_extremes() {
+        let pcm = PcmRAM::new(vec![vec![0.1, -0.5, 0.3, 0.2, -0.1, 0.9]], 44_100, PcmMetadata::default());
+        let pyramid = build_waveform_pyramid(&pcm, 0, 3);
+        assert_eq!(pyramid.num_levels(), 2);
+        assert_eq!(pyramid.level(1), &[MinMax { min: -0.5, max: 0.9 }]);
+    }
+
+    #[test]
+    fn best_level_for_zoom_picks_the_coarsest_that_still_fits() {
+        let pcm = PcmRAM::new(vec![vec![0.0; 100]], 44_100, PcmMetadata::default());
+        let pyramid = build_waveform_pyramid(&pcm, 0, 4);
+        assert_eq!(pyramid.best_level_for_zoom(4), 0);
+        assert_eq!(pyramid.best_level_for_zoom(1_000), pyramid.num_levels() - 1);
+    }
+}
@@ -0,0 +1,9 @@
+//! Transport and musical-time bookkeeping.
+
+pub mod punch_and_count_in;
+pub mod tempo_map;
+pub mod time_signature_map;
+
+pub use punch_and_count_in::{CountIn, CountInSchedule, PunchRegion};
+pub use tempo_map::{TempoMap, TempoSnapshot};
+pub use time_signature_map::TimeSignatureMap;
@@ -0,0 +1,143 @@
+//! Punch-in/punch-out recording regions and pre-roll count-in scheduling.
+//!
+//! Like the rest of this module, these are plain value types and pure
+//! functions rather than a stateful transport object: the host already
+//! owns the playhead, and only needs to ask "is this sample armed for
+//! recording?" or "is this sample still in the count-in?" against its
+//! current position.
+
+use crate::transport::time_signature_map::TimeSignatureMap;
+use crate::transport::TempoMap;
+
+/// A punch recording region on the timeline: recording should only be
+/// armed while the playhead is within `[punch_in, punch_out)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PunchRegion {
+    pub punch_in: u64,
+    pub punch_out: u64,
+}
+
+impl PunchRegion {
+    /// Panics if `punch_in >= punch_out`; an empty or inverted region would
+    /// never arm recording, which almost certainly isn't what the host
+    /// meant to configure.
+    pub fn new(punch_in: u64, punch_out: u64) -> Self {
+        assert!(punch_in < punch_out, "a punch region must not be empty");
+        Self { punch_in, punch_out }
+    }
+
+    /// Whether `sample` falls within this punch region, i.e. recording
+    /// should be armed there.
+    pub fn contains(&self, sample: u64) -> bool {
+        (self.punch_in..self.punch_out).contains(&sample)
+    }
+}
+
+/// A count-in (pre-roll) of a fixed number of bars before playback or
+/// recording reaches its actual start position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountIn {
+    pub bars: u32,
+}
+
+impl CountIn {
+    pub fn new(bars: u32) -> Self {
+        Self { bars }
+    }
+
+    /// Resolves this count-in against `transport_start_sample` (the sample
+    /// where playback/recording is actually meant to begin), returning the
+    /// schedule the host should roll the transport through.
+    pub fn schedule(
+        self,
+        transport_start_sample: u64,
+        tempo_map: &TempoMap,
+        time_sig: &TimeSignatureMap,
+    ) -> CountInSchedule {
+        let start_beat = tempo_map.beat_at_sample(transport_start_sample);
+        let (start_bar, _beat_in_bar) = time_sig.bar_at_beat(start_beat);
+        let pre_roll_bar = start_bar.saturating_sub(self.bars as u64);
+        let pre_roll_beat = time_sig.beat_at_bar(pre_roll_bar);
+        let pre_roll_start_sample = tempo_map.sample_at_beat(pre_roll_beat);
+        CountInSchedule { pre_roll_start_sample, transport_start_sample }
+    }
+}
+
+/// Where a count-in actually begins and ends on the sample timeline, once
+/// resolved against a project's tempo and time signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountInSchedule {
+    pub pre_roll_start_sample: u64,
+    pub transport_start_sample: u64,
+}
+
+impl CountInSchedule {
+    /// Whether `sample` falls within the pre-roll, i.e. before playback
+    /// actually reaches `transport_start_sample`. This is exactly the
+    /// condition a host should report via CLAP's
+    /// `CLAP_TRANSPORT_IS_WITHIN_PRE_ROLL` transport event flag.
+    pub fn is_within_pre_roll(&self, sample: u64) -> bool {
+        (self.pre_roll_start_sample..self.transport_start_sample).contains(&sample)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_punch_region_only_contains_samples_within_its_bounds() {
+        let region = PunchRegion::new(1_000, 2_000);
+        assert!(!region.contains(999));
+        assert!(region.contains(1_000));
+        assert!(region.contains(1_999));
+        assert!(!region.contains(2_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn an_empty_punch_region_panics() {
+        PunchRegion::new(2_000, 2_000);
+    }
+
+    #[test]
+    fn a_two_bar_count_in_at_4_4_starts_two_bars_before_the_transport_start() {
+        let tempo_map = TempoMap::new(1_000.0, 120.0);
+        let time_sig = TimeSignatureMap::new(4, 4);
+        // At 120bpm/4-4, one bar is 4 beats = 2 seconds = 2000 samples at
+        // 1000Hz.
+        let transport_start_sample = 10_000;
+        let schedule = CountIn::new(2).schedule(transport_start_sample, &tempo_map, &time_sig);
+
+        assert_eq!(schedule.transport_start_sample, transport_start_sample);
+        assert_eq!(schedule.pre_roll_start_sample, 10_000 - 4_000);
+    }
+
+    #[test]
+    fn is_within_pre_roll_is_true_only_before_the_transport_start() {
+        let tempo_map = TempoMap::new(1_000.0, 120.0);
+        let time_sig = TimeSignatureMap::new(4, 4);
+        let schedule = CountIn::new(1).schedule(10_000, &tempo_map, &time_sig);
+
+        assert!(schedule.is_within_pre_roll(schedule.pre_roll_start_sample));
+        assert!(schedule.is_within_pre_roll(9_999));
+        assert!(!schedule.is_within_pre_roll(10_000));
+    }
+
+    #[test]
+    fn a_count_in_longer_than_the_transport_start_clamps_to_bar_zero() {
+        let tempo_map = TempoMap::new(1_000.0, 120.0);
+        let time_sig = TimeSignatureMap::new(4, 4);
+        let schedule = CountIn::new(100).schedule(1_000, &tempo_map, &time_sig);
+        assert_eq!(schedule.pre_roll_start_sample, 0);
+    }
+
+    #[test]
+    fn a_zero_bar_count_in_has_no_pre_roll() {
+        let tempo_map = TempoMap::new(1_000.0, 120.0);
+        let time_sig = TimeSignatureMap::new(4, 4);
+        let schedule = CountIn::new(0).schedule(10_000, &tempo_map, &time_sig);
+        assert_eq!(schedule.pre_roll_start_sample, schedule.transport_start_sample);
+        assert!(!schedule.is_within_pre_roll(9_999));
+    }
+}
@@ -0,0 +1,320 @@
+//! Maps sample positions to musical beats, including support for tempo
+//! ramps that change mid-block (CLAP's `tempo_inc`).
+//!
+//! Internal nodes that need to stay phase-accurate with the transport (the
+//! LFO, the clip player, the metronome, ...) should always go through
+//! [`TempoMap::integrate_beats`] rather than multiplying a single
+//! instantaneous BPM value by the block length, since that would drift
+//! whenever the tempo ramps within a block.
+
+/// A single span of constant-slope tempo, in effect from `start_sample`
+/// until the next segment (or forever, for the last segment).
+#[derive(Debug, Clone, Copy)]
+struct TempoSegment {
+    start_sample: u64,
+    start_beat: f64,
+    /// Tempo in beats per minute at `start_sample`.
+    bpm: f64,
+    /// Change in BPM per sample. Zero for a constant-tempo segment.
+    tempo_inc: f64,
+}
+
+/// A piecewise-linear map between sample positions and musical beats.
+///
+/// Each segment has a constant `tempo_inc` (BPM change per sample), which
+/// matches the shape of CLAP's `clap_event_transport::tempo_inc` field and
+/// allows exact (non-drifting) integration of beats over a partial block
+/// even while the tempo is ramping.
+#[derive(Debug, Clone)]
+pub struct TempoMap {
+    sample_rate: f64,
+    segments: Vec<TempoSegment>,
+}
+
+impl TempoMap {
+    /// Creates a new tempo map with a single constant tempo starting at
+    /// sample 0, beat 0.
+    pub fn new(sample_rate: f64, bpm: f64) -> Self {
+        assert!(sample_rate > 0.0);
+        assert!(bpm > 0.0);
+        Self {
+            sample_rate,
+            segments: vec![TempoSegment { start_sample: 0, start_beat: 0.0, bpm, tempo_inc: 0.0 }],
+        }
+    }
+
+    /// Inserts a new tempo segment starting at `start_sample`, replacing any
+    /// segments that started at or after it. `tempo_inc` is the change in
+    /// BPM per sample for a ramp beginning at this point (`0.0` for an
+    /// instant jump to a new constant tempo).
+    ///
+    /// Panics if `start_sample` is before the start of the current last
+    /// segment.
+    pub fn push_tempo_change(&mut self, start_sample: u64, bpm: f64, tempo_inc: f64) {
+        assert!(
+            start_sample >= self.segments.last().unwrap().start_sample,
+            "tempo changes must be pushed in non-decreasing sample order"
+        );
+        let start_beat = self.beat_at_sample(start_sample);
+        self.segments.retain(|seg| seg.start_sample < start_sample);
+        self.segments.push(TempoSegment { start_sample, start_beat, bpm, tempo_inc });
+    }
+
+    fn segment_for_sample(&self, sample: u64) -> &TempoSegment {
+        // Segments are always pushed in non-decreasing sample order, so the
+        // last one with `start_sample <= sample` is the active one.
+        self.segments
+            .iter()
+            .rev()
+            .find(|seg| seg.start_sample <= sample)
+            .unwrap_or(&self.segments[0])
+    }
+
+    /// Returns the exact musical beat position at the given sample,
+    /// integrating through any tempo ramp in effect.
+    pub fn beat_at_sample(&self, sample: u64) -> f64 {
+        let seg = self.segment_for_sample(sample);
+        let dt = sample.saturating_sub(seg.start_sample) as f64;
+        // Average BPM over [start_sample, sample] for a linear ramp is the
+        // mean of the endpoint values.
+        let avg_bpm = seg.bpm + seg.tempo_inc * dt * 0.5;
+        let beats_per_sample = avg_bpm / 60.0 / self.sample_rate;
+        seg.start_beat + beats_per_sample * dt
+    }
+
+    /// Integrates the number of beats elapsed between `start_sample` and
+    /// `start_sample + frames`, accounting for any tempo ramp that begins
+    /// partway through the block. This is the sample-accurate replacement
+    /// for `instantaneous_bpm * frames / (60 * sample_rate)`.
+    pub fn integrate_beats(&self, start_sample: u64, frames: u64) -> f64 {
+        self.beat_at_sample(start_sample + frames) - self.beat_at_sample(start_sample)
+    }
+
+    /// The sample rate this map was constructed with.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Every tempo segment as `(start_sample, bpm, tempo_inc)`, in the
+    /// order they were pushed, for persisting into project save state and
+    /// later rebuilding an identical map via [`new`](Self::new) (for the
+    /// first segment) and [`push_tempo_change`](Self::push_tempo_change)
+    /// (for the rest).
+    pub fn segments(&self) -> Vec<(u64, f64, f64)> {
+        self.segments.iter().map(|s| (s.start_sample, s.bpm, s.tempo_inc)).collect()
+    }
+
+    /// The instantaneous tempo in beats per minute at the given sample.
+    pub fn bpm_at_sample(&self, sample: u64) -> f64 {
+        let seg = self.segment_for_sample(sample);
+        let dt = sample.saturating_sub(seg.start_sample) as f64;
+        seg.bpm + seg.tempo_inc * dt
+    }
+
+    /// Returns the sample position at which `beat` occurs, the inverse of
+    /// [`beat_at_sample`](Self::beat_at_sample). Beats before the first
+    /// segment's start beat clamp to sample 0.
+    pub fn sample_at_beat(&self, beat: f64) -> u64 {
+        let seg = self
+            .segments
+            .iter()
+            .rev()
+            .find(|seg| seg.start_beat <= beat)
+            .unwrap_or(&self.segments[0]);
+
+        // Solve `a*dt^2 + b*dt - target = 0` for `dt`, where `target` is the
+        // beats remaining to cover from this segment's start.
+        let target = beat - seg.start_beat;
+        let b = seg.bpm / 60.0 / self.sample_rate;
+        let a = seg.tempo_inc / 60.0 / self.sample_rate * 0.5;
+
+        let dt = if a.abs() < 1e-12 {
+            if b.abs() < 1e-12 {
+                0.0
+            } else {
+                target / b
+            }
+        } else {
+            let discriminant = (b * b + 4.0 * a * target).max(0.0);
+            (-b + discriminant.sqrt()) / (2.0 * a)
+        };
+
+        seg.start_sample + dt.max(0.0).round() as u64
+    }
+
+    /// Captures the tempo in effect at `start_sample` into a small `Copy`
+    /// value an internal node can hold for the rest of a `process` call
+    /// instead of calling back into the map (and re-walking its segment
+    /// list) once per sample.
+    ///
+    /// Valid only within the block it was taken for: a tempo-synced node
+    /// should call this once per `process` call, right before processing,
+    /// not cache it across blocks.
+    pub fn snapshot_at(&self, start_sample: u64) -> TempoSnapshot {
+        let seg = self.segment_for_sample(start_sample);
+        TempoSnapshot {
+            sample_rate: self.sample_rate,
+            start_sample,
+            start_beat: self.beat_at_sample(start_sample),
+            bpm_at_start: self.bpm_at_sample(start_sample),
+            tempo_inc: seg.tempo_inc,
+        }
+    }
+
+    /// Converts a playhead position on this map to the sample position with
+    /// the same musical (beat) location on `new_map`.
+    ///
+    /// The host never owns a `TempoMap` through the engine (it's passed into
+    /// calls like [`emergency_save_state`](crate::engine::DSEngineMainThread::emergency_save_state)
+    /// the same way an [`AbstractGraph`](crate::graph::AbstractGraph) is),
+    /// so replacing one atomically is the host's job. This is the
+    /// computation that swap needs to keep playback continuous instead of
+    /// jumping to wherever `playhead_sample` falls under the new tempo: find
+    /// the beat the playhead is at now, then find the sample at that same
+    /// beat under the new map.
+    pub fn remap_playhead(&self, new_map: &TempoMap, playhead_sample: u64) -> u64 {
+        new_map.sample_at_beat(self.beat_at_sample(playhead_sample))
+    }
+}
+
+/// A `Copy` snapshot of the tempo in effect at one block's start sample,
+/// returned by [`TempoMap::snapshot_at`]. Answers the same two questions a
+/// tempo-synced node asks every block — "what beat is this frame offset at"
+/// and "what's the instantaneous BPM here" — without re-walking the tempo
+/// map's segment list or touching whatever synchronization the map itself
+/// is behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoSnapshot {
+    sample_rate: f64,
+    start_sample: u64,
+    start_beat: f64,
+    bpm_at_start: f64,
+    tempo_inc: f64,
+}
+
+impl TempoSnapshot {
+    /// The musical beat at `frame_offset` samples into this snapshot's
+    /// block, integrating through the ramp in effect at the time the
+    /// snapshot was taken.
+    pub fn beat_at_frame_offset(&self, frame_offset: u32) -> f64 {
+        let dt = frame_offset as f64;
+        let avg_bpm = self.bpm_at_start + self.tempo_inc * dt * 0.5;
+        self.start_beat + avg_bpm / 60.0 / self.sample_rate * dt
+    }
+
+    /// The instantaneous tempo in beats per minute at `frame_offset`
+    /// samples into this snapshot's block.
+    pub fn bpm_at_frame_offset(&self, frame_offset: u32) -> f64 {
+        self.bpm_at_start + self.tempo_inc * frame_offset as f64
+    }
+
+    /// The absolute sample position this snapshot was taken at.
+    pub fn start_sample(&self) -> u64 {
+        self.start_sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_tempo_integrates_linearly() {
+        let map = TempoMap::new(48_000.0, 120.0);
+        // 120 BPM = 2 beats/sec, so one second (48_000 samples) is 2 beats.
+        assert!((map.integrate_beats(0, 48_000) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ramp_integrates_without_drift_across_subdivided_blocks() {
+        let mut map = TempoMap::new(48_000.0, 120.0);
+        // Ramp from 120 to 240 BPM over 48_000 samples.
+        let tempo_inc = (240.0 - 120.0) / 48_000.0;
+        map.push_tempo_change(0, 120.0, tempo_inc);
+
+        let whole = map.integrate_beats(0, 48_000);
+        let half_a = map.integrate_beats(0, 24_000);
+        let half_b = map.integrate_beats(24_000, 24_000);
+        assert!((whole - (half_a + half_b)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tempo_change_preserves_beat_continuity() {
+        let mut map = TempoMap::new(48_000.0, 120.0);
+        let beat_before = map.beat_at_sample(48_000);
+        map.push_tempo_change(48_000, 90.0, 0.0);
+        let beat_after = map.beat_at_sample(48_000);
+        assert!((beat_before - beat_after).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pushing_at_the_same_start_sample_replaces_the_previous_segment() {
+        let mut map = TempoMap::new(48_000.0, 120.0);
+        map.push_tempo_change(48_000, 90.0, 0.0);
+        // Editing the same tempo change again (e.g. undo/redo) must replace
+        // the earlier segment rather than leaving it dangling in the map.
+        map.push_tempo_change(48_000, 150.0, 0.0);
+
+        assert_eq!(map.segments.len(), 2);
+        assert!((map.bpm_at_sample(48_000) - 150.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sample_at_beat_inverts_beat_at_sample_under_constant_tempo() {
+        let map = TempoMap::new(48_000.0, 120.0);
+        for beat in [0.0, 0.5, 1.0, 2.0, 7.25] {
+            let sample = map.sample_at_beat(beat);
+            assert!((map.beat_at_sample(sample) - beat).abs() < 1e-6, "beat {beat} round-tripped to sample {sample}");
+        }
+    }
+
+    #[test]
+    fn sample_at_beat_inverts_beat_at_sample_across_a_ramp() {
+        let mut map = TempoMap::new(48_000.0, 120.0);
+        let tempo_inc = (240.0 - 120.0) / 48_000.0;
+        map.push_tempo_change(0, 120.0, tempo_inc);
+        map.push_tempo_change(96_000, 240.0, 0.0);
+
+        for beat in [0.5, 1.5, 3.0, 5.0] {
+            let sample = map.sample_at_beat(beat);
+            assert!((map.beat_at_sample(sample) - beat).abs() < 1e-3, "beat {beat} round-tripped to sample {sample}");
+        }
+    }
+
+    #[test]
+    fn remap_playhead_preserves_musical_position_across_a_tempo_change() {
+        let old_map = TempoMap::new(48_000.0, 120.0);
+        // Ten seconds in at 120 BPM is beat 20.
+        let playhead = 480_000;
+
+        let new_map = TempoMap::new(48_000.0, 90.0);
+        let remapped = old_map.remap_playhead(&new_map, playhead);
+
+        assert!((new_map.beat_at_sample(remapped) - old_map.beat_at_sample(playhead)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_snapshot_matches_the_live_map_throughout_its_block() {
+        let mut map = TempoMap::new(48_000.0, 120.0);
+        let tempo_inc = (240.0 - 120.0) / 48_000.0;
+        map.push_tempo_change(0, 120.0, tempo_inc);
+
+        let snapshot = map.snapshot_at(1_000);
+        for offset in [0, 500, 2_000] {
+            assert!((snapshot.beat_at_frame_offset(offset) - map.beat_at_sample(1_000 + offset as u64)).abs() < 1e-9);
+            assert!((snapshot.bpm_at_frame_offset(offset) - map.bpm_at_sample(1_000 + offset as u64)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn a_snapshot_reports_the_sample_it_was_taken_at() {
+        let map = TempoMap::new(48_000.0, 120.0);
+        assert_eq!(map.snapshot_at(4_096).start_sample(), 4_096);
+    }
+
+    #[test]
+    fn remap_playhead_is_a_no_op_when_the_tempo_is_unchanged() {
+        let map = TempoMap::new(48_000.0, 120.0);
+        assert_eq!(map.remap_playhead(&map.clone(), 123_456), 123_456);
+    }
+}
@@ -0,0 +1,258 @@
+//! Maps bar numbers to musical beats under a (possibly changing) time
+//! signature, and enumerates bar/beat grid lines from it.
+//!
+//! This is the single source of truth for "where do the bar and beat lines
+//! fall": host UIs use it for grid drawing, and the metronome and
+//! quantization features use it to find bar/beat boundaries, rather than
+//! each re-deriving bar math from a raw beat position.
+
+use crate::transport::TempoMap;
+
+/// A single span of constant time signature, in effect from `start_bar`
+/// until the next segment (or forever, for the last segment).
+#[derive(Debug, Clone, Copy)]
+struct TimeSignatureSegment {
+    start_bar: u64,
+    start_beat: f64,
+    numerator: u32,
+    denominator: u32,
+}
+
+impl TimeSignatureSegment {
+    /// The length of one bar in this segment, in quarter-note beats.
+    fn beats_per_bar(&self) -> f64 {
+        self.numerator as f64 * 4.0 / self.denominator as f64
+    }
+}
+
+/// A piecewise-constant map between bar numbers and musical beats,
+/// accounting for every time signature change in the project.
+#[derive(Debug, Clone)]
+pub struct TimeSignatureMap {
+    segments: Vec<TimeSignatureSegment>,
+}
+
+impl TimeSignatureMap {
+    /// Creates a new time signature map with a single signature starting at
+    /// bar 0, beat 0.
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        assert!(numerator > 0 && denominator > 0);
+        Self { segments: vec![TimeSignatureSegment { start_bar: 0, start_beat: 0.0, numerator, denominator }] }
+    }
+
+    /// Inserts a new time signature starting at `start_bar`.
+    ///
+    /// Panics if `start_bar` is before the start of the current last
+    /// segment.
+    pub fn push_time_signature_change(&mut self, start_bar: u64, numerator: u32, denominator: u32) {
+        assert!(numerator > 0 && denominator > 0);
+        let last = *self.segments.last().unwrap();
+        assert!(
+            start_bar >= last.start_bar,
+            "time signature changes must be pushed in non-decreasing bar order"
+        );
+        let start_beat = last.start_beat + (start_bar - last.start_bar) as f64 * last.beats_per_bar();
+        self.segments.push(TimeSignatureSegment { start_bar, start_beat, numerator, denominator });
+    }
+
+    fn segment_for_bar(&self, bar: u64) -> &TimeSignatureSegment {
+        self.segments.iter().rev().find(|seg| seg.start_bar <= bar).unwrap_or(&self.segments[0])
+    }
+
+    fn segment_for_beat(&self, beat: f64) -> &TimeSignatureSegment {
+        self.segments.iter().rev().find(|seg| seg.start_beat <= beat).unwrap_or(&self.segments[0])
+    }
+
+    /// The musical beat at which `bar` (0-indexed) begins.
+    pub fn beat_at_bar(&self, bar: u64) -> f64 {
+        let seg = self.segment_for_bar(bar);
+        seg.start_beat + (bar - seg.start_bar) as f64 * seg.beats_per_bar()
+    }
+
+    /// The bar (0-indexed) containing `beat`, and how many beats into that
+    /// bar it is.
+    pub fn bar_at_beat(&self, beat: f64) -> (u64, f64) {
+        let seg = self.segment_for_beat(beat);
+        let bars_in = ((beat - seg.start_beat) / seg.beats_per_bar()).floor().max(0.0);
+        let bar = seg.start_bar + bars_in as u64;
+        (bar, beat - (seg.start_beat + bars_in * seg.beats_per_bar()))
+    }
+
+    /// Every bar-line beat position in `[start_beat, end_beat)`.
+    pub fn bar_lines_in_range(&self, start_beat: f64, end_beat: f64) -> Vec<f64> {
+        if end_beat <= start_beat {
+            return Vec::new();
+        }
+        let mut lines = Vec::new();
+        let (mut bar, _) = self.bar_at_beat(start_beat);
+        loop {
+            let beat = self.beat_at_bar(bar);
+            if beat >= end_beat {
+                break;
+            }
+            if beat >= start_beat {
+                lines.push(beat);
+            }
+            bar += 1;
+        }
+        lines
+    }
+
+    /// Every beat-line position (one per beat unit of the signature in
+    /// effect, e.g. eighth notes under 6/8) in `[start_beat, end_beat)`.
+    pub fn beat_lines_in_range(&self, start_beat: f64, end_beat: f64) -> Vec<f64> {
+        if end_beat <= start_beat {
+            return Vec::new();
+        }
+        let mut lines = Vec::new();
+        for (index, seg) in self.segments.iter().enumerate() {
+            let seg_end_beat = self.segments.get(index + 1).map_or(f64::INFINITY, |next| next.start_beat);
+            let range_start = seg.start_beat.max(start_beat);
+            let range_end = seg_end_beat.min(end_beat);
+            if range_start >= range_end {
+                continue;
+            }
+            let beat_unit = 4.0 / seg.denominator as f64;
+            let mut step = ((range_start - seg.start_beat) / beat_unit).ceil();
+            loop {
+                let beat = seg.start_beat + step * beat_unit;
+                if beat >= range_end {
+                    break;
+                }
+                if beat >= start_beat {
+                    lines.push(beat);
+                }
+                step += 1.0;
+            }
+        }
+        lines
+    }
+
+    /// The time signature (numerator, denominator) in effect at `bar`.
+    pub fn time_signature_at_bar(&self, bar: u64) -> (u32, u32) {
+        let seg = self.segment_for_bar(bar);
+        (seg.numerator, seg.denominator)
+    }
+
+    /// The bar (0-indexed) containing `sample`, and how many beats into that
+    /// bar it is, converting through `tempo_map`.
+    pub fn current_bar_at_sample(&self, tempo_map: &TempoMap, sample: u64) -> (u64, f64) {
+        self.bar_at_beat(tempo_map.beat_at_sample(sample))
+    }
+
+    /// The time signature (numerator, denominator) in effect at `sample`,
+    /// converting through `tempo_map`.
+    pub fn time_signature_at_sample(&self, tempo_map: &TempoMap, sample: u64) -> (u32, u32) {
+        let (bar, _) = self.current_bar_at_sample(tempo_map, sample);
+        self.time_signature_at_bar(bar)
+    }
+
+    /// Every bar-line sample position in `[start_sample, end_sample)`,
+    /// converting through `tempo_map`.
+    pub fn bar_lines_in_sample_range(&self, tempo_map: &TempoMap, start_sample: u64, end_sample: u64) -> Vec<u64> {
+        let start_beat = tempo_map.beat_at_sample(start_sample);
+        let end_beat = tempo_map.beat_at_sample(end_sample);
+        self.bar_lines_in_range(start_beat, end_beat).into_iter().map(|beat| tempo_map.sample_at_beat(beat)).collect()
+    }
+
+    /// Every beat-line sample position in `[start_sample, end_sample)`,
+    /// converting through `tempo_map`.
+    pub fn beat_lines_in_sample_range(&self, tempo_map: &TempoMap, start_sample: u64, end_sample: u64) -> Vec<u64> {
+        let start_beat = tempo_map.beat_at_sample(start_sample);
+        let end_beat = tempo_map.beat_at_sample(end_sample);
+        self.beat_lines_in_range(start_beat, end_beat).into_iter().map(|beat| tempo_map.sample_at_beat(beat)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn four_four_bars_land_every_four_beats() {
+        let map = TimeSignatureMap::new(4, 4);
+        assert_eq!(map.beat_at_bar(0), 0.0);
+        assert_eq!(map.beat_at_bar(1), 4.0);
+        assert_eq!(map.beat_at_bar(3), 12.0);
+    }
+
+    #[test]
+    fn bar_at_beat_inverts_beat_at_bar() {
+        let map = TimeSignatureMap::new(3, 4);
+        assert_eq!(map.bar_at_beat(0.0), (0, 0.0));
+        assert_eq!(map.bar_at_beat(3.0), (1, 0.0));
+        assert_eq!(map.bar_at_beat(4.0), (1, 1.0));
+    }
+
+    #[test]
+    fn a_signature_change_keeps_earlier_bars_unaffected() {
+        let mut map = TimeSignatureMap::new(4, 4);
+        map.push_time_signature_change(2, 3, 4);
+        assert_eq!(map.beat_at_bar(0), 0.0);
+        assert_eq!(map.beat_at_bar(1), 4.0);
+        // Bar 2 still starts at beat 8 (two bars of 4/4 before the change).
+        assert_eq!(map.beat_at_bar(2), 8.0);
+        // But bar 3 is only 3 beats later now that 3/4 is in effect.
+        assert_eq!(map.beat_at_bar(3), 11.0);
+    }
+
+    #[test]
+    fn bar_lines_enumerate_every_bar_start_in_range() {
+        let map = TimeSignatureMap::new(4, 4);
+        assert_eq!(map.bar_lines_in_range(0.0, 13.0), vec![0.0, 4.0, 8.0, 12.0]);
+    }
+
+    #[test]
+    fn beat_lines_enumerate_every_beat_unit_in_range() {
+        let map = TimeSignatureMap::new(4, 4);
+        assert_eq!(map.beat_lines_in_range(0.0, 4.0), vec![0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn beat_lines_respect_a_six_eight_signature() {
+        let map = TimeSignatureMap::new(6, 8);
+        // Eighth-note beat unit is 0.5 quarter-note beats.
+        assert_eq!(map.beat_lines_in_range(0.0, 2.0), vec![0.0, 0.5, 1.0, 1.5]);
+    }
+
+    #[test]
+    fn beat_lines_follow_a_signature_change_across_the_boundary() {
+        let mut map = TimeSignatureMap::new(4, 4);
+        map.push_time_signature_change(1, 6, 8);
+        // Bar 1 starts at beat 4.0; from there the beat unit is an eighth note.
+        assert_eq!(map.beat_lines_in_range(3.0, 5.0), vec![3.0, 4.0, 4.5]);
+    }
+
+    #[test]
+    fn time_signature_at_bar_reflects_changes_across_the_project() {
+        let mut map = TimeSignatureMap::new(4, 4);
+        map.push_time_signature_change(16, 7, 8);
+        assert_eq!(map.time_signature_at_bar(0), (4, 4));
+        assert_eq!(map.time_signature_at_bar(15), (4, 4));
+        assert_eq!(map.time_signature_at_bar(16), (7, 8));
+        assert_eq!(map.time_signature_at_bar(20), (7, 8));
+    }
+
+    #[test]
+    fn current_bar_and_time_signature_at_sample_round_trip_through_the_tempo_map() {
+        let tempo_map = TempoMap::new(48_000.0, 120.0);
+        let mut sig_map = TimeSignatureMap::new(4, 4);
+        sig_map.push_time_signature_change(2, 3, 4);
+
+        // Bar 2 starts at beat 8, i.e. 4 seconds in at 120 BPM.
+        let (bar, beat_in_bar) = sig_map.current_bar_at_sample(&tempo_map, 192_000);
+        assert_eq!(bar, 2);
+        assert!(beat_in_bar.abs() < 1e-9);
+        assert_eq!(sig_map.time_signature_at_sample(&tempo_map, 192_000), (3, 4));
+        assert_eq!(sig_map.time_signature_at_sample(&tempo_map, 0), (4, 4));
+    }
+
+    #[test]
+    fn sample_range_queries_round_trip_through_the_tempo_map() {
+        let tempo_map = TempoMap::new(48_000.0, 120.0);
+        let sig_map = TimeSignatureMap::new(4, 4);
+        // 120 BPM = 2 beats/sec, so a bar (4 beats) is 2 seconds = 96_000 samples.
+        let lines = sig_map.bar_lines_in_sample_range(&tempo_map, 0, 200_000);
+        assert_eq!(lines, vec![0, 96_000, 192_000]);
+    }
+}
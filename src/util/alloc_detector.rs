@@ -0,0 +1,186 @@
+//! Debug-mode realtime-path allocation detector.
+//!
+//! Behind the `alloc-detector` feature, this installs itself as the
+//! process's global allocator and counts heap allocations that happen
+//! while a [`RealtimeGuard`] is active on the current thread. Wrap the
+//! span of a `DSEngineAudioThread::process_*` call or a plugin processor
+//! invocation in a guard during development, and any allocation sneaking
+//! into that path — an easy way to introduce an audio dropout — shows up
+//! immediately instead of as a field report.
+//!
+//! Call sites register themselves once (outside the realtime path, e.g.
+//! at startup) with [`register_site`] to get a [`SiteId`], since the
+//! allocator itself must not allocate to record a violation.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const MAX_SITES: usize = 64;
+
+static SITE_NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static SITE_COUNTS: [AtomicUsize; MAX_SITES] = [const { AtomicUsize::new(0) }; MAX_SITES];
+static PANIC_ON_ALLOCATION: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static ACTIVE_SITE: Cell<Option<SiteId>> = const { Cell::new(None) };
+}
+
+/// Identifies a registered realtime call site, e.g.
+/// `"DSEngineAudioThread::process_block"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiteId(usize);
+
+/// Registers a named realtime call site, returning a [`SiteId`] to enter a
+/// [`RealtimeGuard`] with. Safe to call repeatedly with the same name; it
+/// allocates internally, so only call it outside a guarded realtime span.
+///
+/// # Panics
+///
+/// Panics if more than [`MAX_SITES`] distinct sites are registered.
+pub fn register_site(name: &'static str) -> SiteId {
+    let mut names = SITE_NAMES.lock().unwrap();
+    if let Some(index) = names.iter().position(|&n| n == name) {
+        return SiteId(index);
+    }
+    let index = names.len();
+    assert!(index < MAX_SITES, "alloc_detector: too many registered call sites (max {MAX_SITES})");
+    names.push(name);
+    SiteId(index)
+}
+
+/// Sets whether an allocation detected inside an active [`RealtimeGuard`]
+/// panics immediately (naming the offending site) instead of just being
+/// counted.
+pub fn set_panic_on_allocation(panic: bool) {
+    PANIC_ON_ALLOCATION.store(panic, Ordering::Relaxed);
+}
+
+/// The number of allocations recorded for `site` since the last
+/// [`reset`].
+pub fn violation_count(site: SiteId) -> usize {
+    SITE_COUNTS[site.0].load(Ordering::Relaxed)
+}
+
+/// The name a [`SiteId`] was registered under.
+pub fn site_name(site: SiteId) -> &'static str {
+    SITE_NAMES.lock().unwrap()[site.0]
+}
+
+/// Clears the recorded violation count for every registered site.
+pub fn reset() {
+    for count in &SITE_COUNTS {
+        count.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Marks the current thread as running realtime-critical code for as long
+/// as this guard is alive. Any heap allocation made on this thread while
+/// it's active is recorded against `site` (and panics, if
+/// [`set_panic_on_allocation`] is enabled). Guards nest: dropping an inner
+/// guard restores the outer one's site rather than clearing it.
+pub struct RealtimeGuard {
+    previous: Option<SiteId>,
+}
+
+impl RealtimeGuard {
+    pub fn enter(site: SiteId) -> Self {
+        let previous = ACTIVE_SITE.with(|active| active.replace(Some(site)));
+        Self { previous }
+    }
+}
+
+impl Drop for RealtimeGuard {
+    fn drop(&mut self) {
+        ACTIVE_SITE.with(|active| active.set(self.previous));
+    }
+}
+
+/// The allocator dropseed installs as `#[global_allocator]` behind the
+/// `alloc-detector` feature: forwards every call to [`System`], but first
+/// records (and optionally panics on) allocations made while a
+/// [`RealtimeGuard`] is active on the calling thread.
+pub struct AllocationDetector;
+
+unsafe impl GlobalAlloc for AllocationDetector {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if let Some(site) = ACTIVE_SITE.with(|active| active.get()) {
+            SITE_COUNTS[site.0].fetch_add(1, Ordering::Relaxed);
+            if PANIC_ON_ALLOCATION.load(Ordering::Relaxed) {
+                // Formatting the panic message allocates; clear the active
+                // site first so that allocation isn't attributed (and
+                // doesn't recursively try to panic while already
+                // unwinding, which would abort the process).
+                ACTIVE_SITE.with(|active| active.set(None));
+                panic!("realtime-safety violation: allocation on realtime call site {:?}", site_name(site));
+            }
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if let Some(site) = ACTIVE_SITE.with(|active| active.get()) {
+            SITE_COUNTS[site.0].fetch_add(1, Ordering::Relaxed);
+            if PANIC_ON_ALLOCATION.load(Ordering::Relaxed) {
+                ACTIVE_SITE.with(|active| active.set(None));
+                panic!("realtime-safety violation: reallocation on realtime call site {:?}", site_name(site));
+            }
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entering_a_guard_counts_allocations_made_while_active() {
+        reset();
+        let site = register_site("test::counts_allocations");
+        assert_eq!(violation_count(site), 0);
+        {
+            let _guard = RealtimeGuard::enter(site);
+            let _leak = Box::new([0u8; 128]);
+        }
+        assert!(violation_count(site) > 0);
+    }
+
+    #[test]
+    fn allocations_outside_a_guard_are_not_counted() {
+        reset();
+        let site = register_site("test::outside_guard_not_counted");
+        let _ignored = Box::new([0u8; 128]);
+        assert_eq!(violation_count(site), 0);
+    }
+
+    #[test]
+    fn dropping_a_nested_guard_restores_the_outer_sites_attribution() {
+        reset();
+        let outer = register_site("test::nested_outer");
+        let inner = register_site("test::nested_inner");
+
+        let outer_guard = RealtimeGuard::enter(outer);
+        {
+            let _inner_guard = RealtimeGuard::enter(inner);
+            let _leak = Box::new([0u8; 64]);
+        }
+        assert!(violation_count(inner) > 0);
+        let before_outer_alloc = violation_count(outer);
+        let _leak = Box::new([0u8; 64]);
+        assert!(violation_count(outer) > before_outer_alloc);
+        drop(outer_guard);
+    }
+
+    #[test]
+    fn registering_the_same_name_twice_returns_the_same_site() {
+        let a = register_site("test::same_name");
+        let b = register_site("test::same_name");
+        assert_eq!(a, b);
+    }
+}
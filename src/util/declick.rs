@@ -0,0 +1,152 @@
+//! Declicking across a loop boundary: a short equal-power crossfade between
+//! the tail of a loop and the head of its next repetition, so repeated
+//! playback doesn't introduce an audible click at the seam.
+
+/// Where a sample position sits within an active loop-boundary crossfade.
+///
+/// A node that wants to know whether it's mid-crossfade (e.g. to suppress
+/// its own pitch or filter modulation for the duration of the seam, rather
+/// than fighting the declicker) should read this instead of re-deriving the
+/// ramp itself: `progress` moves from `0.0` at the loop point to `1.0` once
+/// the crossfade completes, and `outgoing_gain`/`incoming_gain` are already
+/// equal-power, so a consumer only needs to scale by them, not recompute
+/// the curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeclickInfo {
+    /// How far into the crossfade this sample is, from `0.0` (just crossed
+    /// the loop point) to `1.0` (crossfade complete).
+    pub progress: f32,
+    /// The gain to apply to the pre-loop-point (outgoing) signal.
+    pub outgoing_gain: f32,
+    /// The gain to apply to the post-loop-point (incoming) signal.
+    pub incoming_gain: f32,
+}
+
+/// Crossfades the outgoing tail and incoming head of a loop across a
+/// configurable number of samples, so the seam doesn't cut over abruptly.
+#[derive(Debug, Clone, Copy)]
+pub struct LoopDeclicker {
+    crossfade_len: usize,
+}
+
+impl LoopDeclicker {
+    /// Creates a declicker that crossfades over `crossfade_len` samples. A
+    /// length of `0` disables declicking entirely.
+    pub fn new(crossfade_len: usize) -> Self {
+        Self { crossfade_len }
+    }
+
+    pub fn crossfade_len(&self) -> usize {
+        self.crossfade_len
+    }
+
+    pub fn set_crossfade_len(&mut self, crossfade_len: usize) {
+        self.crossfade_len = crossfade_len;
+    }
+
+    /// Returns the [`DeclickInfo`] for the sample `samples_since_loop_point`
+    /// after crossing the loop boundary, or `None` once the crossfade has
+    /// finished (or declicking is disabled via a zero-length crossfade).
+    pub fn info_at(&self, samples_since_loop_point: usize) -> Option<DeclickInfo> {
+        if self.crossfade_len == 0 || samples_since_loop_point >= self.crossfade_len {
+            return None;
+        }
+        let progress = samples_since_loop_point as f32 / self.crossfade_len as f32;
+        // Equal-power crossfade so perceived loudness stays constant
+        // through the blend, instead of dipping as a linear fade would.
+        let incoming_gain = (progress * std::f32::consts::FRAC_PI_2).sin();
+        let outgoing_gain = (progress * std::f32::consts::FRAC_PI_2).cos();
+        Some(DeclickInfo { progress, outgoing_gain, incoming_gain })
+    }
+
+    /// Blends `outgoing` (the tail that would have played past the loop
+    /// point) and `incoming` (the head of the next repetition) for the
+    /// sample `samples_since_loop_point` after the loop point. Returns
+    /// `None` once the crossfade is finished, in which case the caller
+    /// should just use `incoming` directly.
+    pub fn process_sample(&self, samples_since_loop_point: usize, outgoing: f32, incoming: f32) -> Option<f32> {
+        self.info_at(samples_since_loop_point).map(|info| outgoing * info.outgoing_gain + incoming * info.incoming_gain)
+    }
+
+    /// Crossfades a whole block in place: `incoming` is blended against
+    /// `outgoing` (the tail that would have continued past the seam)
+    /// sample-by-sample via [`process_sample`](Self::process_sample),
+    /// starting `samples_since_loop_point` samples after the seam. Stops
+    /// blending as soon as either the crossfade finishes or `outgoing`
+    /// runs out, leaving the rest of `incoming` untouched.
+    pub fn apply_to_block(&self, samples_since_loop_point: usize, outgoing: &[f32], incoming: &mut [f32]) {
+        for (i, sample) in incoming.iter_mut().enumerate() {
+            let Some(&outgoing_sample) = outgoing.get(i) else { break };
+            match self.process_sample(samples_since_loop_point + i, outgoing_sample, *sample) {
+                Some(blended) => *sample = blended,
+                None => break,
+            }
+        }
+    }
+}
+
+impl Default for LoopDeclicker {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_zero_length_crossfade_is_disabled() {
+        let declicker = LoopDeclicker::new(0);
+        assert_eq!(declicker.info_at(0), None);
+        assert_eq!(declicker.process_sample(0, 1.0, -1.0), None);
+    }
+
+    #[test]
+    fn the_crossfade_starts_all_outgoing_and_ends_all_incoming() {
+        let declicker = LoopDeclicker::new(4);
+        let start = declicker.info_at(0).unwrap();
+        assert!((start.outgoing_gain - 1.0).abs() < 1e-6);
+        assert!(start.incoming_gain.abs() < 1e-6);
+
+        assert_eq!(declicker.info_at(4), None);
+    }
+
+    #[test]
+    fn apply_to_block_matches_calling_process_sample_one_at_a_time() {
+        let declicker = LoopDeclicker::new(4);
+        let outgoing = [1.0, 0.8, 0.6, 0.4, 0.2];
+        let mut incoming = [-1.0, -1.0, -1.0, -1.0, -1.0];
+        declicker.apply_to_block(0, &outgoing, &mut incoming);
+
+        for i in 0..4 {
+            let expected = declicker.process_sample(i, outgoing[i], -1.0).unwrap();
+            assert!((incoming[i] - expected).abs() < 1e-6);
+        }
+        // Past the crossfade, the incoming sample is left untouched.
+        assert_eq!(incoming[4], -1.0);
+    }
+
+    #[test]
+    fn apply_to_block_stops_at_the_shorter_of_the_two_buffers() {
+        let declicker = LoopDeclicker::new(8);
+        let outgoing = [1.0, 1.0];
+        let mut incoming = [-1.0, -1.0, -1.0, -1.0];
+        declicker.apply_to_block(0, &outgoing, &mut incoming);
+
+        assert_ne!(incoming[0], -1.0);
+        assert_ne!(incoming[1], -1.0);
+        assert_eq!(incoming[2], -1.0, "nothing left in `outgoing` to blend against");
+        assert_eq!(incoming[3], -1.0);
+    }
+
+    #[test]
+    fn equal_power_gains_keep_constant_energy_through_the_blend() {
+        let declicker = LoopDeclicker::new(8);
+        for i in 0..8 {
+            let info = declicker.info_at(i).unwrap();
+            let energy = info.outgoing_gain.powi(2) + info.incoming_gain.powi(2);
+            assert!((energy - 1.0).abs() < 1e-6, "sample {i} had energy {energy}");
+        }
+    }
+}
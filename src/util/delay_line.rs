@@ -0,0 +1,76 @@
+//! A small fixed-length delay buffer for non-realtime bookkeeping (e.g.
+//! delaying GUI feedback values rather than audio).
+
+use std::collections::VecDeque;
+
+/// Delays a stream of values by a fixed number of pushes.
+///
+/// Unlike an audio-rate delay line, this is meant for low-frequency,
+/// main-thread data such as metering or parameter feedback values pushed
+/// once per idle tick.
+#[derive(Debug, Clone)]
+pub struct DelayLine<T> {
+    history: VecDeque<T>,
+    delay: usize,
+}
+
+impl<T> DelayLine<T> {
+    /// Creates a delay line that holds back values by `delay` pushes before
+    /// they are returned from [`DelayLine::push`].
+    pub fn new(delay: usize) -> Self {
+        Self { history: VecDeque::with_capacity(delay + 1), delay }
+    }
+
+    /// The current delay, in number of pushes.
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Changes the delay. Existing buffered history is discarded since it
+    /// no longer corresponds to a meaningful offset.
+    pub fn set_delay(&mut self, delay: usize) {
+        self.delay = delay;
+        self.history.clear();
+    }
+
+    /// Pushes a new value and returns the value that is now `delay` pushes
+    /// old, or `None` if the buffer hasn't filled up yet.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        self.history.push_back(value);
+        if self.history.len() > self.delay {
+            self.history.pop_front()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_delay_passes_through_immediately() {
+        let mut line = DelayLine::new(0);
+        assert_eq!(line.push(1), Some(1));
+    }
+
+    #[test]
+    fn delays_by_the_configured_amount() {
+        let mut line = DelayLine::new(2);
+        assert_eq!(line.push(1), None);
+        assert_eq!(line.push(2), None);
+        assert_eq!(line.push(3), Some(1));
+        assert_eq!(line.push(4), Some(2));
+    }
+
+    #[test]
+    fn changing_delay_resets_history() {
+        let mut line = DelayLine::new(2);
+        line.push(1);
+        line.push(2);
+        line.set_delay(1);
+        assert_eq!(line.push(3), None);
+        assert_eq!(line.push(4), Some(3));
+    }
+}
@@ -0,0 +1,166 @@
+//! Exporting the engine's timed note/CC event representation as a type 0
+//! Standard MIDI File, the complement of [`midi_import`](crate::util::midi_import).
+//!
+//! Events are captured at absolute sample positions (e.g. from a plugin's
+//! note output or a host-provided sequence); this converts each sample
+//! position back to ticks via [`TempoMap::beat_at_sample`] and writes the
+//! tempo map's own segments as `Set Tempo` meta events, so the exported
+//! file carries the same tempo (and any ramps) the events were captured
+//! against.
+
+use crate::transport::TempoMap;
+use crate::util::midi_import::{ImportedEvent, ImportedEventKind};
+
+/// Ticks per quarter note used for every file this module writes.
+pub const TICKS_PER_QUARTER_NOTE: u16 = 480;
+
+/// Writes `events`, sorted by sample and placed in time via `tempo_map`, as
+/// a type 0 Standard MIDI File with `Set Tempo` meta events reconstructed
+/// from `tempo_map`'s own tempo changes at the given sample positions.
+///
+/// `tempo_change_samples` lists the sample position of each tempo change to
+/// emit (typically the `start_sample` a host recorded when it called
+/// [`TempoMap::push_tempo_change`]); sample `0` is always covered even if
+/// absent from the list, so the file always opens with a tempo.
+pub fn export_smf(
+    events: &[ImportedEvent],
+    tempo_map: &TempoMap,
+    tempo_change_samples: &[u64],
+) -> Vec<u8> {
+    let mut track = Vec::new();
+    let mut last_tick = 0u64;
+
+    let mut tempo_samples = tempo_change_samples.to_vec();
+    if !tempo_samples.contains(&0) {
+        tempo_samples.push(0);
+    }
+    tempo_samples.sort_unstable();
+    tempo_samples.dedup();
+
+    let mut marks: Vec<(u64, Mark)> = tempo_samples
+        .into_iter()
+        .map(|sample| (sample, Mark::Tempo(tempo_map.bpm_at_sample(sample))))
+        .collect();
+    for event in events {
+        marks.push((event.sample, Mark::Event(*event)));
+    }
+    marks.sort_by_key(|(sample, _)| *sample);
+
+    for (sample, mark) in marks {
+        let tick = (tempo_map.beat_at_sample(sample) * TICKS_PER_QUARTER_NOTE as f64).round() as u64;
+        let delta = tick.saturating_sub(last_tick);
+        last_tick = tick;
+
+        match mark {
+            Mark::Tempo(bpm) => {
+                let microseconds_per_quarter = (60_000_000.0 / bpm).round() as u32;
+                let bytes = microseconds_per_quarter.to_be_bytes();
+                push_variable_length(&mut track, delta as u32);
+                track.extend([0xff, 0x51, 0x03, bytes[1], bytes[2], bytes[3]]);
+            }
+            Mark::Event(event) => {
+                push_variable_length(&mut track, delta as u32);
+                let status_channel = event.channel & 0x0f;
+                match event.kind {
+                    ImportedEventKind::NoteOn { note, velocity } => {
+                        track.extend([0x90 | status_channel, note, velocity]);
+                    }
+                    ImportedEventKind::NoteOff { note } => {
+                        track.extend([0x80 | status_channel, note, 0]);
+                    }
+                    ImportedEventKind::ControlChange { controller, value } => {
+                        track.extend([0xb0 | status_channel, controller, value]);
+                    }
+                }
+            }
+        }
+    }
+
+    push_variable_length(&mut track, 0);
+    track.extend([0xff, 0x2f, 0x00]);
+
+    let mut data = b"MThd".to_vec();
+    data.extend(6u32.to_be_bytes());
+    data.extend(0u16.to_be_bytes()); // format 0: a single track
+    data.extend(1u16.to_be_bytes());
+    data.extend(TICKS_PER_QUARTER_NOTE.to_be_bytes());
+    data.extend(b"MTrk");
+    data.extend((track.len() as u32).to_be_bytes());
+    data.extend(track);
+    data
+}
+
+enum Mark {
+    Tempo(f64),
+    Event(ImportedEvent),
+}
+
+fn push_variable_length(out: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = vec![(value & 0x7f) as u8];
+    value >>= 7;
+    while value > 0 {
+        bytes.push(((value & 0x7f) as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.reverse();
+    out.extend(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::midi_import::import_smf;
+
+    #[test]
+    fn exporting_then_importing_round_trips_note_events() {
+        let tempo_map = TempoMap::new(48_000.0, 120.0);
+        let events = vec![
+            ImportedEvent { sample: 0, channel: 0, kind: ImportedEventKind::NoteOn { note: 60, velocity: 100 } },
+            ImportedEvent {
+                sample: tempo_map.sample_at_beat(1.0),
+                channel: 0,
+                kind: ImportedEventKind::NoteOff { note: 60 },
+            },
+        ];
+
+        let data = export_smf(&events, &tempo_map, &[]);
+        let imported = import_smf(&data, &tempo_map).unwrap();
+        assert_eq!(imported.events, events);
+    }
+
+    #[test]
+    fn a_tempo_change_is_written_as_a_set_tempo_meta_event() {
+        let mut tempo_map = TempoMap::new(48_000.0, 120.0);
+        tempo_map.push_tempo_change(48_000, 90.0, 0.0);
+
+        let data = export_smf(&[], &tempo_map, &[48_000]);
+        let imported = import_smf(&data, &TempoMap::new(48_000.0, 1.0)).unwrap();
+        assert_eq!(imported.tempo_changes.len(), 2);
+        assert!((imported.tempo_changes[0].1 - 120.0).abs() < 0.01);
+        assert!((imported.tempo_changes[1].1 - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn control_change_events_round_trip() {
+        let tempo_map = TempoMap::new(48_000.0, 120.0);
+        let events = vec![ImportedEvent {
+            sample: 0,
+            channel: 3,
+            kind: ImportedEventKind::ControlChange { controller: 7, value: 64 },
+        }];
+
+        let data = export_smf(&events, &tempo_map, &[]);
+        let imported = import_smf(&data, &tempo_map).unwrap();
+        assert_eq!(imported.events, events);
+    }
+
+    #[test]
+    fn an_exported_file_always_opens_with_a_tempo_event_even_without_one_requested() {
+        let tempo_map = TempoMap::new(48_000.0, 140.0);
+        let data = export_smf(&[], &tempo_map, &[]);
+        let imported = import_smf(&data, &TempoMap::new(48_000.0, 1.0)).unwrap();
+        assert_eq!(imported.tempo_changes.len(), 1);
+        assert_eq!(imported.tempo_changes[0].0, 0.0);
+        assert!((imported.tempo_changes[0].1 - 140.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,12 @@
+//! Small, dependency-free utilities shared across the engine.
+
+#[cfg(feature = "alloc-detector")]
+pub mod alloc_detector;
+pub mod declick;
+pub mod delay_line;
+pub mod midi_export;
+pub mod midi_import;
+pub mod rt_priority;
+pub mod sample_format;
+pub mod smoothed_param;
+pub mod versioned_migrations;
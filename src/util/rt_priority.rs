@@ -0,0 +1,195 @@
+//! Best-effort realtime scheduling priority for audio-critical threads.
+//!
+//! The audio callback thread (and any worker thread the multi-threaded
+//! scheduler spins up to help it) misses its deadline far more often when
+//! it's scheduled like any other thread: a burst of unrelated system load
+//! can delay it long enough to drop a block. [`request_realtime_priority`]
+//! asks the OS for the scheduling treatment real-time audio needs, with a
+//! different mechanism per platform:
+//!
+//! - **Linux**: `SCHED_FIFO` via `sched_setscheduler`.
+//! - **macOS**: a time-constraint Mach thread policy, the same mechanism
+//!   Core Audio's render thread uses (a simpler, longer-standing API than
+//!   the newer `os_workgroup` one, which needs an app's full audio
+//!   workgroup rather than a single borrowed thread).
+//! - **Windows**: `AvSetMmThreadCharacteristicsW("Pro Audio")`, the MMCSS
+//!   class Windows audio drivers expect real-time audio threads to join.
+//!
+//! Every platform's request can fail (insufficient privilege, an unknown
+//! OS, a kernel too old) and [`request_realtime_priority`] reports that as
+//! an [`RtPriorityError`] rather than silently leaving the thread at normal
+//! priority, since a host relying on this to meet its latency budget needs
+//! to know when it didn't take effect.
+
+use std::fmt;
+
+/// Why a [`request_realtime_priority`] call failed.
+#[derive(Debug)]
+pub enum RtPriorityError {
+    /// The calling process lacks permission to raise scheduling priority
+    /// (e.g. no `CAP_SYS_NICE` on Linux, not in the `audio` group).
+    PermissionDenied,
+    /// The underlying OS call reported a failure; the message is the raw
+    /// errno/description, since the exact cause varies by platform.
+    OsError(String),
+    /// Realtime priority isn't implemented for the OS this was built for.
+    UnsupportedPlatform,
+}
+
+impl fmt::Display for RtPriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RtPriorityError::PermissionDenied => {
+                write!(f, "insufficient privilege to request realtime scheduling priority")
+            }
+            RtPriorityError::OsError(e) => write!(f, "OS error requesting realtime priority: {e}"),
+            RtPriorityError::UnsupportedPlatform => {
+                write!(f, "realtime priority is not implemented for this platform")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RtPriorityError {}
+
+/// Requests realtime scheduling priority for the calling thread. Intended
+/// to be called once, from the thread itself, right after it starts (the
+/// host's audio callback thread, or a worker thread in the scheduler's
+/// pool).
+pub fn request_realtime_priority() -> Result<(), RtPriorityError> {
+    platform::request_realtime_priority()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::RtPriorityError;
+
+    #[repr(C)]
+    struct SchedParam {
+        sched_priority: i32,
+    }
+
+    const SCHED_FIFO: i32 = 1;
+
+    extern "C" {
+        fn sched_setscheduler(pid: i32, policy: i32, param: *const SchedParam) -> i32;
+        fn sched_get_priority_max(policy: i32) -> i32;
+        #[link_name = "__errno_location"]
+        fn errno_location() -> *mut i32;
+    }
+
+    pub(super) fn request_realtime_priority() -> Result<(), RtPriorityError> {
+        // SAFETY: `sched_get_priority_max`/`sched_setscheduler` are plain
+        // libc syscalls with no invariants beyond a valid `policy` value,
+        // which `SCHED_FIFO` is; `pid == 0` targets the calling thread.
+        unsafe {
+            let max_priority = sched_get_priority_max(SCHED_FIFO);
+            if max_priority < 0 {
+                return Err(RtPriorityError::OsError(format!("sched_get_priority_max failed (errno {})", *errno_location())));
+            }
+            let param = SchedParam { sched_priority: max_priority };
+            if sched_setscheduler(0, SCHED_FIFO, &param) != 0 {
+                let errno = *errno_location();
+                return Err(match errno {
+                    1 => RtPriorityError::PermissionDenied, // EPERM
+                    _ => RtPriorityError::OsError(format!("sched_setscheduler failed (errno {errno})")),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::RtPriorityError;
+
+    const THREAD_TIME_CONSTRAINT_POLICY: i32 = 2;
+    const THREAD_TIME_CONSTRAINT_POLICY_COUNT: u32 = 4;
+
+    #[repr(C)]
+    struct ThreadTimeConstraintPolicy {
+        period: u32,
+        computation: u32,
+        constraint: u32,
+        preemptible: i32,
+    }
+
+    extern "C" {
+        fn mach_thread_self() -> u32;
+        fn thread_policy_set(thread: u32, flavor: i32, policy_info: *const ThreadTimeConstraintPolicy, count: u32) -> i32;
+    }
+
+    pub(super) fn request_realtime_priority() -> Result<(), RtPriorityError> {
+        // A ~2.9ms period/computation/constraint at a nominal 44.1kHz host,
+        // matching the budget a real-time audio render callback expects;
+        // `preemptible = 1` lets the kernel still preempt for higher-
+        // priority realtime work rather than starving the system.
+        let policy = ThreadTimeConstraintPolicy { period: 128_000, computation: 100_000, constraint: 128_000, preemptible: 1 };
+        // SAFETY: `mach_thread_self` returns a valid port for the calling
+        // thread; `thread_policy_set` is called with a pointer to a
+        // correctly sized, correctly tagged policy struct.
+        let result = unsafe { thread_policy_set(mach_thread_self(), THREAD_TIME_CONSTRAINT_POLICY, &policy, THREAD_TIME_CONSTRAINT_POLICY_COUNT) };
+        if result != 0 {
+            return Err(RtPriorityError::OsError(format!("thread_policy_set failed (kern_return_t {result})")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::RtPriorityError;
+
+    #[link(name = "avrt")]
+    extern "system" {
+        fn AvSetMmThreadCharacteristicsW(task_name: *const u16, task_index: *mut u32) -> *mut std::ffi::c_void;
+    }
+
+    pub(super) fn request_realtime_priority() -> Result<(), RtPriorityError> {
+        let task_name: Vec<u16> = "Pro Audio".encode_utf16().chain(std::iter::once(0)).collect();
+        let mut task_index: u32 = 0;
+        // SAFETY: `task_name` is a valid NUL-terminated UTF-16 string for
+        // the duration of the call, and `task_index` is a valid out
+        // parameter.
+        let handle = unsafe { AvSetMmThreadCharacteristicsW(task_name.as_ptr(), &mut task_index) };
+        if handle.is_null() {
+            return Err(RtPriorityError::OsError("AvSetMmThreadCharacteristicsW returned NULL".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::RtPriorityError;
+
+    pub(super) fn request_realtime_priority() -> Result<(), RtPriorityError> {
+        Err(RtPriorityError::UnsupportedPlatform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_platform_error_message_names_the_platform() {
+        let message = RtPriorityError::UnsupportedPlatform.to_string();
+        assert!(message.contains("not implemented"));
+    }
+
+    #[test]
+    fn permission_denied_has_a_distinct_message_from_a_generic_os_error() {
+        assert_ne!(
+            RtPriorityError::PermissionDenied.to_string(),
+            RtPriorityError::OsError("oops".to_string()).to_string()
+        );
+    }
+
+    // The real `request_realtime_priority` call is exercised by the host in
+    // its own environment rather than here: it mutates process-wide
+    // scheduling state and, on most CI runners, fails with
+    // `PermissionDenied` for lack of `CAP_SYS_NICE`, which isn't something
+    // this crate's test suite should depend on.
+}
@@ -0,0 +1,185 @@
+//! A reusable per-sample parameter smoother, so internal plugins don't each
+//! reimplement zipper-noise-free ramping the way [`GainRamp`](crate::plugin::gain_stage)
+//! does for gain.
+//!
+//! Smoothing time is specified in milliseconds and converted to a sample
+//! count once, at construction or on a sample rate change, rather than
+//! per-block, so tempo-synced or UI-driven parameter changes stay
+//! declick-smooth regardless of block size.
+
+/// How a [`SmoothedParam`] approaches its target value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmoothingKind {
+    /// Constant per-sample step; reaches the target in exactly the
+    /// configured time, then holds.
+    Linear,
+    /// Exponential decay toward the target; fast at first, settling in
+    /// asymptotically. Never exactly reaches the target, so
+    /// [`is_settled`](SmoothedParam::is_settled) uses an epsilon.
+    Exponential,
+}
+
+/// A single smoothed parameter value, stepped one sample at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothedParam {
+    kind: SmoothingKind,
+    sample_rate: f64,
+    smoothing_samples: u32,
+    current: f32,
+    target: f32,
+    linear_step: f32,
+    exp_coeff: f32,
+}
+
+/// Below this distance from the target, an exponential smoother is
+/// considered settled rather than asymptotically creeping forever.
+const SETTLE_EPSILON: f32 = 1e-5;
+
+impl SmoothedParam {
+    /// Creates a smoother starting (and targeting) `initial_value`, with no
+    /// smoothing in progress.
+    pub fn new(kind: SmoothingKind, sample_rate: f64, smoothing_time_ms: f32, initial_value: f32) -> Self {
+        let mut param = Self {
+            kind,
+            sample_rate,
+            smoothing_samples: 1,
+            current: initial_value,
+            target: initial_value,
+            linear_step: 0.0,
+            exp_coeff: 0.0,
+        };
+        param.set_smoothing_time_ms(smoothing_time_ms);
+        param
+    }
+
+    /// Reconfigures the smoothing duration, e.g. after a sample rate
+    /// change. Does not affect the value already in flight.
+    pub fn set_smoothing_time_ms(&mut self, smoothing_time_ms: f32) {
+        self.smoothing_samples = ((smoothing_time_ms.max(0.0) / 1000.0) * self.sample_rate as f32).round().max(1.0) as u32;
+        self.exp_coeff = (-1.0 / self.smoothing_samples as f32).exp();
+        self.retarget();
+    }
+
+    /// Sets a new target value; [`advance`](Self::advance) will ramp toward
+    /// it from wherever the current value is.
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+        self.retarget();
+    }
+
+    fn retarget(&mut self) {
+        if self.kind == SmoothingKind::Linear {
+            self.linear_step = (self.target - self.current) / self.smoothing_samples as f32;
+        }
+    }
+
+    /// Jumps immediately to `value`, skipping any ramp (e.g. on project
+    /// load, where the persisted value shouldn't fade in from a default).
+    pub fn snap_to(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.linear_step = 0.0;
+    }
+
+    /// Advances by one sample and returns the new current value.
+    pub fn advance(&mut self) -> f32 {
+        match self.kind {
+            SmoothingKind::Linear => {
+                if (self.target - self.current).abs() <= self.linear_step.abs().max(f32::EPSILON) {
+                    self.current = self.target;
+                } else {
+                    self.current += self.linear_step;
+                }
+            }
+            SmoothingKind::Exponential => {
+                self.current = self.target + (self.current - self.target) * self.exp_coeff;
+                if (self.target - self.current).abs() < SETTLE_EPSILON {
+                    self.current = self.target;
+                }
+            }
+        }
+        self.current
+    }
+
+    /// Fills `block` with one smoothed value per sample, a convenience for
+    /// plugins that want a whole block's worth of gain/parameter curve at
+    /// once instead of calling [`advance`](Self::advance) in their own loop.
+    pub fn process_block(&mut self, block: &mut [f32]) {
+        for sample in block {
+            *sample = self.advance();
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Whether the current value has reached (or is indistinguishably
+    /// close to, for exponential smoothing) the target.
+    pub fn is_settled(&self) -> bool {
+        self.current == self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_smoothing_reaches_the_target_in_exactly_the_configured_samples() {
+        let mut param = SmoothedParam::new(SmoothingKind::Linear, 100.0, 40.0, 0.0);
+        param.set_target(1.0);
+        // 40ms at 100Hz is exactly 4 samples.
+        for _ in 0..3 {
+            param.advance();
+            assert!(!param.is_settled());
+        }
+        assert_eq!(param.advance(), 1.0);
+        assert!(param.is_settled());
+    }
+
+    #[test]
+    fn exponential_smoothing_moves_fastest_at_the_start() {
+        let mut param = SmoothedParam::new(SmoothingKind::Exponential, 48_000.0, 10.0, 0.0);
+        param.set_target(1.0);
+        let first_step = param.advance() - 0.0;
+        let second_step = param.advance() - first_step;
+        assert!(first_step > second_step, "exponential smoothing should decelerate as it approaches the target");
+    }
+
+    #[test]
+    fn exponential_smoothing_eventually_settles_within_epsilon() {
+        let mut param = SmoothedParam::new(SmoothingKind::Exponential, 48_000.0, 5.0, 0.0);
+        param.set_target(1.0);
+        for _ in 0..48_000 {
+            param.advance();
+        }
+        assert!(param.is_settled());
+    }
+
+    #[test]
+    fn snap_to_skips_the_ramp_entirely() {
+        let mut param = SmoothedParam::new(SmoothingKind::Linear, 48_000.0, 50.0, 0.0);
+        param.set_target(1.0);
+        param.snap_to(0.5);
+        assert_eq!(param.advance(), 0.5);
+        assert!(param.is_settled());
+    }
+
+    #[test]
+    fn process_block_matches_calling_next_one_at_a_time() {
+        let mut a = SmoothedParam::new(SmoothingKind::Linear, 48_000.0, 1.0, 0.0);
+        let mut b = a;
+        a.set_target(1.0);
+        b.set_target(1.0);
+
+        let mut block = vec![0.0; 64];
+        a.process_block(&mut block);
+        let manual: Vec<f32> = (0..64).map(|_| b.advance()).collect();
+        assert_eq!(block, manual);
+    }
+}
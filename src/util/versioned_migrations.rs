@@ -0,0 +1,91 @@
+//! A small generic registry for migrating versioned on-disk/in-memory data
+//! forward step by step, used by both plugin and project save-state formats
+//! so a future format change doesn't silently corrupt or reject old saves.
+
+use std::collections::BTreeMap;
+
+/// Registers one migration function per source version and chains them to
+/// bring data from any older version up to the current one.
+///
+/// Each registered migration takes the data at version `from` and returns
+/// the equivalent data at version `from + 1`; [`migrate`](Self::migrate)
+/// repeatedly applies the next migration until the target version is
+/// reached.
+pub struct MigrationRegistry<T> {
+    steps: BTreeMap<u32, Box<dyn Fn(T) -> T>>,
+}
+
+impl<T> Default for MigrationRegistry<T> {
+    fn default() -> Self {
+        Self { steps: BTreeMap::new() }
+    }
+}
+
+impl<T> MigrationRegistry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a migration from `from_version` to `from_version + 1`.
+    /// Registering a second migration for the same `from_version` replaces
+    /// the first.
+    pub fn register(&mut self, from_version: u32, migrate: impl Fn(T) -> T + 'static) {
+        self.steps.insert(from_version, Box::new(migrate));
+    }
+
+    /// Migrates `data` from `from_version` to `to_version`, applying each
+    /// registered step in order. Returns `Err(from_version)` with the data
+    /// handed back unmigrated if a required step (some version strictly
+    /// between `from_version` and `to_version`) has no registered
+    /// migration.
+    pub fn migrate(&self, mut data: T, from_version: u32, to_version: u32) -> Result<T, MigrationGap> {
+        let mut version = from_version;
+        while version < to_version {
+            match self.steps.get(&version) {
+                Some(step) => {
+                    data = step(data);
+                    version += 1;
+                }
+                None => return Err(MigrationGap { missing_from_version: version }),
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// No migration was registered to advance past `missing_from_version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationGap {
+    pub missing_from_version: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_migrations_across_multiple_versions() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(1, |s: String| format!("{s}+v2"));
+        registry.register(2, |s: String| format!("{s}+v3"));
+
+        let migrated = registry.migrate("base".to_string(), 1, 3).unwrap();
+        assert_eq!(migrated, "base+v2+v3");
+    }
+
+    #[test]
+    fn already_current_data_passes_through_unchanged() {
+        let registry: MigrationRegistry<String> = MigrationRegistry::new();
+        let migrated = registry.migrate("base".to_string(), 3, 3).unwrap();
+        assert_eq!(migrated, "base");
+    }
+
+    #[test]
+    fn a_missing_step_reports_the_gap_instead_of_panicking() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(1, |s: String| format!("{s}+v2"));
+
+        let err = registry.migrate("base".to_string(), 1, 3).unwrap_err();
+        assert_eq!(err.missing_from_version, 2);
+    }
+}
@@ -0,0 +1,74 @@
+//! Integration tests for [`LoopDeclicker`] rendering across a loop
+//! boundary: the click energy of the declicked output should stay well
+//! below that of an abrupt, undeclicked cut at the seam.
+
+use dropseed::engine::DSEngineMainThread;
+use dropseed::util::declick::LoopDeclicker;
+
+/// A simple click-energy proxy: the sum of squared sample-to-sample
+/// differences. A sharp discontinuity dominates this sum; a smooth
+/// crossfade spreads the same total change out and keeps it low.
+fn click_energy(samples: &[f32]) -> f32 {
+    samples.windows(2).map(|pair| (pair[1] - pair[0]).powi(2)).sum()
+}
+
+/// Renders one loop boundary: `crossfade_len` samples of the outgoing tail
+/// ramping toward 1.0, crossfaded against an incoming head that starts
+/// flat at -1.0 (a sharp discontinuity if left undeclicked).
+fn render_declicked_boundary(declicker: &LoopDeclicker, crossfade_len: usize) -> Vec<f32> {
+    (0..crossfade_len)
+        .map(|i| {
+            let outgoing = i as f32 / crossfade_len as f32;
+            let incoming = -1.0_f32;
+            declicker.process_sample(i, outgoing, incoming).expect("crossfade should still be active")
+        })
+        .collect()
+}
+
+#[test]
+fn crossfading_a_loop_boundary_keeps_click_energy_low() {
+    let crossfade_len = 32;
+    let declicker = LoopDeclicker::new(crossfade_len);
+
+    let declicked = render_declicked_boundary(&declicker, crossfade_len);
+    let declicked_energy = click_energy(&declicked);
+
+    // An abrupt, undeclicked cut jumps straight from the last outgoing
+    // sample to the incoming value in one step.
+    let abrupt_cut_energy = (-1.0_f32 - (crossfade_len - 1) as f32 / crossfade_len as f32).powi(2);
+
+    assert!(
+        declicked_energy < abrupt_cut_energy * 0.1,
+        "declicked energy {declicked_energy} should be far below an abrupt cut's {abrupt_cut_energy}"
+    );
+}
+
+#[test]
+fn a_longer_crossfade_further_reduces_click_energy() {
+    let short = LoopDeclicker::new(8);
+    let long = LoopDeclicker::new(64);
+
+    let short_energy = click_energy(&render_declicked_boundary(&short, 8));
+    let long_energy = click_energy(&render_declicked_boundary(&long, 64));
+
+    assert!(long_energy < short_energy, "longer crossfade ({long_energy}) should click less than a shorter one ({short_energy})");
+}
+
+#[test]
+fn engine_output_declick_is_disabled_by_default_and_configurable() {
+    let mut engine = DSEngineMainThread::new();
+    assert_eq!(engine.output_declick().crossfade_len(), 0);
+
+    engine.set_output_declick_len(256);
+    assert_eq!(engine.output_declick().crossfade_len(), 256);
+}
+
+#[test]
+fn a_requested_declick_is_reported_once_and_then_cleared() {
+    let mut engine = DSEngineMainThread::new();
+    assert!(!engine.take_pending_declick(), "nothing requested yet");
+
+    engine.request_declick();
+    assert!(engine.take_pending_declick(), "a seek or loop should arm the next block's crossfade");
+    assert!(!engine.take_pending_declick(), "consuming the request should clear it");
+}